@@ -7,7 +7,7 @@ fn main() {
     cc::Build::new().file("src/stfle.c").compile("stfle");
     #[cfg(not(target_arch = "s390x"))]
     {
-        println!("cargo:warning=cpacfinfo will have no functionality on non s390x architectures!");
+        println!("cargo:warning=cpacfinfo can only analyze snapshots on non s390x architectures!");
         cc::Build::new().file("src/noop.c").compile("stfle");
     }
     println!("cargo:rerun-if-changed=src/stfle.c")
@@ -3,8 +3,9 @@
 // Copyright IBM Corp. 2024
 
 use clap::Parser;
+use std::path::PathBuf;
 
-use crate::msa::InstructionKind;
+use cpacfinfo::InstructionKind;
 
 #[derive(Clone, PartialEq, clap::ValueEnum, Default)]
 pub enum Format {
@@ -70,6 +71,68 @@ pub struct Cli {
     ///
     /// Default is human to produce human readable output. When set to json will produce json
     /// output.
-    #[arg(long, value_enum, default_value_t)]
+    #[arg(long, value_enum, default_value_t, global = true)]
     pub format: Format,
+
+    /// Reads the CPACF information from a snapshot file instead of the running system
+    ///
+    /// The snapshot must be created with --save-snapshot. This allows to analyze the CPACF
+    /// capabilities of another system, also on non-s390x architectures.
+    #[arg(long, value_name = "FILE")]
+    pub from_snapshot: Option<PathBuf>,
+
+    /// Saves the CPACF information of the running system to a snapshot file and exits
+    ///
+    /// The snapshot contains the STFLE bits and the query and Query Authentication Information
+    /// parameter blocks of all instructions.
+    #[arg(long, value_name = "FILE", conflicts_with("from_snapshot"))]
+    pub save_snapshot: Option<PathBuf>,
+
+    /// Verifies the Query Authentication Information against known firmware levels
+    ///
+    /// Compares IFCL version and hash of every available instruction with the known-good
+    /// firmware levels listed in FILE. Lists the instructions with unknown or mismatching
+    /// firmware levels and exits with 1 if any. Can be combined with -i/--instructions.
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all(["msa", "functions", "save_snapshot"])
+    )]
+    pub verify_qai: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(clap::Subcommand)]
+pub enum Command {
+    /// Checks that the system provides the required CPACF functions
+    ///
+    /// Lists the required functions that are missing or unknown and exits with 1 if any
+    /// requirement is not satisfied. Use --format json to get the evaluation of every
+    /// requirement.
+    Require(RequireArgs),
+}
+
+#[derive(clap::Args)]
+#[command(group(clap::ArgGroup::new("reqs").required(true).multiple(true)))]
+pub struct RequireArgs {
+    /// Required functions, separated by ","
+    ///
+    /// A function is specified as INS:FUNCTION. FUNCTION is the function name with or without
+    /// the instruction prefix (e.g. KMA:GCM-AES-256) or the function code (e.g. KMA:20).
+    #[arg(
+        long,
+        value_name = "INS:FUNCTION",
+        value_delimiter = ',',
+        group = "reqs"
+    )]
+    pub functions: Vec<String>,
+
+    /// Reads the required functions from a file
+    ///
+    /// The file lists the functions in the same format as --functions, separated by "," or
+    /// whitespace. Everything after "#" is a comment.
+    #[arg(long, value_name = "FILE", group = "reqs")]
+    pub requirements: Option<PathBuf>,
 }
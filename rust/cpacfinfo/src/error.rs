@@ -0,0 +1,124 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2024
+
+use std::path::PathBuf;
+
+use crate::msa::InstructionKind;
+
+/// Result type for this crate
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Error cases for this crate
+#[allow(missing_docs)]
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum Error {
+    #[error(
+        "There seems to be an insufficient kernel level running (sysfs interface {0} is missing)"
+    )]
+    SysfsMissing(&'static str),
+
+    #[error("Cannot read the query information of the {0} instruction")]
+    Query(InstructionKind, #[source] std::io::Error),
+
+    #[error("Cannot read snapshot {}", .path.display())]
+    SnapshotRead {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Cannot write snapshot {}", .path.display())]
+    SnapshotWrite {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid snapshot {}", .path.display())]
+    SnapshotParse {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+
+    #[error("Unsupported snapshot version {0}")]
+    UnsupportedSnapshotVersion(u32),
+
+    #[error("Invalid STFLE bits in snapshot")]
+    InvalidSnapshotStfle,
+
+    #[error("Cannot read reference file {}", .path.display())]
+    ReferenceRead {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid entry in line {line} of the reference file: {reason}")]
+    InvalidReference { line: usize, reason: String },
+}
+
+/// Origin of the CPACF information
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InfoSource {
+    /// sysfs of the running system
+    Sysfs,
+    /// A snapshot of a system
+    Snapshot,
+}
+
+impl std::fmt::Display for InfoSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Sysfs => write!(f, "sysfs"),
+            Self::Snapshot => write!(f, "the snapshot"),
+        }
+    }
+}
+
+/// Non-fatal issues found while gathering the CPACF information
+///
+/// The information is still usable but might be incomplete.
+#[allow(missing_docs)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Warning {
+    StfleUnavailable,
+    StflePartial,
+    NoQueryInfo {
+        kind: InstructionKind,
+        source: InfoSource,
+    },
+    NoQai {
+        kind: InstructionKind,
+        source: InfoSource,
+    },
+    UnknownQaiFormat {
+        kind: InstructionKind,
+        format: u8,
+    },
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::StfleUnavailable => write!(
+                f,
+                "Unable to fetch STFLE which is only available on s390x architecture"
+            ),
+            Self::StflePartial => {
+                write!(f, "Partial read of STFLE, information might be incomplete")
+            }
+            Self::NoQueryInfo { kind, source } => write!(
+                f,
+                "Not able to retrieve subfunction information from {source} for {kind} instruction"
+            ),
+            Self::NoQai { kind, source } => write!(
+                f,
+                "Not able to retrieve Query Authentication Information from {source} for {kind} instruction"
+            ),
+            Self::UnknownQaiFormat { kind, format } => write!(
+                f,
+                "format {format} in query authentication information of instruction {kind} is UNKNOWN"
+            ),
+        }
+    }
+}
@@ -0,0 +1,258 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2024
+
+use std::io::{self, ErrorKind};
+
+use crate::msa::*;
+use crate::query::*;
+use crate::snapshot::Snapshot;
+use crate::stfle::Stfle;
+use crate::{Error, InfoSource, Result, Warning};
+
+/// CPACF information of a system
+///
+/// Contains all instructions and functions known by cpacfinfo, whether they are available, the
+/// Query Authentication Information of the instructions and the MSA levels of the system.
+#[derive(Debug)]
+pub struct CpacfInfo {
+    instructions: Vec<Instruction>,
+    levels: Vec<MsaLevel>,
+    warnings: Vec<Warning>,
+}
+
+impl CpacfInfo {
+    /// Gather the CPACF information of the running system
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the cpacf sysfs interface is not available or the
+    /// query information cannot be read.
+    pub fn probe() -> Result<Self> {
+        let mut warnings = Vec::new();
+        let stfle = Stfle::new(&mut warnings);
+        if !check_sysfs() {
+            return Err(Error::SysfsMissing(SYSFS_PATH));
+        }
+        Self::evaluate(&stfle, query, InfoSource::Sysfs, warnings)
+    }
+
+    /// Gather the CPACF information of the system captured in `snapshot`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the snapshot is invalid.
+    pub fn from_snapshot(snapshot: &Snapshot) -> Result<Self> {
+        snapshot.check()?;
+        let stfle = snapshot.stfle().ok_or(Error::InvalidSnapshotStfle)?;
+        Self::evaluate(
+            &stfle,
+            |kind, fc| snapshot.query(kind, fc),
+            InfoSource::Snapshot,
+            Vec::new(),
+        )
+    }
+
+    /// Capture the raw CPACF information of the running system for later analysis
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the cpacf sysfs interface is not available or the
+    /// query information cannot be read.
+    pub fn capture_snapshot() -> Result<Snapshot> {
+        let stfle = Stfle::new(&mut Vec::new());
+        if !check_sysfs() {
+            return Err(Error::SysfsMissing(SYSFS_PATH));
+        }
+        let mut instructions = Vec::new();
+        init_instructions(&mut instructions);
+        Snapshot::capture(&stfle, &instructions)
+    }
+
+    fn evaluate<Q>(
+        stfle: &Stfle,
+        query: Q,
+        source: InfoSource,
+        mut warnings: Vec<Warning>,
+    ) -> Result<Self>
+    where
+        Q: Fn(&InstructionKind, u8) -> io::Result<Param>,
+    {
+        let mut instructions = Vec::new();
+        init_instructions(&mut instructions);
+
+        // run query function (fc 0) for every instruction to check available functions
+        for ins in &mut instructions {
+            if !stfle.check_bit_in_stfle(ins.info.stfle_bit) {
+                continue;
+            }
+            ins.info.available = true;
+
+            // run query; save result in param
+            let mut param = match query(&ins.kind, QUERY_FUNCTION_CODE) {
+                Ok(pb) => pb,
+                Err(e) if e.kind() == ErrorKind::NotFound => {
+                    warnings.push(Warning::NoQueryInfo {
+                        kind: ins.kind,
+                        source,
+                    });
+                    continue;
+                }
+                Err(e) => return Err(Error::Query(ins.kind, e)),
+            };
+
+            // check if bit for functions of current instruction is set in param
+            for func in &mut ins.funcs {
+                if !param.check_bit_in_param(func.function_code as usize) {
+                    continue;
+                }
+
+                func.available = true;
+
+                // unset the bit in param to later see if any unsupported functions may be available
+                param.unset_bit_in_param(func.function_code);
+
+                // check if qai is available
+                if func.function_code == QAI_FUNCTION_CODE {
+                    ins.info.qai_available = true;
+                }
+            }
+
+            // every bit in param that is still 1 is an unsupported function; add it to the
+            // instruction as available UNKNOWN function
+            for i in 0..NUMBER_FUNC_CODES {
+                if !param.check_bit_in_param(i) {
+                    continue;
+                }
+                let mut func = Function::new(i as u8, Msa::UNKNOWN, "UNKNOWN");
+                func.available = true;
+                ins.add(func);
+            }
+
+            // if query authentication information (fc 127) available run query authentication
+            // information
+            if ins.info.qai_available {
+                let param = match query(&ins.kind, QAI_FUNCTION_CODE) {
+                    Ok(pb) => pb,
+                    Err(e) if e.kind() == ErrorKind::NotFound => {
+                        warnings.push(Warning::NoQai {
+                            kind: ins.kind,
+                            source,
+                        });
+                        ins.info.qai_available = false;
+                        continue;
+                    }
+                    Err(e) => return Err(Error::Query(ins.kind, e)),
+                };
+
+                // parse qai information into QueryAuthInfo struct
+                if !param.parse_qai_based_on_format(&mut ins.info.qai) {
+                    warnings.push(Warning::UnknownQaiFormat {
+                        kind: ins.kind,
+                        format: ins.info.qai.format,
+                    });
+                }
+            }
+        }
+
+        // a level is available if its STFLE bit is set or any function it introduced is
+        let levels = (0..MSA_LEVEL_COUNT)
+            .map(|lvl| {
+                let msa = num2msa(lvl).expect("programming error");
+                let mut level = MsaLevel::new(msa.clone(), msa2stfle(&msa));
+                level.update_function_count(&instructions, &[]);
+                level.enabled = level
+                    .stfle_bit
+                    .is_some_and(|bit| stfle.check_bit_in_stfle(bit))
+                    || level.available_functions > 0;
+                level
+            })
+            .collect();
+
+        Ok(Self {
+            instructions,
+            levels,
+            warnings,
+        })
+    }
+
+    /// All instructions known by cpacfinfo
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    /// The instruction `kind`
+    pub fn instruction(&self, kind: InstructionKind) -> Option<&Instruction> {
+        self.instructions.iter().find(|i| i.kind == kind)
+    }
+
+    /// Check whether the function with function code `fc` of instruction `kind` is available
+    pub fn is_available(&self, kind: InstructionKind, fc: u8) -> bool {
+        self.instruction(kind)
+            .and_then(|i| i.funcs.iter().find(|f| f.function_code == fc))
+            .is_some_and(|f| f.available)
+    }
+
+    /// All MSA levels
+    pub fn levels(&self) -> &[MsaLevel] {
+        &self.levels
+    }
+
+    /// Issues found while gathering the information
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Restrict the function counts of the MSA levels to the instructions in `filter`
+    ///
+    /// Only affects the human readable representation of the levels. An empty filter selects
+    /// all instructions.
+    pub fn set_instruction_filter(&mut self, filter: &[InstructionKind]) {
+        for lvl in &mut self.levels {
+            lvl.update_function_count(&self.instructions, filter);
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_from_snapshot() {
+    // STFLE bits 17 (MSA) and 146 (MSA 8); KMA provides query (0) and GCM-AES-256 (20)
+    let snapshot: Snapshot = serde_json::from_str(&format!(
+        r#"{{"version": 1, "stfle": ["0x0000400000000000", "0x0", "0x0000200000000000"],
+            "query": {{"kma": "0x800008{}", "km": "0x80{}"}}, "qai": {{}}}}"#,
+        "00".repeat(13),
+        "00".repeat(15)
+    ))
+    .unwrap();
+    let mut info = CpacfInfo::from_snapshot(&snapshot).unwrap();
+
+    assert!(info.is_available(InstructionKind::KMA, 20));
+    assert!(!info.is_available(InstructionKind::KMA, 18));
+    assert!(info.is_available(InstructionKind::KM, 0));
+    assert!(
+        !info
+            .instruction(InstructionKind::KDSA)
+            .unwrap()
+            .info
+            .available
+    );
+    // KIMD is enabled by STFLE bit 17 but not part of the snapshot
+    let warning = Warning::NoQueryInfo {
+        kind: InstructionKind::KIMD,
+        source: InfoSource::Snapshot,
+    };
+    assert!(info.warnings().contains(&warning));
+    assert!(warning.to_string().contains("from the snapshot"));
+
+    let msa8 = info
+        .levels()
+        .iter()
+        .find(|l| l.msa_level == Msa::MSA8)
+        .unwrap();
+    assert!(msa8.enabled);
+    assert_eq!(msa8.available_functions, 2);
+
+    info.set_instruction_filter(&[InstructionKind::KM]);
+    assert!(info.levels()[0].to_string().contains("(  1 /"));
+}
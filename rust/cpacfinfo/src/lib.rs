@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2024
+
+//! # Library for CP Assist for Cryptographic Functions (CPACF) information
+//!
+//! This crate provides the information about the CPACF instructions, their functions, and the
+//! Message Security Assist (MSA) levels of a system, as shown by the `cpacfinfo` tool.
+//!
+//! [`CpacfInfo::probe`] gathers the information of the running system,
+//! [`CpacfInfo::from_snapshot`] the information of a system captured with
+//! [`CpacfInfo::capture_snapshot`].
+//!
+//! [`QaiReference`] verifies the Query Authentication Information of the instructions against
+//! known-good firmware levels.
+//!
+//! ```no_run
+//! use cpacfinfo::{CpacfInfo, InstructionKind};
+//!
+//! let info = CpacfInfo::probe()?;
+//! // KMA-GCM-AES-256
+//! if info.is_available(InstructionKind::KMA, 20) {
+//!     println!("AES-256-GCM is accelerated");
+//! }
+//! # Ok::<(), cpacfinfo::Error>(())
+//! ```
+
+mod error;
+mod info;
+mod msa;
+mod query;
+mod reference;
+mod snapshot;
+mod stfle;
+
+pub use error::{Error, InfoSource, Result, Warning};
+pub use info::CpacfInfo;
+pub use msa::{
+    Function, Instruction, InstructionInfo, InstructionKind, Msa, MsaLevel, QueryAuthInfo,
+};
+pub use reference::{QaiReference, QaiReport, QaiStatus, QaiVerification};
+pub use snapshot::Snapshot;
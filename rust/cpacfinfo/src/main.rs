@@ -3,24 +3,19 @@
 // Copyright IBM Corp. 2024
 
 mod cli;
-mod msa;
-mod query;
-mod stfle;
+mod require;
 
-use anyhow::bail;
 use anyhow::Result as anyhowRes;
-use clap::Parser;
-use std::io::ErrorKind;
+use clap::{CommandFactory, Parser};
+use cpacfinfo::{CpacfInfo, Error, Instruction, MsaLevel, QaiReference, QaiStatus, Snapshot};
+use std::path::Path;
 use std::result::Result::Ok;
 use utils::print_version;
 
-use crate::cli::{Cli, Format};
-use crate::msa::*;
-use crate::query::*;
-use crate::stfle::*;
+use crate::cli::{Cli, Command, Format, RequireArgs};
 
 /// producing -m/--msa output
-fn out_msa(args: &Cli, levels: &Vec<MsaLevel>, instructions: &Vec<Instruction>) {
+fn out_msa(args: &Cli, levels: &[MsaLevel], instructions: &[Instruction]) {
     // produce json output
     if args.format == Format::Json {
         println!("{}", serde_json::to_string(levels).unwrap());
@@ -70,7 +65,7 @@ fn out_msa(args: &Cli, levels: &Vec<MsaLevel>, instructions: &Vec<Instruction>)
 }
 
 /// produces output for all cpacfinfo commands that do not contain the -m/--msa flag
-fn out_instructions(args: &Cli, instructions: &Vec<Instruction>) {
+fn out_instructions(args: &Cli, instructions: &[Instruction]) {
     // produce json output
     if args.format == Format::Json {
         println!("{}", serde_json::to_string(instructions).unwrap());
@@ -107,6 +102,76 @@ fn out_instructions(args: &Cli, instructions: &Vec<Instruction>) {
     }
 }
 
+/// evaluates the requirements of the `require` command and exits with 1 if one is not satisfied
+fn out_require(args: &Cli, req: &RequireArgs, instructions: &[Instruction]) -> anyhowRes<()> {
+    let mut requirements = req.functions.clone();
+    if let Some(path) = &req.requirements {
+        requirements.extend(require::read_requirements(path)?);
+    }
+
+    let report = require::evaluate(&requirements, instructions);
+    match args.format {
+        Format::Json => println!("{}", serde_json::to_string(&report)?),
+        Format::Human => require::print_human(&report),
+    }
+
+    if !report.satisfied() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// verifies the query authentication information and exits with 1 if it is not verified
+fn out_verify_qai(args: &Cli, path: &Path, instructions: &[Instruction]) -> anyhowRes<()> {
+    let reference = QaiReference::read(path)?;
+    let report = reference.verify(
+        instructions
+            .iter()
+            .filter(|ins| args.instructions.is_empty() || args.instructions.contains(&ins.kind)),
+    );
+
+    match args.format {
+        Format::Json => println!("{}", serde_json::to_string(&report)?),
+        Format::Human => {
+            for v in &report.instructions {
+                let version = v.version.unwrap_or_default();
+                match v.status {
+                    QaiStatus::Verified => match &v.level {
+                        Some(level) => println!("{}: IFCL version {version} verified ({level})", v.instruction),
+                        None => println!("{}: IFCL version {version} verified", v.instruction),
+                    },
+                    QaiStatus::Mismatch => println!(
+                        "{}: IFCL version {version} with hash {:#} does not match any known firmware level",
+                        v.instruction,
+                        utils::HexSlice::from(&v.hash)
+                    ),
+                    QaiStatus::NoReference => println!(
+                        "{}: IFCL version {version} cannot be verified (no reference for this instruction)",
+                        v.instruction
+                    ),
+                    QaiStatus::UnsupportedFormat => println!(
+                        "{}: Query Authentication Information has an unknown format",
+                        v.instruction
+                    ),
+                    QaiStatus::Unavailable => println!(
+                        "{}: Query Authentication Information not available",
+                        v.instruction
+                    ),
+                }
+            }
+            match report.verified {
+                true => println!("The firmware of all instructions is verified"),
+                false => println!("The firmware of some instructions could not be verified"),
+            }
+        }
+    }
+
+    if !report.verified {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
 fn main() -> anyhowRes<()> {
     /* ---- PARSE COMMAND LINE ARGUMENTS ---- */
     let args: Cli = Cli::parse();
@@ -116,139 +181,58 @@ fn main() -> anyhowRes<()> {
         print_version!("2024");
         return Ok(());
     }
-
-    /* ---- SET CONSTANTS ---- */
-    let mut instructions = Vec::new();
-    init_instructions(&mut instructions);
-
-    let mut levels = Vec::new();
-    for lvl in 0..MSA_LEVEL_COUNT {
-        let temp = match num2msa(lvl) {
-            Some(l) => l,
-            None => panic!("programming error"),
-        };
-        let stfle_bit = msa2stfle(&temp);
-        levels.push(MsaLevel::new(temp, stfle_bit));
-        let idx_of_last_element = levels.len() - 1;
-        update_msa_function_count(&args, &mut levels[idx_of_last_element], &instructions);
+    if args.verify_qai.is_some() && args.command.is_some() {
+        Cli::command()
+            .error(
+                clap::error::ErrorKind::ArgumentConflict,
+                "--verify-qai cannot be used with a command",
+            )
+            .exit();
     }
 
     /* ---- GET INFORMATION ---- */
-    // get stfle bits
-    let stfle_bits = Stfle::new()?;
-
-    // check stfle bits for available MSA levels
-    for lvl in &mut levels {
-        match lvl.stfle_bit {
-            Some(bit) => lvl.enabled = stfle_bits.check_bit_in_stfle(bit),
-            None => continue,
+    // save the raw information of the running system for later analysis
+    let info = match (&args.save_snapshot, &args.from_snapshot) {
+        (Some(path), _) => CpacfInfo::capture_snapshot()
+            .and_then(|s| s.write(path))
+            .map(|_| None),
+        // read a snapshot of another system instead of the running one
+        (_, Some(path)) => Snapshot::read(path)
+            .and_then(|s| CpacfInfo::from_snapshot(&s))
+            .map(Some),
+        _ => CpacfInfo::probe().map(Some),
+    };
+    let mut info = match info {
+        Ok(Some(info)) => info,
+        Ok(None) => return Ok(()),
+        Err(e @ Error::SysfsMissing(_)) => {
+            println!(
+                "Warning: {e}\nNo information can be fetched from sysfs, application exits early."
+            );
+            // requirements cannot be satisfied without information about the functions
+            if matches!(args.command, Some(Command::Require(_))) || args.verify_qai.is_some() {
+                std::process::exit(1);
+            }
+            return Ok(());
         }
+        Err(e) => return Err(e.into()),
+    };
+    for warning in info.warnings() {
+        println!("Warning: {warning}");
     }
+    info.set_instruction_filter(&args.instructions);
 
-    // check if SYSFS_PATH is available
-    match check_sysfs() {
-        true => (),
-        false => return Ok(()),
+    /* ---- OUTPUT ---- */
+    if let Some(Command::Require(req)) = &args.command {
+        return out_require(&args, req, info.instructions());
     }
-
-    // run query function (fc 0) for every instruction to check available functions
-    for ins in &mut instructions {
-        if stfle_bits.check_bit_in_stfle(ins.info.stfle_bit) {
-            ins.info.available = true;
-
-            // run query; save result in param
-            let mut param = match query(&ins.kind, QUERY_FUNCTION_CODE) {
-                Ok(pb) => match pb {
-                    Param::QueryParam(_) => pb,
-                    Param::QaiParam(_) => panic!("programming error"),
-                },
-                Err(e) => match e.kind() {
-                    ErrorKind::NotFound => {
-                        println!("Warning: Not able to retrieve subfunction information from sysfs for {ins} instruction");
-                        continue;
-                    }
-                    _ => return Err(e.into()),
-                },
-            };
-
-            // check if bit for functions of current instruction is set in param
-            for func in &mut ins.funcs {
-                if !param.check_bit_in_param(func.function_code as usize) {
-                    continue;
-                }
-
-                func.available = true;
-
-                // unset the bit in param to later see if any unsupported functions may be available
-                param.unset_bit_in_param(func.function_code);
-
-                // check if qai is available
-                if func.function_code == QAI_FUNCTION_CODE {
-                    ins.info.qai_available = true;
-                }
-
-                // sync MsaLevel struct
-                for lvl in &mut levels {
-                    if lvl.msa_level == func.msa {
-                        lvl.enabled = true;
-                        lvl.available_functions += 1;
-                        if args.instructions.is_empty() || args.instructions.contains(&ins.kind) {
-                            lvl.dynamic_available_functions += 1;
-                        }
-                        break;
-                    }
-                }
-            }
-
-            // look for any unsupported functions that my be available
-            for i in 0..NUMBER_FUNC_CODES {
-                // every bit in param that is 1 is an unsupported function
-                if !param.check_bit_in_param(i) {
-                    continue;
-                }
-
-                // add function to instruction as UNKNOWN
-                Instruction::add(ins, Function::new(i as u8, Msa::UNKNOWN, "UNKNOWN"));
-
-                // set function as available
-                match ins.funcs.last_mut() {
-                    Some(ret) => ret.available = true,
-                    None => panic!("programming error"),
-                }
-            }
-
-            // if query authentication information (fc 127) available run query authentication
-            // information
-            if ins.info.qai_available {
-                // get qai from sysfs
-                let param = match query(&ins.kind, QAI_FUNCTION_CODE) {
-                    Ok(pb) => match pb {
-                        Param::QueryParam(_) => panic!("programming error"),
-                        Param::QaiParam(_) => pb,
-                    },
-                    Err(e) => match e.kind() {
-                        ErrorKind::NotFound => {
-                            println!("Warning: Not able to retrieve Query Authentication Information from sysfs for {ins} instruction");
-                            continue;
-                        }
-                        _ => return Err(e.into()),
-                    },
-                };
-
-                // parse qai information into QueryAuthInfo struct
-                match param.parse_qai_based_on_format(&mut ins.info.qai) {
-                    Ok(true) => (),
-                    Ok(false) => println!("WARNING: format {} in query authentication information of instruction {} is UNKNOWN", ins.info.qai.format, ins.kind),
-                    Err(e) => bail!(e.to_string()),
-                }
-            }
-        }
+    if let Some(path) = &args.verify_qai {
+        return out_verify_qai(&args, path, info.instructions());
     }
 
-    /* ---- OUTPUT ---- */
     match args.msa {
-        true => out_msa(&args, &levels, &instructions),
-        false => out_instructions(&args, &instructions),
+        true => out_msa(&args, info.levels(), info.instructions()),
+        false => out_instructions(&args, info.instructions()),
     }
     Ok(())
 }
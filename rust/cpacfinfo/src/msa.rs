@@ -2,11 +2,12 @@
 //
 // Copyright IBM Corp. 2024
 
-use crate::cli::Cli;
 use core::fmt::{Display, Formatter, Result};
 use serde::{Serialize, Serializer};
 use utils::HexSlice;
 
+use crate::query::qai_format_supported;
+
 /// Number of total function codes (0 to 127)
 pub const NUMBER_FUNC_CODES: usize = 128;
 
@@ -14,7 +15,7 @@ pub const NUMBER_FUNC_CODES: usize = 128;
 pub const MSA_LEVEL_COUNT: u8 = 14;
 
 /// enum of all supported instructions
-#[derive(PartialEq, Clone, clap::ValueEnum, Serialize)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, clap::ValueEnum, Serialize)]
 #[allow(clippy::upper_case_acronyms)]
 pub enum InstructionKind {
     /// introduced with MSA
@@ -46,7 +47,7 @@ pub enum InstructionKind {
 }
 
 /// enum of all MSA levels
-#[derive(Clone, Default, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 #[allow(clippy::upper_case_acronyms)]
 pub enum Msa {
     MSA,
@@ -135,15 +136,17 @@ impl Display for InstructionKind {
     }
 }
 
-#[derive(Serialize, Default)]
+/// A MSA level and the number of functions it introduced
+#[derive(Debug, Serialize, Default)]
 pub struct MsaLevel {
     pub msa_level: Msa,
-    total_functions: u8,
+    pub total_functions: u8,
     pub available_functions: u8,
+    // counts restricted to the instructions selected with CpacfInfo::set_instruction_filter
     #[serde(skip)]
     dynamic_total_functions: u8,
     #[serde(skip)]
-    pub dynamic_available_functions: u8,
+    dynamic_available_functions: u8,
     pub stfle_bit: Option<u8>,
     pub enabled: bool,
 }
@@ -156,6 +159,33 @@ impl MsaLevel {
             ..Default::default()
         }
     }
+
+    /// number of functions introduced by a level is dynamically counted to ease extension
+    ///
+    /// The dynamic counts only consider the instructions in `filter`, or all if `filter` is
+    /// empty.
+    pub(crate) fn update_function_count(
+        &mut self,
+        ins: &[Instruction],
+        filter: &[InstructionKind],
+    ) {
+        self.total_functions = 0;
+        self.available_functions = 0;
+        self.dynamic_total_functions = 0;
+        self.dynamic_available_functions = 0;
+        for i in ins {
+            let funcs = i.funcs.iter().filter(|f| f.msa == self.msa_level);
+            let num_of_funcs_in_level = funcs.clone().count() as u8;
+            let num_of_avail_funcs = funcs.filter(|f| f.available).count() as u8;
+
+            self.total_functions += num_of_funcs_in_level;
+            self.available_functions += num_of_avail_funcs;
+            if filter.is_empty() || filter.contains(&i.kind) {
+                self.dynamic_total_functions += num_of_funcs_in_level;
+                self.dynamic_available_functions += num_of_avail_funcs;
+            }
+        }
+    }
 }
 
 impl Display for MsaLevel {
@@ -177,9 +207,10 @@ impl Display for MsaLevel {
     }
 }
 
-#[derive(Serialize, Clone, Default)]
+/// A function of an instruction, identified by its function code
+#[derive(Debug, Serialize, Clone, Default)]
 pub struct Function {
-    name: String,
+    pub name: String,
     pub function_code: u8,
     pub available: bool,
     #[serde(skip)]
@@ -208,7 +239,8 @@ impl Display for Function {
     }
 }
 
-#[derive(Serialize, Default)]
+/// Query Authentication Information (QAI) of an instruction, describing the running firmware
+#[derive(Debug, Serialize, Default)]
 pub struct QueryAuthInfo {
     pub format: u8,
     pub hash_len: u16,
@@ -221,7 +253,7 @@ pub struct QueryAuthInfo {
 impl Display for QueryAuthInfo {
     fn fmt(&self, f: &mut Formatter) -> Result {
         write!(f, "  Format: {}", self.format)?;
-        if self.format != 0 {
+        if !qai_format_supported(self.format) {
             writeln!(f, " (unknown format)")?;
             return Ok(());
         }
@@ -236,7 +268,8 @@ impl Display for QueryAuthInfo {
     }
 }
 
-#[derive(Serialize, Default)]
+/// Availability information of an instruction
+#[derive(Debug, Serialize, Default)]
 pub struct InstructionInfo {
     pub name: String,
     pub available: bool,
@@ -256,7 +289,8 @@ impl InstructionInfo {
     }
 }
 
-#[derive(Serialize)]
+/// A CPACF instruction with all functions known by cpacfinfo
+#[derive(Debug, Serialize)]
 pub struct Instruction {
     pub kind: InstructionKind,
     pub info: InstructionInfo,
@@ -558,18 +592,6 @@ pub fn init_instructions(instructions: &mut Vec<Instruction>) {
     instructions.push(kdsa);
 }
 
-/// number of functions introduced by a level is dynamically counted to ease extension
-pub fn update_msa_function_count(args: &Cli, msa: &mut MsaLevel, ins: &Vec<Instruction>) {
-    for i in ins {
-        let num_of_funcs_in_level = i.funcs.iter().filter(|f| f.msa == msa.msa_level).count() as u8;
-
-        msa.total_functions += num_of_funcs_in_level;
-        if args.instructions.is_empty() || args.instructions.contains(&i.kind) {
-            msa.dynamic_total_functions += num_of_funcs_in_level;
-        }
-    }
-}
-
 pub fn ser_hex<S: Serializer>(data: &Vec<u8>, ser: S) -> std::result::Result<S::Ok, S::Error> {
     HexSlice::from(data).serialize(ser)
 }
@@ -8,13 +8,13 @@ use std::io::Read;
 use std::ops::Index;
 use std::result::Result;
 
-use zerocopy::FromBytes;
+use zerocopy::{BigEndian, FromBytes, U16, U32};
 
 use crate::msa::InstructionKind;
 use crate::msa::QueryAuthInfo;
 
 /// Path to sysfs in which the query and qai informations are fetched from
-const SYSFS_PATH: &str = "/sys/devices/system/cpu/cpacf/";
+pub const SYSFS_PATH: &str = "/sys/devices/system/cpu/cpacf/";
 
 /// Every Instruction has a Query function to get information about what functions are available
 pub const QUERY_FUNCTION_CODE: u8 = 0;
@@ -32,13 +32,26 @@ pub const QAI_PARAM_SIZE_IN_BYTES: usize = 256;
 /// Query authentication information format identifier
 const FORMAT_0: u8 = 0;
 
+/// Parses the query authentication information block of a specific format
+type QaiParser = fn(&mut QueryAuthInfo, &[u8]);
+
+/// Parsers of all supported query authentication information formats
+///
+/// To support a new format add its identifier along with a parsing function here.
+const QAI_PARSERS: &[(u8, QaiParser)] = &[(FORMAT_0, parse_qai_format_0)];
+
+/// check if cpacfinfo is able to parse query authentication information of the given format
+pub fn qai_format_supported(format: u8) -> bool {
+    QAI_PARSERS.iter().any(|(f, _)| *f == format)
+}
+
 #[derive(FromBytes)]
 #[repr(C)]
 struct QaiFmt0 {
     res00: [u8; 6],
-    hash_length: u16,
+    hash_length: U16<BigEndian>,
     res08: [u8; 4],
-    version: u32,
+    version: U32<BigEndian>,
     hash: [u8; 64],
 }
 
@@ -60,6 +73,29 @@ impl Index<u8> for Param {
 }
 
 impl Param {
+    /// zeroed param for the query function `fc`
+    pub fn new(fc: u8) -> Self {
+        match fc {
+            QUERY_FUNCTION_CODE => Self::QueryParam([0; QUERY_PARAM_SIZE_IN_BYTES]),
+            QAI_FUNCTION_CODE => Self::QaiParam([0; QAI_PARAM_SIZE_IN_BYTES]),
+            _ => panic!("programming error"),
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Self::QueryParam(p) => p,
+            Self::QaiParam(p) => p,
+        }
+    }
+
+    pub fn as_mut_bytes(&mut self) -> &mut [u8] {
+        match self {
+            Self::QueryParam(p) => p,
+            Self::QaiParam(p) => p,
+        }
+    }
+
     pub fn len(&self) -> usize {
         match self {
             Self::QueryParam(_) => QUERY_PARAM_SIZE_IN_BYTES,
@@ -122,24 +158,22 @@ impl Param {
         }
     }
 
-    // Outsourced for potential future formats to be easily added in this match statement
-    pub fn parse_qai_based_on_format(
-        &self,
-        qai: &mut QueryAuthInfo,
-    ) -> Result<bool, anyhow::Error> {
+    // The parser is selected based on the format of the block, see QAI_PARSERS
+    //
+    // returns false if the format is unknown
+    pub fn parse_qai_based_on_format(&self, qai: &mut QueryAuthInfo) -> bool {
         match self {
             Self::QueryParam(_) => panic!("programming error"),
             Self::QaiParam(bin) => {
                 // The third byte of the param block specifies which format to use to parse the rest
                 qai.format = bin[3];
 
-                // for new formats add a match case here along with a parsing function
-                match qai.format {
-                    FORMAT_0 => {
-                        parse_qai_format_0(qai, bin);
-                        Ok(true)
+                match QAI_PARSERS.iter().find(|(f, _)| *f == qai.format) {
+                    Some((_, parse)) => {
+                        parse(qai, bin);
+                        true
                     }
-                    _ => Ok(false),
+                    None => false,
                 }
             }
         }
@@ -148,13 +182,7 @@ impl Param {
 
 // check if SYSFS_PATH exists
 pub fn check_sysfs() -> bool {
-    match std::path::Path::new(SYSFS_PATH).exists() {
-        true => true,
-        false => {
-            println!("Warning: There seems to be an insufficient kernel level running (sysfs interface {SYSFS_PATH} is missing)\nNo information can be fetched from sysfs, application exits early.");
-            false
-        }
-    }
+    std::path::Path::new(SYSFS_PATH).exists()
 }
 
 /// parsing the information supplied by sysfs into QueryAuthInfo struct
@@ -163,6 +191,7 @@ pub fn check_sysfs() -> bool {
 /// The length of field IFCL Hash depends on IFCL Hash Length and is either 32 or 64 bytes long.
 /// In case of a 32 bytes length the latter 32 bytes of the 64 bytes Hash are filled with zeros.
 ///
+/// ```text
 ///     | BYTE          | BYTE          | BYTE          | BYTE          |
 ///     -----------------------------------------------------------------
 ///     | RESERVED (3)                                    FORMAT (1)    |
@@ -172,37 +201,29 @@ pub fn check_sysfs() -> bool {
 ///     | IFCL HASH (32 / 64)                                           |
 ///     | RESERVED (176)                                                |
 ///     -----------------------------------------------------------------
+/// ```
 fn parse_qai_format_0(qai: &mut QueryAuthInfo, param: &[u8]) {
     // parse param to temporary struct to ease further conversion
     let (tmp, _) = QaiFmt0::read_from_prefix(param).expect("programming error");
 
     // parse from temporary struct
-    qai.hash_len = tmp.hash_length;
-    qai.version = tmp.version;
-
-    // depending on the parsed hash length the hash is parsed
-    qai.hash = vec![0; qai.hash_len as usize];
-    qai.hash
-        .as_mut_slice()
-        .copy_from_slice(&tmp.hash[..qai.hash_len as usize]);
+    qai.hash_len = tmp.hash_length.get();
+    qai.version = tmp.version.get();
+
+    // depending on the parsed hash length the hash is parsed; the length is capped to the size
+    // of the hash field, e.g. for corrupted snapshots
+    let hash_len = (qai.hash_len as usize).min(tmp.hash.len());
+    qai.hash = tmp.hash[..hash_len].to_vec();
 }
 
 /// cpacfinfo does not execute the actual instruction with function code but uses information
 /// provided by the sysfs
 pub fn query(ins: &InstructionKind, fc: u8) -> Result<Param, Error> {
     // query dependent file names
-    let auth_info;
-    let mut param;
-    match fc {
-        QUERY_FUNCTION_CODE => {
-            auth_info = "";
-            param = Param::QueryParam([0; QUERY_PARAM_SIZE_IN_BYTES]);
-        }
-        QAI_FUNCTION_CODE => {
-            auth_info = "_auth_info";
-            param = Param::QaiParam([0; QAI_PARAM_SIZE_IN_BYTES]);
-        }
-        _ => panic!("programming error"),
+    let mut param = Param::new(fc);
+    let auth_info = match fc {
+        QAI_FUNCTION_CODE => "_auth_info",
+        _ => "",
     };
 
     // depending on which query is performed the bytes to be read from sysfs vary
@@ -218,12 +239,7 @@ pub fn query(ins: &InstructionKind, fc: u8) -> Result<Param, Error> {
     let mut f = File::open(filepath)?;
 
     // read file
-    let res = match param {
-        Param::QueryParam(ref mut c) => read_file_to_buf(&mut f, c),
-        Param::QaiParam(ref mut c) => read_file_to_buf(&mut f, c),
-    };
-
-    let bytes_read = res?;
+    let bytes_read = read_file_to_buf(&mut f, param.as_mut_bytes())?;
 
     match bytes_read == bytes_to_be_read {
         true => Ok(param),
@@ -0,0 +1,260 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2024
+
+use std::path::Path;
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::msa::{ser_hex, Instruction, InstructionKind};
+use crate::query::qai_format_supported;
+use crate::snapshot::decode_hex;
+use crate::{Error, Result};
+
+/// A known-good firmware level of an instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct QaiReferenceEntry {
+    kind: InstructionKind,
+    version: u32,
+    hash: Vec<u8>,
+    name: Option<String>,
+}
+
+/// Known-good firmware levels to verify the Query Authentication Information (QAI) against
+///
+/// The reference file lists one firmware level per line:
+///
+/// ```text
+/// # INS  IFCL-VERSION  IFCL-HASH          [NAME]
+/// kma    5             0x0123...cdef      driver 61
+/// ```
+///
+/// Several levels can be listed for the same instruction. Everything after "#" is a comment.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QaiReference {
+    entries: Vec<QaiReferenceEntry>,
+}
+
+/// Verification state of the QAI of a single instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum QaiStatus {
+    /// IFCL version and hash match a reference entry
+    Verified,
+    /// The reference lists the instruction but no entry matches IFCL version and hash
+    Mismatch,
+    /// The reference does not list the instruction
+    NoReference,
+    /// The format of the QAI is not supported by cpacfinfo
+    UnsupportedFormat,
+    /// The instruction provides no QAI, e.g. on machines without MSA 13
+    Unavailable,
+}
+
+/// Result of the verification of the QAI of a single instruction
+#[derive(Debug, Serialize)]
+pub struct QaiVerification {
+    pub instruction: InstructionKind,
+    pub status: QaiStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
+    #[serde(skip_serializing_if = "Vec::is_empty", serialize_with = "ser_hex")]
+    pub hash: Vec<u8>,
+    /// Name of the matching reference entry
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<String>,
+}
+
+/// Result of the verification of all instructions
+#[derive(Debug, Serialize)]
+pub struct QaiReport {
+    pub verified: bool,
+    pub instructions: Vec<QaiVerification>,
+}
+
+fn parse_version(s: &str) -> Option<u32> {
+    match s.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+impl QaiReference {
+    /// Read the reference from a file
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be read or contains an invalid
+    /// entry.
+    pub fn read(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|source| Error::ReferenceRead {
+            path: path.to_owned(),
+            source,
+        })?;
+        Self::parse(&content)
+    }
+
+    /// Parse the content of a reference file
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `content` contains an invalid entry.
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut entries = Vec::new();
+        for (n, line) in content.lines().enumerate() {
+            let err = |reason: &str| Error::InvalidReference {
+                line: n + 1,
+                reason: reason.to_string(),
+            };
+            let line = line.split('#').next().unwrap_or_default();
+            let mut fields = line.split_whitespace();
+            let Some(ins) = fields.next() else {
+                continue;
+            };
+            let kind = InstructionKind::from_str(ins, true)
+                .map_err(|_| err(&format!("unknown instruction '{ins}'")))?;
+            let version = fields
+                .next()
+                .and_then(parse_version)
+                .ok_or_else(|| err("missing or invalid IFCL version"))?;
+            let hash = fields
+                .next()
+                .and_then(decode_hex)
+                .filter(|h| !h.is_empty())
+                .ok_or_else(|| err("missing or invalid IFCL hash"))?;
+            let name = fields.collect::<Vec<_>>().join(" ");
+            entries.push(QaiReferenceEntry {
+                kind,
+                version,
+                hash,
+                name: (!name.is_empty()).then_some(name),
+            });
+        }
+        Ok(Self { entries })
+    }
+
+    /// Verify the QAI of the available `instructions` against the reference
+    ///
+    /// The report is verified if the QAI of every instruction matches a reference entry.
+    /// Instructions that provide no QAI at all do not fail the verification.
+    pub fn verify<'a, I>(&self, instructions: I) -> QaiReport
+    where
+        I: IntoIterator<Item = &'a Instruction>,
+    {
+        let instructions: Vec<_> = instructions
+            .into_iter()
+            .filter(|ins| ins.info.available)
+            .map(|ins| self.verify_one(ins))
+            .collect();
+        QaiReport {
+            verified: instructions
+                .iter()
+                .all(|v| matches!(v.status, QaiStatus::Verified | QaiStatus::Unavailable)),
+            instructions,
+        }
+    }
+
+    fn verify_one(&self, ins: &Instruction) -> QaiVerification {
+        let qai = &ins.info.qai;
+        let mut res = QaiVerification {
+            instruction: ins.kind,
+            status: QaiStatus::Unavailable,
+            version: None,
+            hash: Vec::new(),
+            level: None,
+        };
+        if !ins.info.qai_available {
+            return res;
+        }
+        if !qai_format_supported(qai.format) {
+            res.status = QaiStatus::UnsupportedFormat;
+            return res;
+        }
+
+        res.version = Some(qai.version);
+        res.hash = qai.hash.clone();
+        let mut refs = self
+            .entries
+            .iter()
+            .filter(|e| e.kind == ins.kind)
+            .peekable();
+        if refs.peek().is_none() {
+            res.status = QaiStatus::NoReference;
+            return res;
+        }
+        match refs.find(|e| e.version == qai.version && e.hash == qai.hash) {
+            Some(entry) => {
+                res.status = QaiStatus::Verified;
+                res.level = entry.name.clone();
+            }
+            None => res.status = QaiStatus::Mismatch,
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_verify() {
+    use crate::{CpacfInfo, Snapshot};
+
+    // KM and KMA (STFLE bits 17 and 146) provide the query and QAI functions (0 and 127)
+    let query = format!("0x80{}01", "00".repeat(14));
+    let qai = |version: &str, hash: &str| {
+        format!(
+            "0x000000000000000400000000{version}{hash}{}",
+            "00".repeat(256 - 16 - 4)
+        )
+    };
+    let snapshot: Snapshot = serde_json::from_str(&format!(
+        r#"{{"version": 1, "stfle": ["0x0000400000000000", "0x0", "0x0000200000000000"],
+            "query": {{"km": "{query}", "kma": "{query}", "kmc": "{query}"}},
+            "qai": {{"km": "{}", "kma": "{}"}}}}"#,
+        qai("00000005", "01020304"),
+        qai("00000006", "0a0b0c0d"),
+    ))
+    .unwrap();
+    let info = CpacfInfo::from_snapshot(&snapshot).unwrap();
+
+    let reference = QaiReference::parse(
+        "# known levels\n\
+         km 5 0x01020304 driver 61 # comment\n\
+         KMA 0x6 0x01020304\n",
+    )
+    .unwrap();
+    let report = reference.verify(info.instructions());
+    assert!(!report.verified);
+    let status = |kind| {
+        report
+            .instructions
+            .iter()
+            .find(|v| v.instruction == kind)
+            .unwrap()
+    };
+    assert_eq!(status(InstructionKind::KM).status, QaiStatus::Verified);
+    assert_eq!(
+        status(InstructionKind::KM).level.as_deref(),
+        Some("driver 61")
+    );
+    assert_eq!(status(InstructionKind::KMA).status, QaiStatus::Mismatch);
+    // KMC announces the QAI function but the snapshot does not contain the QAI
+    assert_eq!(status(InstructionKind::KMC).status, QaiStatus::Unavailable);
+    assert_eq!(status(InstructionKind::KIMD).status, QaiStatus::Unavailable);
+
+    let report = reference.verify(
+        info.instructions()
+            .iter()
+            .filter(|i| i.kind == InstructionKind::KM),
+    );
+    assert!(report.verified);
+
+    assert!(matches!(
+        QaiReference::parse("km 5\n"),
+        Err(Error::InvalidReference { line: 1, .. })
+    ));
+    assert!(matches!(
+        QaiReference::parse("\nxyz 5 0x00\n"),
+        Err(Error::InvalidReference { line: 2, .. })
+    ));
+}
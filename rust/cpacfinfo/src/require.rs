@@ -0,0 +1,174 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2024
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::Serialize;
+
+use cpacfinfo::{Instruction, InstructionKind};
+
+/// Evaluation state of a single requirement
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    /// The function is available on the system
+    Available,
+    /// The function is known by cpacfinfo but not available on the system
+    Missing,
+    /// The requirement does not name a function known by cpacfinfo
+    Unknown,
+}
+
+/// Result of the evaluation of a single requirement
+#[derive(Serialize, Debug)]
+pub struct Evaluation {
+    requirement: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    function: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    function_code: Option<u8>,
+    status: Status,
+}
+
+/// Result of the evaluation of all requirements
+#[derive(Serialize, Debug)]
+pub struct Report {
+    satisfied: bool,
+    requirements: Vec<Evaluation>,
+}
+
+impl Report {
+    pub fn satisfied(&self) -> bool {
+        self.satisfied
+    }
+}
+
+/// Reads requirements from a file
+///
+/// One or more requirements per line separated by "," or whitespace. Everything after "#" is a
+/// comment.
+pub fn read_requirements(path: &Path) -> Result<Vec<String>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Cannot read requirements file {}", path.display()))?;
+    Ok(content
+        .lines()
+        .map(|l| l.split('#').next().unwrap_or_default())
+        .flat_map(|l| l.split(|c: char| c == ',' || c.is_whitespace()))
+        .filter(|r| !r.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Evaluates one requirement of the form INS:FUNCTION
+///
+/// FUNCTION is either the function name with or without the instruction prefix
+/// (e.g. KMA:GCM-AES-256 or KMA:KMA-GCM-AES-256) or the function code (e.g. KMA:20).
+fn evaluate_one(requirement: &str, instructions: &[Instruction]) -> Evaluation {
+    let unknown = || Evaluation {
+        requirement: requirement.to_string(),
+        function: None,
+        function_code: None,
+        status: Status::Unknown,
+    };
+
+    let Some((ins, func)) = requirement.split_once(':') else {
+        return unknown();
+    };
+    let Ok(kind) = InstructionKind::from_str(ins.trim(), true) else {
+        return unknown();
+    };
+    let Some(ins) = instructions.iter().find(|i| i.kind == kind) else {
+        return unknown();
+    };
+
+    let func = func.trim();
+    let prefixed = format!("{kind}-{func}");
+    let found = match func.parse::<u8>() {
+        Ok(fc) => ins.funcs.iter().find(|f| f.function_code == fc),
+        Err(_) => ins
+            .funcs
+            .iter()
+            .find(|f| f.name.eq_ignore_ascii_case(&prefixed) || f.name.eq_ignore_ascii_case(func)),
+    };
+
+    match found {
+        Some(f) => Evaluation {
+            requirement: requirement.to_string(),
+            function: Some(f.name.clone()),
+            function_code: Some(f.function_code),
+            status: match f.available {
+                true => Status::Available,
+                false => Status::Missing,
+            },
+        },
+        None => unknown(),
+    }
+}
+
+/// Evaluates all requirements against the instructions of the system
+pub fn evaluate(requirements: &[String], instructions: &[Instruction]) -> Report {
+    let requirements: Vec<_> = requirements
+        .iter()
+        .map(|r| evaluate_one(r, instructions))
+        .collect();
+    Report {
+        satisfied: requirements.iter().all(|e| e.status == Status::Available),
+        requirements,
+    }
+}
+
+/// Prints the unsatisfied requirements in human readable form
+pub fn print_human(report: &Report) {
+    for e in &report.requirements {
+        match (&e.status, &e.function, e.function_code) {
+            (Status::Available, _, _) => (),
+            (Status::Missing, Some(name), Some(fc)) => {
+                println!("Missing function: {name} (function code {fc})")
+            }
+            _ => println!("Unknown function: {}", e.requirement),
+        }
+    }
+    match report.satisfied {
+        true => println!(
+            "All {} required functions are available",
+            report.requirements.len()
+        ),
+        false => println!("Requirements are not satisfied"),
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_evaluate() {
+    // KMA (STFLE bit 146) provides the query function (0) and GCM-AES-256 (20)
+    let snapshot: cpacfinfo::Snapshot = serde_json::from_str(&format!(
+        r#"{{"version": 1, "stfle": ["0x0", "0x0", "0x0000200000000000"],
+            "query": {{"kma": "0x800008{}"}}, "qai": {{}}}}"#,
+        "00".repeat(13)
+    ))
+    .unwrap();
+    let info = cpacfinfo::CpacfInfo::from_snapshot(&snapshot).unwrap();
+    let instructions = info.instructions();
+
+    let reqs: Vec<String> = ["KMA:GCM-AES-256", "kma:20", "KMA:KMA-GCM-AES-256"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    let report = evaluate(&reqs, instructions);
+    assert!(report.satisfied());
+
+    let reqs: Vec<String> = ["KMA:GCM-AES-128", "KDSA:ECDSA-P521", "XYZ:1", "KMA"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    let report = evaluate(&reqs, instructions);
+    assert!(!report.satisfied());
+    assert_eq!(report.requirements[0].status, Status::Missing);
+    assert_eq!(report.requirements[0].function_code, Some(18));
+    assert_eq!(report.requirements[1].status, Status::Unknown);
+    assert_eq!(report.requirements[2].status, Status::Unknown);
+    assert_eq!(report.requirements[3].status, Status::Unknown);
+}
@@ -0,0 +1,191 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2024
+
+use std::collections::BTreeMap;
+use std::io::{self, ErrorKind};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use utils::HexSlice;
+
+use crate::msa::{Instruction, InstructionKind};
+use crate::query::{query, Param, QAI_FUNCTION_CODE, QUERY_FUNCTION_CODE};
+use crate::stfle::{Stfle, STFLE_LEN};
+use crate::{Error, Result};
+
+/// Current version of the snapshot file format
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// Raw CPACF information of a system
+///
+/// Contains the STFLE bits and the raw query and query authentication information parameter
+/// blocks of every instruction as hex strings. Instructions are identified by their lowercase
+/// mnemonic, the same name the sysfs files use.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+pub struct Snapshot {
+    version: u32,
+    stfle: Vec<String>,
+    query: BTreeMap<String, String>,
+    qai: BTreeMap<String, String>,
+}
+
+pub(crate) fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    if s.len() % 2 != 0 {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn ins_name(ins: &InstructionKind) -> String {
+    ins.to_string().to_lowercase()
+}
+
+impl Snapshot {
+    /// Capture the STFLE bits and all query parameter blocks available via sysfs
+    pub(crate) fn capture(stfle: &Stfle, instructions: &[Instruction]) -> Result<Self> {
+        let mut snapshot = Self {
+            version: SNAPSHOT_VERSION,
+            stfle: stfle
+                .data()
+                .iter()
+                .map(|d| format!("{:#}", HexSlice::from(&d.to_be_bytes())))
+                .collect(),
+            ..Default::default()
+        };
+
+        for ins in instructions {
+            for (fc, map) in [
+                (QUERY_FUNCTION_CODE, &mut snapshot.query),
+                (QAI_FUNCTION_CODE, &mut snapshot.qai),
+            ] {
+                match query(&ins.kind, fc) {
+                    Ok(param) => {
+                        map.insert(
+                            ins_name(&ins.kind),
+                            format!("{:#}", HexSlice::from(param.as_bytes())),
+                        );
+                    }
+                    // not every instruction provides (all) query information
+                    Err(e) if e.kind() == ErrorKind::NotFound => (),
+                    Err(e) => return Err(Error::Query(ins.kind, e)),
+                }
+            }
+        }
+        Ok(snapshot)
+    }
+
+    /// Read a snapshot written by [`Snapshot::write`]
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be read or does not contain a
+    /// valid snapshot.
+    pub fn read(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|source| Error::SnapshotRead {
+            path: path.to_owned(),
+            source,
+        })?;
+        let snapshot: Self =
+            serde_json::from_str(&content).map_err(|source| Error::SnapshotParse {
+                path: path.to_owned(),
+                source,
+            })?;
+        snapshot.check()?;
+        Ok(snapshot)
+    }
+
+    /// Write the snapshot as JSON
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be written.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        serde_json::to_string_pretty(self)
+            .map_err(io::Error::from)
+            .and_then(|json| std::fs::write(path, json))
+            .map_err(|source| Error::SnapshotWrite {
+                path: path.to_owned(),
+                source,
+            })
+    }
+
+    /// Check that the snapshot has a supported version and valid STFLE bits
+    pub(crate) fn check(&self) -> Result<()> {
+        if self.version != SNAPSHOT_VERSION {
+            return Err(Error::UnsupportedSnapshotVersion(self.version));
+        }
+        if self.stfle().is_none() {
+            return Err(Error::InvalidSnapshotStfle);
+        }
+        Ok(())
+    }
+
+    /// STFLE bits of the captured system
+    pub(crate) fn stfle(&self) -> Option<Stfle> {
+        let mut data = [0; STFLE_LEN];
+        if self.stfle.len() > STFLE_LEN {
+            return None;
+        }
+        for (d, s) in data.iter_mut().zip(&self.stfle) {
+            *d = u64::from_str_radix(s.strip_prefix("0x").unwrap_or(s), 16).ok()?;
+        }
+        Some(Stfle::from_raw(data))
+    }
+
+    /// Equivalent of [`query`] for the captured system
+    pub(crate) fn query(&self, ins: &InstructionKind, fc: u8) -> io::Result<Param> {
+        let map = match fc {
+            QUERY_FUNCTION_CODE => &self.query,
+            QAI_FUNCTION_CODE => &self.qai,
+            _ => panic!("programming error"),
+        };
+        let hex = map
+            .get(&ins_name(ins))
+            .ok_or_else(|| io::Error::new(ErrorKind::NotFound, format!("{ins} not in snapshot")))?;
+
+        let mut param = Param::new(fc);
+        match decode_hex(hex) {
+            Some(bytes) if bytes.len() == param.len() => {
+                param.as_mut_bytes().copy_from_slice(&bytes);
+                Ok(param)
+            }
+            _ => Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!("Invalid parameter block for {ins} in snapshot"),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_snapshot_query() {
+    let mut snapshot = Snapshot {
+        version: SNAPSHOT_VERSION,
+        stfle: vec!["0x0000400000000000".to_string()],
+        ..Default::default()
+    };
+    snapshot
+        .query
+        .insert("kma".to_string(), format!("0x80{}", "00".repeat(15)));
+    snapshot.qai.insert("kma".to_string(), "0x0011".to_string());
+
+    let param = snapshot.query(&InstructionKind::KMA, QUERY_FUNCTION_CODE);
+    assert!(param.unwrap().check_bit_in_param(0));
+    let err = snapshot.query(&InstructionKind::KM, QUERY_FUNCTION_CODE);
+    assert_eq!(err.err().unwrap().kind(), ErrorKind::NotFound);
+    let err = snapshot.query(&InstructionKind::KMA, QAI_FUNCTION_CODE);
+    assert_eq!(err.err().unwrap().kind(), ErrorKind::InvalidData);
+
+    let stfle = snapshot.stfle().unwrap();
+    assert!(stfle.check_bit_in_stfle(17));
+    assert!(!stfle.check_bit_in_stfle(146));
+
+    let json = serde_json::to_string(&snapshot).unwrap();
+    assert_eq!(serde_json::from_str::<Snapshot>(&json).unwrap(), snapshot);
+}
@@ -2,18 +2,21 @@
 //
 // Copyright IBM Corp. 2024
 
-use anyhow::Error;
+use crate::Warning;
 
 /// Specifies the number of u64 values needed to store the stfle block
 pub const STFLE_LEN: usize = 3;
 
+#[derive(Debug)]
 pub struct Stfle {
     data: [u64; STFLE_LEN],
 }
 
 impl Stfle {
     /// Constructs a STFLE block, and saves the STFLE information in the structure.
-    pub fn new() -> Result<Self, Error> {
+    ///
+    /// Problems fetching the STFLE information are added to `warnings`.
+    pub fn new(warnings: &mut Vec<Warning>) -> Self {
         let mut ret = Self {
             data: [0; STFLE_LEN],
         };
@@ -23,18 +26,28 @@ impl Stfle {
         let rc = unsafe { stfle(&mut ret.data[0], STFLE_LEN as u32) };
         let rc = match rc {
             0 => {
-                println!("Unable to fetch STFLE which is only available on s390x architecture");
-                return Ok(ret);
+                warnings.push(Warning::StfleUnavailable);
+                return ret;
             }
             rc if rc as usize >= STFLE_LEN => STFLE_LEN as u32,
             rc => rc + 1,
         };
 
         if rc != STFLE_LEN as u32 {
-            println!("Partial read of STFLE, information might be incomplete");
+            warnings.push(Warning::StflePartial);
         }
 
-        Ok(ret)
+        ret
+    }
+
+    /// Constructs a STFLE block from previously fetched STFLE information.
+    pub fn from_raw(data: [u64; STFLE_LEN]) -> Self {
+        Self { data }
+    }
+
+    /// raw STFLE information (big-endian doublewords)
+    pub fn data(&self) -> &[u64; STFLE_LEN] {
+        &self.data
     }
 
     /// check specific bit in stfle (accounts for big-endianness of stfle)
@@ -66,10 +79,7 @@ extern "C" {
 #[cfg(test)]
 #[test]
 fn test_check_bit_in_stfle() {
-    let mut stfle = match Stfle::new() {
-        Ok(ret) => ret,
-        Err(e) => panic!("{e}"),
-    };
+    let mut stfle = Stfle::new(&mut Vec::new());
 
     for b in 0..(STFLE_LEN * 64) {
         // set bit
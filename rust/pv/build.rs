@@ -0,0 +1,17 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+//! Sets `ossl300` if openssl-sys links against OpenSSL 3.0 or newer.
+//!
+//! Some OpenSSL constants that openssl-sys does not bind changed their values with OpenSSL 3.0.
+
+fn main() {
+    println!("cargo:rustc-check-cfg=cfg(ossl300)");
+    if let Ok(version) = std::env::var("DEP_OPENSSL_VERSION_NUMBER") {
+        let version = u64::from_str_radix(&version, 16).expect("invalid OpenSSL version number");
+        if version >= 0x3000_0000 {
+            println!("cargo:rustc-cfg=ossl300");
+        }
+    }
+}
@@ -2,7 +2,7 @@
 //
 // Copyright IBM Corp. 2023, 2024
 
-use std::{convert::TryInto, fmt::Display, ops::Range};
+use std::{convert::TryInto, ffi::CString, fmt::Display, ops::Range};
 
 use enum_dispatch::enum_dispatch;
 use openssl::{
@@ -20,7 +20,7 @@ use openssl::{
 };
 use pv_core::request::Confidential;
 
-use crate::{error::Result, Error};
+use crate::{error::Result, openssl_extensions, Error};
 
 /// An AES256-GCM key that will purge itself out of the memory when going out of scope
 pub type Aes256GcmKey = Confidential<[u8; SymKeyType::AES_256_GCM_KEY_LEN]>;
@@ -505,6 +505,26 @@ pub(crate) fn sign_msg(
     }
 }
 
+/// Load a private key from a file or an OpenSSL store URI.
+///
+/// `uri` can be a file name, a `file:` URI, or any URI supported by the configured OpenSSL
+/// providers, e.g. a `pkcs11:` URI of a key in a hardware security module. Keys from a
+/// hardware token stay in the token; signing is performed by the provider.
+///
+/// # Errors
+///
+/// This function will return an error if the store cannot be opened or does not contain a
+/// private key.
+pub fn load_private_key(uri: &str) -> Result<PKey<Private>> {
+    let c_uri = CString::new(uri).map_err(|_| Error::NoPrivateKey(uri.to_string()))?;
+    openssl_extensions::load_private_key(&c_uri)
+        .map_err(|source| Error::PrivateKeyLoad {
+            uri: uri.to_string(),
+            source,
+        })?
+        .ok_or_else(|| Error::NoPrivateKey(uri.to_string()))
+}
+
 /// Verify the digital signature of a message.
 ///
 /// Verifies the digital signature of the provided message using the signing key.
@@ -577,6 +597,30 @@ mod tests {
         assert!(verify_signature(&keypair, MessageDigest::sha512(), data, &sign).unwrap());
     }
 
+    #[test]
+    fn load_private_key() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/assets/keys/rsa3072key.pem"
+        );
+        let exp = PKey::private_key_from_pem(get_test_asset!("keys/rsa3072key.pem")).unwrap();
+
+        for uri in [path.to_string(), format!("file:{path}")] {
+            let key = super::load_private_key(&uri).unwrap();
+            assert!(key.public_eq(&exp));
+        }
+
+        let pub_key = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/assets/keys/rsa2048.crt");
+        assert!(matches!(
+            super::load_private_key(pub_key),
+            Err(Error::NoPrivateKey(_))
+        ));
+        assert!(matches!(
+            super::load_private_key("file:/does/not/exist.pem"),
+            Err(Error::PrivateKeyLoad { .. })
+        ));
+    }
+
     #[test]
     fn derive_aes256_gcm_key() {
         let (cust_key, host_key) = get_test_keys();
@@ -120,6 +120,15 @@ pub enum Error {
     #[error("Invalid data from OpenSSL")]
     InvalSslData,
 
+    #[error("Cannot load a private key from '{uri}'")]
+    PrivateKeyLoad {
+        uri: String,
+        source: openssl::error::ErrorStack,
+    },
+
+    #[error("'{0}' does not contain a private key")]
+    NoPrivateKey(String),
+
     // errors from other crates
     #[error(transparent)]
     PvCore(#[from] pv_core::Error),
@@ -127,9 +136,13 @@ pub enum Error {
     Io(#[from] std::io::Error),
     #[error(transparent)]
     Crypto(#[from] openssl::error::ErrorStack),
+    #[cfg(feature = "network")]
     #[error(transparent)]
     Curl(#[from] curl::Error),
 
+    #[error("Cannot {0}: built without network support")]
+    NoNetwork(&'static str),
+
     #[error("No Authenticated Encryption with Associated Data (AEAD) key")]
     NoAeadKey,
 
@@ -33,12 +33,17 @@
 //!
 //! # Verify
 //! [`attest::AttestationItems`], [`attest::AttestationMeasurement`]
+//!
+//! # Testing
+//!
+//! [`testing::SoftUv`] emulates the Ultravisor for add-secret and attestation requests.
 mod brcb;
 mod crypto;
 mod error;
 mod openssl_extensions;
 mod pem_utils;
 mod req;
+mod soft_uv;
 mod utils;
 mod uvattest;
 mod uvsecret;
@@ -50,6 +55,13 @@ mod verify;
 #[allow(dead_code)]
 pub mod test_utils;
 
+/// Software emulation of the Ultravisor for testing request workflows without an SE-guest
+///
+/// DO NOT USE IN PRODUCTION CODE.
+pub mod testing {
+    pub use crate::soft_uv::{SoftUv, SoftUvAttestation, SoftUvBackend};
+}
+
 pub use pv_core::{assert_size, static_assert};
 
 const PAGESIZE: usize = 0x1000;
@@ -68,7 +80,7 @@ pub mod attest {
         arcb::{
             AttestationAuthenticated, AttestationFlags, AttestationRequest, AttestationVersion,
         },
-        attest::{AttestationItems, AttestationMeasurement},
+        attest::{challenge_user_data, AttestationItems, AttestationMeasurement},
     };
 }
 
@@ -94,14 +106,20 @@ pub mod request {
     pub use crate::{
         brcb::{seek_se_hdr_start, BootHdrTags, SeImgMetaData},
         crypto::{
-            decrypt_aead, derive_aes256_gcm_key, encrypt_aead, gen_ec_key, random_array,
-            AeadDecryptionResult, AeadEncryptionResult, Aes256GcmKey, Aes256XtsKey, SymKey,
-            SymKeyType, SHA_512_HASH_LEN,
+            decrypt_aead, derive_aes256_gcm_key, encrypt_aead, gen_ec_key, load_private_key,
+            random_array, AeadDecryptionResult, AeadEncryptionResult, Aes256GcmKey, Aes256XtsKey,
+            SymKey, SymKeyType, SHA_512_HASH_LEN,
         },
         req::{EcPubKeyCoord, Encrypt, Keyslot, ReqEncrCtx, Request},
-        verify::{CertVerifier, HkdVerifier, NoVerifyHkd},
+        verify::{
+            x509_dist_points, CertVerifier, CrlFetcher, DirCrlFetcher, HkdStepOutcome, HkdVerifier,
+            HkdVerifyStep, NoCrlFetcher, NoVerifyHkd,
+        },
     };
 
+    #[cfg(feature = "network")]
+    pub use crate::verify::HttpCrlFetcher;
+
     /// Reexports some useful OpenSSL symbols
     pub mod openssl {
         pub use openssl::{error::ErrorStack, hash::DigestBytes, nid::Nid, pkey, x509};
@@ -9,7 +9,9 @@ mod akid;
 mod bio;
 mod crl;
 mod stackable_crl;
+mod store;
 
 pub use akid::*;
 pub use bio::*;
 pub use crl::*;
+pub use store::*;
@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use foreign_types::ForeignType;
+use openssl::{
+    error::ErrorStack,
+    pkey::{PKey, Private},
+};
+use std::{ffi::CStr, ptr};
+
+#[allow(non_camel_case_types)]
+mod ffi {
+    use openssl_sys::EVP_PKEY;
+    use std::ffi::{c_char, c_int, c_void};
+
+    pub enum OSSL_STORE_CTX {}
+    pub enum OSSL_STORE_INFO {}
+    pub enum UI_METHOD {}
+
+    // OpenSSL 3.0 inserted OSSL_STORE_INFO_PUBKEY before PKEY
+    #[cfg(ossl300)]
+    pub const OSSL_STORE_INFO_PKEY: c_int = 4;
+    #[cfg(not(ossl300))]
+    pub const OSSL_STORE_INFO_PKEY: c_int = 3;
+
+    pub type OSSL_STORE_post_process_info_fn =
+        Option<unsafe extern "C" fn(*mut OSSL_STORE_INFO, *mut c_void) -> *mut OSSL_STORE_INFO>;
+
+    extern "C" {
+        pub fn UI_get_default_method() -> *const UI_METHOD;
+        pub fn OSSL_STORE_open(
+            uri: *const c_char,
+            ui_method: *const UI_METHOD,
+            ui_data: *mut c_void,
+            post_process: OSSL_STORE_post_process_info_fn,
+            post_process_data: *mut c_void,
+        ) -> *mut OSSL_STORE_CTX;
+        pub fn OSSL_STORE_expect(ctx: *mut OSSL_STORE_CTX, expected_type: c_int) -> c_int;
+        pub fn OSSL_STORE_load(ctx: *mut OSSL_STORE_CTX) -> *mut OSSL_STORE_INFO;
+        pub fn OSSL_STORE_eof(ctx: *mut OSSL_STORE_CTX) -> c_int;
+        pub fn OSSL_STORE_error(ctx: *mut OSSL_STORE_CTX) -> c_int;
+        pub fn OSSL_STORE_close(ctx: *mut OSSL_STORE_CTX) -> c_int;
+        pub fn OSSL_STORE_INFO_get_type(info: *const OSSL_STORE_INFO) -> c_int;
+        pub fn OSSL_STORE_INFO_get1_PKEY(info: *const OSSL_STORE_INFO) -> *mut EVP_PKEY;
+        pub fn OSSL_STORE_INFO_free(info: *mut OSSL_STORE_INFO);
+    }
+}
+
+struct StoreCtx(*mut ffi::OSSL_STORE_CTX);
+
+impl Drop for StoreCtx {
+    fn drop(&mut self) {
+        // SAFETY: Pointer is valid. The pointer value is dropped after the close.
+        unsafe {
+            ffi::OSSL_STORE_close(self.0);
+        }
+    }
+}
+
+/// Loads the first private key found at `uri` using the OpenSSL store API.
+///
+/// `uri` can be a file name or any URI supported by the loaded providers, e.g. a `pkcs11:` URI.
+/// If the store requires a PIN or passphrase, the user is prompted on the terminal.
+///
+/// Returns `None` if the store contains no private key.
+pub fn load_private_key(uri: &CStr) -> Result<Option<PKey<Private>>, ErrorStack> {
+    openssl_sys::init();
+
+    // SAFETY: `uri` is a valid C string; the UI method is static. Returns a valid pointer or
+    // null. null-case is tested right after this.
+    let ctx = unsafe {
+        ffi::OSSL_STORE_open(
+            uri.as_ptr(),
+            ffi::UI_get_default_method(),
+            ptr::null_mut(),
+            None,
+            ptr::null_mut(),
+        )
+    };
+    if ctx.is_null() {
+        return Err(ErrorStack::get());
+    }
+    let ctx = StoreCtx(ctx);
+
+    // SAFETY: ctx is valid for the lifetime of `ctx`. Each info is freed after use; the key is
+    // a new reference owned by the returned `PKey`.
+    unsafe {
+        // Only a hint for the loader; not all loaders support it.
+        ffi::OSSL_STORE_expect(ctx.0, ffi::OSSL_STORE_INFO_PKEY);
+        while ffi::OSSL_STORE_eof(ctx.0) == 0 {
+            let info = ffi::OSSL_STORE_load(ctx.0);
+            if info.is_null() {
+                // A null info without error flag is a skipped object, not a failure.
+                if ffi::OSSL_STORE_error(ctx.0) != 0 {
+                    let errors = ErrorStack::get();
+                    // OpenSSL 3 also sets the error flag if a load runs into the end of the
+                    // store. Any other error fails, even without a queued error.
+                    if ffi::OSSL_STORE_eof(ctx.0) == 0 || !errors.errors().is_empty() {
+                        return Err(errors);
+                    }
+                }
+                continue;
+            }
+            let pkey = match ffi::OSSL_STORE_INFO_get_type(info) {
+                ffi::OSSL_STORE_INFO_PKEY => ffi::OSSL_STORE_INFO_get1_PKEY(info),
+                _ => ptr::null_mut(),
+            };
+            ffi::OSSL_STORE_INFO_free(info);
+            if !pkey.is_null() {
+                return Ok(Some(PKey::from_ptr(pkey)));
+            }
+        }
+    }
+    Ok(None)
+}
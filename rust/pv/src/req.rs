@@ -84,6 +84,8 @@ pub struct Keyslot(PKey<Public>);
 impl Keyslot {
     /// Size of a host-key hash
     pub const PHKH_SIZE: u32 = 0x20;
+    /// Size of an encrypted keyslot in bytes
+    pub(crate) const BIN_SIZE: usize = 80;
 
     /// Creates a new Keyslot from the provided public key
     pub fn new(hostkey: PKey<Public>) -> Self {
@@ -114,7 +116,7 @@ impl Encrypt for Keyslot {
             encrypt_aead(&derived_key.into(), &[0; 12], &[], prot_key)?.into_buf();
         let phk: EcPubKeyCoord = self.0.as_ref().try_into()?;
 
-        to.reserve(80);
+        to.reserve(Self::BIN_SIZE);
         to.extend_from_slice(&hash(MessageDigest::sha256(), phk.as_ref())?);
         to.append(&mut wrpk_and_kst);
         Ok(())
@@ -301,6 +303,15 @@ impl EcPubKeyCoord {
     }
 }
 
+/// Reads a public key from the ECDH coordinates in the format the Ultravisor expects it.
+///
+/// # Errors
+///
+/// This function will return an error if the coordinates do not represent a SECP521R1 point.
+pub(crate) fn ecdh_pub_key_from_coords(coords: [u8; 160]) -> Result<PKey<Public>> {
+    EcPubKeyCoord(coords).try_into().map_err(Error::Crypto)
+}
+
 /// Get the pub ECDH coordinates in the format the Ultravisor expects it:
 /// The two coordinates are padded to 80 bytes each.
 fn get_pub_ecdh_points(pkey: &EcPointRef, grp: &EcGroupRef) -> Result<[u8; 160], ErrorStack> {
@@ -440,6 +451,7 @@ pub(crate) struct BinReqValues<'a> {
     tag: &'a [u8],
     version: u32,
     len: usize,
+    nks: u8,
 }
 impl<'a> BinReqValues<'a> {
     pub(crate) const TAG_LEN: usize = SymKeyType::AES_256_GCM_TAG_LEN;
@@ -453,7 +465,7 @@ impl<'a> BinReqValues<'a> {
         let rql = hdr.rql.get() as usize;
         let sea = hdr.sea.get() as usize;
 
-        if rql < req.len() || sea + Self::TAG_LEN > rql {
+        if rql > req.len() || sea + Self::TAG_LEN > rql {
             return Err(Error::BinRequestSmall);
         }
         let aad_size = rql - sea - Self::TAG_LEN;
@@ -465,7 +477,7 @@ impl<'a> BinReqValues<'a> {
         let aad = &req[..aad_size];
         let req_dep_aad = &req[size_of::<RequestHdr>()..aad_size];
         let encr = &req[aad_size..(aad_size + sea)];
-        let tag = &req[rql - Self::TAG_LEN..];
+        let tag = &req[rql - Self::TAG_LEN..rql];
 
         Ok(Self {
             iv,
@@ -475,6 +487,7 @@ impl<'a> BinReqValues<'a> {
             tag,
             version: hdr.rqvn.get(),
             len: rql,
+            nks: hdr.nks,
         })
     }
 
@@ -488,6 +501,11 @@ impl<'a> BinReqValues<'a> {
         self.len
     }
 
+    /// Returns the number of keyslots of this [`BinReqValues`].
+    pub(crate) fn nks(&self) -> u8 {
+        self.nks
+    }
+
     /// Returns the size of the encrypted area
     pub(crate) fn sea(&self) -> u32 {
         self.encr.len() as u32
@@ -511,6 +529,19 @@ impl<'a> BinReqValues<'a> {
         T::ref_from_prefix(self.req_dep_aad).map(|s| s.0).ok()
     }
 
+    /// Like [`Self::req_dep_aad`], but interprets the request dependent authenticated area
+    /// starting at `offset`.
+    ///
+    /// Returns None if the area is too small.
+    pub(crate) fn req_dep_aad_at<T>(&self, offset: usize) -> Option<&T>
+    where
+        T: FromBytes + Sized + Immutable + KnownLayout,
+    {
+        T::ref_from_prefix(self.req_dep_aad.get(offset..)?)
+            .map(|s| s.0)
+            .ok()
+    }
+
     /// Returns a reference to the tag of this [`BinReqValues`].
     pub(crate) fn tag(&self) -> &[u8] {
         self.tag
@@ -0,0 +1,703 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2024
+
+use std::{
+    fs,
+    io::{Read, Seek, SeekFrom},
+    mem::size_of,
+    path::Path,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use openssl::{
+    hash::MessageDigest,
+    pkey::{PKey, Private, Public},
+};
+use pv_core::{
+    misc::{open_file, read_file},
+    secret::AddSecretMagic,
+    FileAccessErrorType,
+};
+
+use crate::{
+    attest::{
+        AttestationAuthenticated, AttestationItems, AttestationMeasurement, AttestationRequest,
+    },
+    brcb::seek_se_hdr_start,
+    crypto::{decrypt_aead, derive_aes256_gcm_key, hash, random_array, SymKey, SymKeyType},
+    req::{ecdh_pub_key_from_coords, BinReqValues, EcPubKeyCoord, Keyslot},
+    request::{BootHdrTags, Confidential},
+    secret::{AddSecretVersion, ExtSecret},
+    uv::{
+        ioctl_nr, AddCmd, AttestationCmd, ConfigUid, ListCmd, ListableSecretType, LockCmd,
+        RetrieveCmd, SecretEntry, SecretId, SecretList, UvBackend, UvCmd, UvcPayload, UvcRc,
+    },
+    uvattest::additional::FW_STATE_SIZE,
+    uvsecret::{
+        asrcb::ReqAuthData,
+        guest_secret::NullSecretHdr,
+        user_data::{check_asrcb_user_data, USER_DATA_SIZE},
+    },
+    Error, PvCoreError, Result,
+};
+
+/// Add-secret flags the software UV accepts (flag 0 - disable dump)
+const ADD_SECRET_FLAGS: u64 = 0x8000_0000_0000_0000;
+/// Attestation flags the software UV accepts (flags 1-5)
+const ATTESTATION_FLAGS: u64 = 0x7c00_0000_0000_0000;
+/// Maximum size of attestation user-data
+const ATTESTATION_USER_MAX: usize = 0x100;
+/// Offset of the customer public key in the request dependent AAD of an SE-header
+const SE_HDR_CUST_KEY_OFFS: usize = 0x10;
+/// Offset of the keyslots in the request dependent AAD of an SE-header (after PLD, ALD, TLD)
+const SE_HDR_KEYSLOT_OFFS: usize = SE_HDR_CUST_KEY_OFFS + size_of::<EcPubKeyCoord>() + 3 * 0x40;
+
+fn uv_err(rc: u16, msg: &'static str) -> Error {
+    PvCoreError::Uv { rc, rrc: 0, msg }.into()
+}
+
+/// Maps a failed decryption to the UV return code `rc`
+fn decrypt_err(rc: u16, msg: &'static str) -> impl FnOnce(Error) -> Error {
+    move |e| match e {
+        Error::GcmTagMismatch => uv_err(rc, msg),
+        e => e,
+    }
+}
+
+/// Maps a failed decryption to `err`
+fn decrypt_err_to(err: Error) -> impl FnOnce(Error) -> Error {
+    move |e| match e {
+        Error::GcmTagMismatch => err,
+        e => e,
+    }
+}
+
+#[derive(Debug)]
+struct HostKey {
+    phkh: [u8; Keyslot::PHKH_SIZE as usize],
+    key: PKey<Private>,
+}
+
+#[derive(Debug)]
+struct StoredSecret {
+    entry: SecretEntry,
+    value: Confidential<Vec<u8>>,
+}
+
+/// Response of a successful attestation by the [`SoftUv`]
+#[derive(Debug)]
+pub struct SoftUvAttestation {
+    measurement: Vec<u8>,
+    additional: Option<Vec<u8>>,
+    cuid: ConfigUid,
+}
+
+impl SoftUvAttestation {
+    /// The attestation measurement
+    pub fn measurement(&self) -> &[u8] {
+        &self.measurement
+    }
+
+    /// The additional data requested by the attestation flags
+    pub fn additional(&self) -> Option<&[u8]> {
+        self.additional.as_deref()
+    }
+
+    /// The Configuration Unique ID of the guest
+    pub fn cuid(&self) -> &ConfigUid {
+        &self.cuid
+    }
+}
+
+/// A software Ultravisor for a single SE-guest
+///
+/// Processes add-secret and attestation requests like the Ultravisor does for the guest with
+/// the given SE-header tags. The private host-keys are required to unwrap the requests. This
+/// allows testing the whole lifecycle of requests on any architecture.
+///
+/// Failures that the Ultravisor reports with a return code are reported as
+/// [`PvCoreError::Uv`] with the same return code.
+///
+/// The software UV differs from the real one:
+/// - retrievable secrets are returned as provided, not as protected keys
+/// - the image public host-key hash is the hash of the host-key that unwraps the SE-header, or
+///   of the first host-key if the SE-header is not known
+/// - the firmware state is all zeros
+/// - the secret-store hash is emulated, see [`SoftUv::secret_store_hash`]
+/// - the user data of add-secret requests is checked against its type and signed user data is
+///   verified with the key given by [`SoftUv::set_user_data_key`]
+///
+/// DO NOT USE IN PRODUCTION CODE. All secrets are kept in process memory.
+#[derive(Debug)]
+pub struct SoftUv {
+    host_keys: Vec<HostKey>,
+    image_phkh: [u8; Keyslot::PHKH_SIZE as usize],
+    boot_tags: BootHdrTags,
+    cuid: ConfigUid,
+    ext_secret: ExtSecret,
+    secrets: Vec<StoredSecret>,
+    request_tags: Vec<u8>,
+    locked: bool,
+    user_data_key: Option<PKey<Public>>,
+}
+
+impl SoftUv {
+    /// Creates a software UV for the guest with the SE-header tags `boot_tags`.
+    ///
+    /// The guest gets a random Configuration Unique ID, and an all-zero extension secret. The
+    /// guest is assumed to be booted with the first host-key.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if no host-key is given or a host-key is no EC
+    /// SECP521R1 key.
+    pub fn new(host_keys: Vec<PKey<Private>>, boot_tags: BootHdrTags) -> Result<Self> {
+        if host_keys.is_empty() {
+            return Err(Error::NoHostkey);
+        }
+        let host_keys = host_keys
+            .into_iter()
+            .map(|key| {
+                let coords: EcPubKeyCoord = key.as_ref().try_into()?;
+                Ok(HostKey {
+                    // Panic: SHA256 digests are always 32 bytes long
+                    phkh: coords.sha256()?.as_ref().try_into().unwrap(),
+                    key,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            image_phkh: host_keys[0].phkh,
+            host_keys,
+            boot_tags,
+            cuid: random_array()?,
+            ext_secret: ExtSecret::Simple([0; 32].into()),
+            secrets: Vec::new(),
+            request_tags: Vec::new(),
+            locked: false,
+            user_data_key: None,
+        })
+    }
+
+    /// Creates a software UV for the guest of the SE-image `img`.
+    ///
+    /// The guest is booted with the host-key of the first keyslot of the SE-header that one of
+    /// `host_keys` can unwrap.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `img` contains no SE-header, none of `host_keys`
+    /// can unwrap the SE-header, or [`Self::new`] fails.
+    pub fn from_se_image<R>(host_keys: Vec<PKey<Private>>, img: &mut R) -> Result<Self>
+    where
+        R: Read + Seek,
+    {
+        let start = img.stream_position()?;
+        let mut uv = Self::new(host_keys, BootHdrTags::from_se_image(img)?)?;
+
+        img.seek(SeekFrom::Start(start))?;
+        if !seek_se_hdr_start(img)? {
+            return Err(Error::InvBootHdr);
+        }
+        let mut hdr = vec![0; 0x10];
+        img.read_exact(&mut hdr)?;
+        let size = u32::from_be_bytes(hdr[0xc..0x10].try_into().unwrap()) as usize;
+        if size < hdr.len() {
+            return Err(Error::InvBootHdr);
+        }
+        hdr.resize(size, 0);
+        img.read_exact(&mut hdr[0x10..])?;
+
+        let req = BinReqValues::get(&hdr)?;
+        let unwrapped = uv
+            .unwrap_prot_key(&req, SE_HDR_CUST_KEY_OFFS, SE_HDR_KEYSLOT_OFFS)
+            .map_err(decrypt_err_to(Error::InvBootHdr))?;
+        let Some((prot_key, host_key)) = unwrapped else {
+            return Err(Error::InvBootHdr);
+        };
+        let phkh = host_key.phkh;
+        // the header must be authentic
+        req.decrypt(&prot_key)
+            .map_err(decrypt_err_to(Error::InvBootHdr))?;
+        uv.image_phkh = phkh;
+        Ok(uv)
+    }
+
+    /// Creates a software UV from the files in `dir`.
+    ///
+    /// `dir` contains the SE-image (or just the SE-header) of the guest as `se.img` and the
+    /// private host-keys in PEM format as `*.key` files, ordered by their file names. This is the
+    /// `soft:DIR` [`UvBackendSpec`](crate::uv::UvBackendSpec).
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the files cannot be read or
+    /// [`Self::from_se_image`] fails.
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref();
+        let mut key_paths = fs::read_dir(dir)
+            .and_then(|entries| {
+                entries
+                    .map(|entry| entry.map(|entry| entry.path()))
+                    .collect::<std::io::Result<Vec<_>>>()
+            })
+            .map_err(|source| PvCoreError::FileAccess {
+                ty: FileAccessErrorType::Open,
+                path: dir.to_path_buf(),
+                source,
+            })?;
+        key_paths.retain(|path| path.extension().is_some_and(|ext| ext == "key"));
+        key_paths.sort();
+        let host_keys = key_paths
+            .iter()
+            .map(|path| Ok(PKey::private_key_from_pem(&read_file(path, "host-key")?)?))
+            .collect::<Result<_>>()?;
+        Self::from_se_image(host_keys, &mut open_file(dir.join("se.img"))?)
+    }
+
+    /// Sets the Configuration Unique ID of the guest.
+    pub fn set_cuid(&mut self, cuid: ConfigUid) {
+        self.cuid = cuid;
+    }
+
+    /// Returns the Configuration Unique ID of the guest.
+    pub fn cuid(&self) -> &ConfigUid {
+        &self.cuid
+    }
+
+    /// Sets the extension secret of the guest.
+    ///
+    /// A [`ExtSecret::Derived`] secret is derived from the CCK like in the SE-header.
+    pub fn set_ext_secret(&mut self, ext_secret: ExtSecret) {
+        self.ext_secret = ext_secret;
+    }
+
+    /// Sets the key to verify signed user data of add-secret requests.
+    ///
+    /// Without a key, only the format of the signature is checked.
+    pub fn set_user_data_key(&mut self, key: PKey<Public>) {
+        self.user_data_key = Some(key);
+    }
+
+    /// Returns whether the secret store is locked.
+    pub fn locked(&self) -> bool {
+        self.locked
+    }
+
+    /// Finds the first keyslot for one of the host-keys and unwraps its request protection key.
+    ///
+    /// `cust_key_offs` and `ks_offs` are the offsets of the customer public key and the keyslots
+    /// in the request dependent authenticated data.
+    fn unwrap_prot_key(
+        &self,
+        req: &BinReqValues,
+        cust_key_offs: usize,
+        ks_offs: usize,
+    ) -> Result<Option<(SymKey, &HostKey)>> {
+        let cust_key = req
+            .req_dep_aad_at::<[u8; size_of::<EcPubKeyCoord>()]>(cust_key_offs)
+            .ok_or(Error::BinRequestSmall)?;
+        let cust_key = ecdh_pub_key_from_coords(*cust_key)?;
+
+        for n in 0..req.nks() as usize {
+            let ks = req
+                .req_dep_aad_at::<[u8; Keyslot::BIN_SIZE]>(ks_offs + n * Keyslot::BIN_SIZE)
+                .ok_or(Error::BinRequestSmall)?;
+            let (phkh, wrapped) = ks.split_at(Keyslot::PHKH_SIZE as usize);
+            let Some(host_key) = self.host_keys.iter().find(|k| k.phkh == phkh) else {
+                continue;
+            };
+            let (wrapped, tag) = wrapped.split_at(SymKeyType::AES_256_GCM_KEY_LEN);
+            let derived = derive_aes256_gcm_key(&host_key.key, &cust_key)?;
+            let prot_key = decrypt_aead(&derived.into(), &[0; 12], &[], wrapped, tag)?;
+            let prot_key = SymKey::try_from_data(SymKeyType::Aes256Gcm, prot_key.into_plain())?;
+            return Ok(Some((prot_key, host_key)));
+        }
+        Ok(None)
+    }
+
+    /// Processes an add-secret request.
+    ///
+    /// Verifies that the request is bound to this guest (SE-header tags, CUID, and extension
+    /// secret) and adds the secret to the secret store. An update-CCK secret replaces the
+    /// extension secret with one derived from the new CCK.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request is malformed or the UV would reject it.
+    pub fn add_secret(&mut self, asrcb: &[u8]) -> Result<()> {
+        AddSecretMagic::try_from_bytes(asrcb)?;
+        let req = BinReqValues::get(asrcb)?;
+
+        if self.locked {
+            return Err(uv_err(0x0102, "secret store locked"));
+        }
+        if req.version() != AddSecretVersion::One as u32 {
+            return Err(uv_err(0x0104, "unsupported add secret version"));
+        }
+        if req.nks() == 0 {
+            return Err(uv_err(0x0106, "invalid number of host-keys"));
+        }
+        let auth: &ReqAuthData = req.req_dep_aad().ok_or(Error::BinRequestSmall)?;
+        if u64::from(auth.flags) & !ADD_SECRET_FLAGS != 0 {
+            return Err(uv_err(0x0107, "unsupported flags specified"));
+        }
+
+        let cust_key_offs = size_of::<ReqAuthData>() + USER_DATA_SIZE;
+        let (prot_key, _) = self
+            .unwrap_prot_key(
+                &req,
+                cust_key_offs,
+                cust_key_offs + size_of::<EcPubKeyCoord>(),
+            )
+            .map_err(decrypt_err(0x0108, "unable to decrypt the request"))?
+            .ok_or_else(|| uv_err(0x0108, "unable to decrypt the request"))?;
+        let conf = req
+            .decrypt(&prot_key)
+            .map_err(decrypt_err(0x0108, "unable to decrypt the request"))?;
+
+        // the request must target this guest
+        if auth.boot_tags != self.boot_tags
+            || (auth.cuid != ConfigUid::default() && auth.cuid != self.cuid)
+        {
+            return Err(uv_err(0x010c, "unable to add secret"));
+        }
+        let conf = conf.value();
+        if conf.len() < 32 {
+            return Err(uv_err(0x010a, "invalid length for the specified secret"));
+        }
+        let (secret, ext_secret) = conf.split_at(conf.len() - 32);
+        if ext_secret != self.ext_secret.value(&self.boot_tags)?.value() {
+            return Err(uv_err(0x010c, "unable to add secret"));
+        }
+        check_asrcb_user_data(asrcb, self.user_data_key.as_ref())?;
+
+        let hdr_offs =
+            cust_key_offs + size_of::<EcPubKeyCoord>() + req.nks() as usize * Keyslot::BIN_SIZE;
+        let hdr: &NullSecretHdr = req.req_dep_aad_at(hdr_offs).ok_or(Error::BinRequestSmall)?;
+        if hdr.secret_len.get() as usize != secret.len() {
+            return Err(uv_err(0x010a, "invalid length for the specified secret"));
+        }
+
+        match hdr.kind.get() {
+            ListableSecretType::NULL => (),
+            ListableSecretType::UPDATE_CCK => {
+                let cck: [u8; 32] = secret
+                    .try_into()
+                    .map_err(|_| uv_err(0x010a, "invalid length for the specified secret"))?;
+                self.ext_secret = ExtSecret::Derived(cck.into());
+            }
+            kind => {
+                if !matches!(
+                    kind.into(),
+                    ListableSecretType::Association | ListableSecretType::Retrievable(_)
+                ) {
+                    return Err(uv_err(0x0109, "unsupported secret provided"));
+                }
+                let id: &SecretId = req
+                    .req_dep_aad_at(hdr_offs + size_of::<NullSecretHdr>())
+                    .ok_or(Error::BinRequestSmall)?;
+                if self.secrets.iter().any(|s| s.entry.id() == id.as_ref()) {
+                    return Err(uv_err(0x010c, "unable to add secret"));
+                }
+                let index = self.secrets.len() as u16;
+                self.secrets.push(StoredSecret {
+                    entry: SecretEntry::new(index, kind.into(), id.clone(), secret.len() as u32),
+                    value: secret.to_vec().into(),
+                });
+            }
+        }
+
+        self.request_tags.extend_from_slice(req.tag());
+        Ok(())
+    }
+
+    /// Locks the secret store.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the secret store is already locked.
+    pub fn lock(&mut self) -> Result<()> {
+        if self.locked {
+            return Err(uv_err(0x0102, "secret store already locked"));
+        }
+        self.locked = true;
+        Ok(())
+    }
+
+    /// Lists the secrets in the secret store.
+    pub fn list(&self) -> SecretList {
+        self.secrets.iter().map(|s| s.entry.clone()).collect()
+    }
+
+    /// Retrieves the secret with the index `index` from the secret store.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is no such secret or the secret is not
+    /// retrievable.
+    pub fn retrieve(&self, index: u16) -> Result<Confidential<Vec<u8>>> {
+        if self.secrets.is_empty() {
+            return Err(uv_err(0x010f, "the Secret Store is empty"));
+        }
+        let secret = self
+            .secrets
+            .iter()
+            .find(|s| s.entry.index() == index)
+            .ok_or_else(|| {
+                uv_err(
+                    0x0110,
+                    "the Secret Store does not contain a secret with the specified index",
+                )
+            })?;
+        match secret.entry.stype() {
+            ListableSecretType::Retrievable(_) => Ok(secret.value.clone()),
+            _ => Err(uv_err(0x0111, "the secret is not retrievable")),
+        }
+    }
+
+    /// Calculates the emulated secret-store hash.
+    ///
+    /// SHA512 over the tags of all successfully added requests followed by the lock state (one
+    /// byte). This emulates the hash with the calculation 'pvattest check' uses to verify it.
+    /// It does not come from an Ultravisor, so tests must not use it as a reference value for a
+    /// real Ultravisor.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if OpenSSL could not calculate the hash.
+    pub fn secret_store_hash(&self) -> Result<Vec<u8>> {
+        let mut data = self.request_tags.clone();
+        data.push(self.locked as u8);
+        Ok(hash(MessageDigest::sha512(), &data)?.to_vec())
+    }
+
+    /// Processes an attestation request.
+    ///
+    /// Calculates the attestation measurement over the SE-header tags, the CUID, `user_data`,
+    /// the nonce, and the additional data requested by the attestation flags.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request is malformed or the UV would reject it.
+    pub fn attest(&self, arcb: &[u8], user_data: Option<&[u8]>) -> Result<SoftUvAttestation> {
+        const UNDECRYPTABLE: &str = "Unable to decrypt attestation request control block. Probably no valid host-key was provided";
+
+        let auth = AttestationRequest::auth_bin(arcb)?;
+        let req = BinReqValues::get(arcb)?;
+        if user_data.is_some_and(|u| u.len() > ATTESTATION_USER_MAX) {
+            return Err(uv_err(0x0102, "User data length is greater than 256"));
+        }
+        if req.nks() == 0 {
+            return Err(uv_err(
+                0x0108,
+                "Number of key slots is either equal to 0 or greater than the maximum number supported by the specified attestation request version",
+            ));
+        }
+        if auth.flags().bits() & !ATTESTATION_FLAGS != 0 {
+            return Err(uv_err(0x010a, "Unsupported plaintext attestation flag set"));
+        }
+
+        let cust_key_offs = size_of::<AttestationAuthenticated>();
+        let (prot_key, host_key) = self
+            .unwrap_prot_key(
+                &req,
+                cust_key_offs,
+                cust_key_offs + size_of::<EcPubKeyCoord>(),
+            )
+            .map_err(decrypt_err(0x010c, UNDECRYPTABLE))?
+            .ok_or_else(|| uv_err(0x010c, UNDECRYPTABLE))?;
+        let (auth, conf) = AttestationRequest::decrypt_bin(arcb, &prot_key)
+            .map_err(decrypt_err(0x010c, UNDECRYPTABLE))?;
+
+        let flags = auth.flags();
+        let mut additional = Vec::with_capacity(flags.expected_additional_size() as usize);
+        if flags.image_phkh() {
+            additional.extend_from_slice(&self.image_phkh);
+        }
+        if flags.attest_phkh() {
+            additional.extend_from_slice(&host_key.phkh);
+        }
+        if flags.secret_store_hash() {
+            additional.append(&mut self.secret_store_hash()?);
+        }
+        if flags.firmware_state() {
+            additional.resize(additional.len() + FW_STATE_SIZE as usize, 0);
+        }
+        let additional = (!additional.is_empty()).then_some(additional);
+
+        let items = AttestationItems::new(
+            &self.boot_tags,
+            &self.cuid,
+            user_data,
+            conf.nonce().as_ref().map(|n| n.value()),
+            additional.as_deref(),
+        );
+        let meas_key = PKey::hmac(conf.measurement_key())?;
+        let measurement = AttestationMeasurement::calculate(items, auth.mai(), &meas_key)?;
+
+        Ok(SoftUvAttestation {
+            measurement: measurement.as_ref().to_vec(),
+            additional,
+            cuid: self.cuid,
+        })
+    }
+}
+
+/// [`UvBackend`] that emulates the uvdevice with a [`SoftUv`]
+///
+/// Pass it to [`UvDevice::with_backend`](crate::uv::UvDevice::with_backend) to send
+/// Ultravisor commands to the software UV. Clones share the same software UV.
+///
+/// IOCTLs other than add-secret, list, lock, retrieve, and attestation fail with `ENOTTY`.
+#[derive(Debug, Clone)]
+pub struct SoftUvBackend(Arc<Mutex<SoftUv>>);
+
+impl SoftUvBackend {
+    /// Creates a new [`SoftUvBackend`] using `uv`.
+    pub fn new(uv: SoftUv) -> Self {
+        Self(Arc::new(Mutex::new(uv)))
+    }
+
+    /// Provides access to the software UV.
+    pub fn soft_uv(&self) -> MutexGuard<'_, SoftUv> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn dispatch(&self, cmd: u64, payload: UvcPayload<'_>) -> Result<UvcRc> {
+        const RC_SUCCESS: UvcRc = UvcRc::new(0x0001, 0);
+        const RC_MORE_DATA: UvcRc = UvcRc::new(0x0100, 0);
+
+        let mut uv = self.soft_uv();
+        match (ioctl_nr(cmd), payload) {
+            (AddCmd::UV_IOCTL_NR, UvcPayload::Data(asrcb)) => uv.add_secret(asrcb)?,
+            (ListCmd::UV_IOCTL_NR, UvcPayload::Data(buf)) => {
+                let mut list = Vec::with_capacity(buf.len());
+                uv.list().encode(&mut list)?;
+                let len = list.len().min(buf.len());
+                buf[..len].copy_from_slice(&list[..len]);
+                if len < list.len() {
+                    return Ok(RC_MORE_DATA);
+                }
+            }
+            (LockCmd::UV_IOCTL_NR, UvcPayload::None) => uv.lock()?,
+            (RetrieveCmd::UV_IOCTL_NR, UvcPayload::Data(buf)) if buf.len() >= 2 => {
+                let secret = uv.retrieve(u16::from_be_bytes([buf[0], buf[1]]))?;
+                let secret = secret.value();
+                if secret.len() > buf.len() {
+                    return Ok(UvcRc::new(0x0009, 0));
+                }
+                buf.fill(0);
+                buf[..secret.len()].copy_from_slice(secret);
+            }
+            (AttestationCmd::UV_IOCTL_NR, UvcPayload::Attestation(att)) => {
+                let user_data = (!att.user_data.is_empty()).then_some(att.user_data);
+                let res = uv.attest(att.arcb, user_data)?;
+                let additional = res.additional().unwrap_or_default();
+                if att.measurement.len() < res.measurement().len() {
+                    return Ok(UvcRc::new(0x010d, 0));
+                }
+                if att.additional.as_ref().map_or(0, |a| a.len()) < additional.len() {
+                    return Ok(UvcRc::new(0x010e, 0));
+                }
+                att.measurement[..res.measurement().len()].copy_from_slice(res.measurement());
+                if let Some(buf) = att.additional {
+                    buf[..additional.len()].copy_from_slice(additional);
+                }
+                *att.additional_len = additional.len() as u32;
+                *att.cuid = *res.cuid();
+            }
+            _ => {
+                let enotty = std::io::Error::from_raw_os_error(libc::ENOTTY);
+                return Err(PvCoreError::Io(enotty).into());
+            }
+        }
+        Ok(RC_SUCCESS)
+    }
+}
+
+impl UvBackend for SoftUvBackend {
+    fn call(&self, cmd: u64, payload: UvcPayload<'_>) -> pv_core::Result<UvcRc> {
+        match self.dispatch(cmd, payload) {
+            Ok(rc) => Ok(rc),
+            Err(Error::PvCore(PvCoreError::Uv { rc, rrc, .. })) => Ok(UvcRc::new(rc, rrc)),
+            Err(Error::PvCore(e)) => Err(e),
+            Err(e) => Err(PvCoreError::UvBackend(e.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{
+        attest::{AttestationFlags, AttestationMeasAlg, AttestationVersion},
+        req::ReqEncrCtx,
+        request::Request,
+        test_utils::{create_test_se_hdr, get_cert_asset, load_gen_cert},
+    };
+
+    fn host_key(name: &str) -> PKey<Private> {
+        PKey::private_key_from_pem(&get_cert_asset(name)).unwrap()
+    }
+
+    fn phkh(hkd: &str) -> Vec<u8> {
+        let coords: EcPubKeyCoord = load_gen_cert(hkd).public_key().unwrap().try_into().unwrap();
+        coords.sha256().unwrap().to_vec()
+    }
+
+    #[test]
+    fn image_phkh() {
+        let hdr = create_test_se_hdr(&["host.crt", "host2.crt"]);
+        let from_hdr = |keys: Vec<&str>| {
+            SoftUv::from_se_image(
+                keys.into_iter().map(host_key).collect(),
+                &mut Cursor::new(&hdr),
+            )
+        };
+
+        // only the second keyslot can be unwrapped
+        let uv = from_hdr(vec!["host2.key"]).unwrap();
+        assert_eq!(uv.image_phkh.as_slice(), phkh("host2.crt"));
+        assert_eq!(uv.boot_tags.tag(), &hdr[hdr.len() - 0x10..]);
+
+        // the first keyslot that can be unwrapped is used
+        let uv = from_hdr(vec!["host2.key", "host.key"]).unwrap();
+        assert_eq!(uv.image_phkh.as_slice(), phkh("host.crt"));
+
+        assert!(matches!(
+            from_hdr(vec!["fake_host.key"]),
+            Err(Error::InvBootHdr)
+        ));
+        let mut tampered = hdr.clone();
+        let encr = tampered.len() - 0x20;
+        tampered[encr] ^= 1;
+        assert!(matches!(
+            SoftUv::from_se_image(vec![host_key("host.key")], &mut Cursor::new(&tampered)),
+            Err(Error::InvBootHdr)
+        ));
+
+        // the attestation reports the image host-key, not the attesting one
+        let uv = from_hdr(vec!["host.key", "host2.key"]).unwrap();
+        let mut flags = AttestationFlags::default();
+        flags.set_image_phkh();
+        flags.set_attest_phkh();
+        let mut arcb = AttestationRequest::new(
+            AttestationVersion::One,
+            AttestationMeasAlg::HmacSha512,
+            flags,
+        )
+        .unwrap();
+        arcb.add_hostkey(load_gen_cert("host2.crt").public_key().unwrap());
+        let arcb = arcb
+            .encrypt(&ReqEncrCtx::random(SymKeyType::Aes256Gcm).unwrap())
+            .unwrap();
+        let additional = uv.attest(&arcb, None).unwrap().additional.unwrap();
+        assert_eq!(additional[..0x20], phkh("host.crt"));
+        assert_eq!(additional[0x20..], phkh("host2.crt"));
+    }
+}
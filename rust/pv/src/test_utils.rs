@@ -99,6 +99,15 @@ pub fn get_test_keys() -> (PKey<Private>, PKey<Public>) {
     (cust_key, host.public_key().unwrap())
 }
 
+/// TEST ONLY! Get a fixed host-key pair and its host-key document
+///
+/// Intended for TESTING only. All parts of the key including the private key are checked in git and
+/// visible for the public
+pub fn get_test_host_key_and_cert() -> (PKey<Private>, X509) {
+    let key = PKey::private_key_from_pem(&get_cert_asset("host.key")).unwrap();
+    (key, load_gen_cert("host.crt"))
+}
+
 fn read_ecdh_pubkey(coords: &[u8]) -> Result<PKey<Public>, ErrorStack> {
     assert!(coords.len() == 160);
     let x = BigNum::from_slice(&coords[..80])?;
@@ -122,3 +131,36 @@ fn get_keypair(pub_coords: &[u8], priv_num: &[u8]) -> Result<PKey<Private>, Erro
     key.check_key()?;
     PKey::from_ec_key(key)
 }
+
+/// TEST ONLY! Build an SE-header with a keyslot for each host-key document in the asset path
+///
+/// The header has fixed PLD, ALD, and TLD, and a random tag.
+///
+/// panic on errors
+pub fn create_test_se_hdr(hkds: &[&str]) -> Vec<u8> {
+    use crate::{
+        brcb::{BootHdrMagic, BootHdrTags},
+        req::{Aad, Keyslot, ReqEncrCtx},
+        request::{MagicValue, SymKeyType},
+    };
+
+    let tags = BootHdrTags::new([1; 64], [2; 64], [3; 64], [0; 16]);
+    let ctx = ReqEncrCtx::random(SymKeyType::Aes256Gcm).unwrap();
+    let keyslots: Vec<_> = hkds
+        .iter()
+        .map(|hkd| Keyslot::new(load_gen_cert(hkd).public_key().unwrap()))
+        .collect();
+    let cust_key = ctx.key_coords().unwrap();
+    let nep_pcf = [0; 0x10];
+    let mut aad = vec![
+        Aad::Plain(&nep_pcf),
+        Aad::Plain(cust_key.as_ref()),
+        Aad::Plain(&tags.as_ref()[..3 * 0x40]),
+    ];
+    aad.extend(keyslots.iter().map(Aad::Ks));
+    let conf = [0; 0x40];
+    let aad = ctx
+        .build_aad(0x100, &aad, conf.len(), BootHdrMagic::MAGIC)
+        .unwrap();
+    ctx.encrypt_aead(&aad, &conf).unwrap().into_buf()
+}
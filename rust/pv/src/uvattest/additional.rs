@@ -15,7 +15,7 @@ use super::arcb::AttestationFlags;
 pub(super) const PHKH_SIZE: u32 = 0x20;
 static_assert!(Keyslot::PHKH_SIZE == PHKH_SIZE);
 pub(super) const SECRET_STORE_HASH_SIZE: u32 = 0x40;
-pub(super) const FW_STATE_SIZE: u32 = 0x140;
+pub(crate) const FW_STATE_SIZE: u32 = 0x140;
 
 /// Additional-data of an Attestation Request
 #[derive(Serialize, Debug)]
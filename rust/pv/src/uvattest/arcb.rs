@@ -34,7 +34,8 @@ use crate::{
 ///
 /// An ARCB holds an Attestation Measurement key to attest a SE-guest.
 /// The (architectural optional) nonce is always used and freshly generated for a new
-/// [`AttestationRequest`].
+/// [`AttestationRequest`]. A verifier-chosen nonce can be set with
+/// [`AttestationRequest::set_nonce`].
 ///
 /// Layout:
 /// ```none
@@ -117,6 +118,14 @@ impl AttestationRequest {
         &self.aad.flags
     }
 
+    /// Replaces the randomly generated nonce of this [`AttestationRequest`].
+    ///
+    /// Use this if the verifier chooses the nonce, e.g. to prove the freshness of the
+    /// attestation response to a remote party.
+    pub fn set_nonce(&mut self, nonce: [u8; 16]) {
+        self.conf.value_mut().nonce = nonce;
+    }
+
     /// Returns a copy of the confidential data of this [`AttestationRequest`].
     ///
     /// Gives a copy of the confidential data of this request for further
@@ -142,7 +151,7 @@ impl AttestationRequest {
     }
 
     /// Checks for magic and returns [`BinReqValues`]
-    fn bin_values(arcb: &[u8]) -> Result<BinReqValues> {
+    fn bin_values(arcb: &[u8]) -> Result<BinReqValues<'_>> {
         if !AttestationMagic::starts_with_magic(arcb) {
             return Err(Error::NoArcb);
         }
@@ -155,6 +164,16 @@ impl AttestationRequest {
         Ok(values)
     }
 
+    /// Get the version of a binary attestation request.
+    ///
+    /// In contrast to [`Self::auth_bin`] this does not reject unknown versions.
+    pub fn bin_version(arcb: &[u8]) -> Result<u32> {
+        if !AttestationMagic::starts_with_magic(arcb) {
+            return Err(Error::NoArcb);
+        }
+        BinReqValues::get(arcb).map(|v| v.version())
+    }
+
     /// Returns the authenticated area of an binary attestation request.
     ///
     /// # Error
@@ -390,6 +409,11 @@ impl AttestationFlags {
     pub fn firmware_state(&self) -> bool {
         self.0.is_set(5)
     }
+
+    /// Returns the raw value of the flags
+    pub fn bits(&self) -> u64 {
+        self.0.into()
+    }
 }
 
 #[repr(C)]
@@ -480,6 +504,21 @@ mod test {
         assert_eq!(conf.nonce().as_ref().unwrap().value(), &NONCE);
     }
 
+    #[test]
+    fn set_nonce() {
+        let mut arcb = AttestationRequest::new(
+            AttestationVersion::One,
+            AttestationMeasAlg::HmacSha512,
+            AttestationFlags::default(),
+        )
+        .unwrap();
+        arcb.set_nonce(NONCE);
+        assert_eq!(
+            arcb.confidential_data().nonce().as_ref().unwrap().value(),
+            &NONCE
+        );
+    }
+
     #[test]
     fn decrypt_bin_fail_magic() {
         let arpk = SymKey::Aes256(ARPK.into());
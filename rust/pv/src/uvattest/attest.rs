@@ -4,8 +4,12 @@
 
 use super::AttNonce;
 use crate::{
-    attest::AttestationMeasAlg, brcb::BootHdrTags, crypto::calculate_hmac, request::Confidential,
-    uv::ConfigUid, Result,
+    attest::AttestationMeasAlg,
+    brcb::BootHdrTags,
+    crypto::{calculate_hmac, hash, SHA_512_HASH_LEN},
+    request::Confidential,
+    uv::ConfigUid,
+    Result,
 };
 use openssl::{
     hash::MessageDigest,
@@ -104,6 +108,35 @@ impl AttestationItems {
     }
 }
 
+/// Label prepended to the challenge by [`challenge_user_data`]
+const CHALLENGE_LABEL: &[u8] = b"s390-pv-attestation-challenge-v1";
+
+/// Derives attestation user-data that binds a verifier-chosen challenge.
+///
+/// The user-data is the SHA-512 digest over the ASCII label
+/// `s390-pv-attestation-challenge-v1` (without a terminating zero byte) followed by the
+/// challenge:
+///
+/// ```none
+/// user-data = SHA-512("s390-pv-attestation-challenge-v1" || challenge)
+/// ```
+///
+/// The challenge can be of arbitrary size. The resulting 64 bytes fit into the user-data of an
+/// attestation measurement. A verifier recalculates the user-data from the challenge it sent and
+/// compares it with the (verified) user-data of the attestation response.
+///
+/// # Errors
+///
+/// This function will return an error if OpenSSL could not calculate the hash.
+pub fn challenge_user_data(challenge: &[u8]) -> Result<[u8; SHA_512_HASH_LEN]> {
+    let mut data = Vec::with_capacity(CHALLENGE_LABEL.len() + challenge.len());
+    data.extend_from_slice(CHALLENGE_LABEL);
+    data.extend_from_slice(challenge);
+    let digest = hash(MessageDigest::sha512(), &data)?;
+    // Panic: SHA-512 digests are always 64 bytes long
+    Ok(digest.as_ref().try_into().unwrap())
+}
+
 /// Holds an attestation measurement
 #[derive(Debug)]
 #[allow(clippy::len_without_is_empty)]
@@ -242,4 +275,16 @@ mod test {
         assert_eq!(meas, EXP_HMAC[..]);
         assert!(meas.eq_secure(&EXP_HMAC[..]));
     }
+
+    #[test]
+    fn challenge() {
+        let user_data = challenge_user_data(b"challenge").unwrap();
+        let exp = hash(
+            MessageDigest::sha512(),
+            b"s390-pv-attestation-challenge-v1challenge",
+        )
+        .unwrap();
+        assert_eq!(&user_data, exp.as_ref());
+        assert_ne!(user_data, challenge_user_data(b"challenge2").unwrap());
+    }
 }
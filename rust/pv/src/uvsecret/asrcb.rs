@@ -2,31 +2,35 @@
 //
 // Copyright IBM Corp. 2023
 
-use super::{guest_secret::ListableSecretHdr, user_data::UserData};
+use super::{
+    guest_secret::ListableSecretHdr,
+    user_data::{UserData, USER_DATA_SIZE},
+};
 use crate::{
     assert_size,
-    crypto::{hkdf_rfc_5869, AeadEncryptionResult},
+    crypto::AeadEncryptionResult,
     misc::Flags,
-    req::{Aad, BinReqValues, Keyslot, ReqEncrCtx},
+    req::{Aad, BinReqValues, EcPubKeyCoord, Keyslot, ReqEncrCtx},
     request::{BootHdrTags, Confidential, Request},
     secret::{ExtSecret, GuestSecret},
     uv::{ConfigUid, UvFlags},
-    Result,
+    Error, Result,
 };
-use openssl::{
-    md::Md,
-    pkey::{PKey, Private, Public},
+use openssl::pkey::{PKey, Private, Public};
+use pv_core::{
+    request::RequestVersion,
+    secret::AddSecretMagic,
+    uv::{ListableSecretType, SecretId},
 };
-use pv_core::{request::RequestVersion, secret::AddSecretMagic, uv::SecretId};
-use zerocopy::{Immutable, IntoBytes};
+use zerocopy::{BigEndian, FromBytes, Immutable, IntoBytes, KnownLayout, U16, U64};
 
 /// Authenticated data w/o user data
 #[repr(C)]
-#[derive(Debug, Clone, Copy, IntoBytes, Immutable)]
-struct ReqAuthData {
-    flags: UvFlags,
-    boot_tags: BootHdrTags,
-    cuid: ConfigUid,
+#[derive(Debug, Clone, Copy, IntoBytes, FromBytes, Immutable, KnownLayout)]
+pub(crate) struct ReqAuthData {
+    pub(crate) flags: UvFlags,
+    pub(crate) boot_tags: BootHdrTags,
+    pub(crate) cuid: ConfigUid,
     reserved90: [u8; 0x100],
 }
 assert_size!(ReqAuthData, 0x1e8);
@@ -142,7 +146,7 @@ pub struct AddSecretRequest {
 
 impl AddSecretRequest {
     /// Offset of the user-data in the add-secret request in bytes
-    pub(super) const V1_USER_DATA_OFFS: usize = 0x218;
+    pub(crate) const V1_USER_DATA_OFFS: usize = 0x218;
 
     /// Create a new add-secret request.
     ///
@@ -177,17 +181,7 @@ impl AddSecretRequest {
     ///
     /// This function will return an error if the key derivation fails for a [`ExtSecret::Derived`].
     pub fn set_ext_secret(&mut self, ext_secret: ExtSecret) -> Result<()> {
-        const DER_EXT_SECRET_INFO: &[u8] = "IBM Z Ultravisor Add-Secret".as_bytes();
-        self.conf.extension_secret = match ext_secret {
-            ExtSecret::Simple(s) => s,
-            ExtSecret::Derived(cck) => hkdf_rfc_5869(
-                Md::sha512(),
-                cck.value(),
-                self.aad.boot_tags.tag(),
-                DER_EXT_SECRET_INFO,
-            )?
-            .into(),
-        };
+        self.conf.extension_secret = ext_secret.value(&self.aad.boot_tags)?;
         Ok(())
     }
 
@@ -290,6 +284,37 @@ impl AddSecretRequest {
             .map(|req| req.req_dep_aad::<ListableSecretHdr>().map(|a| a.id.clone()))
     }
 
+    /// Get the version of the add secret request
+    pub fn bin_version(asrcb: &[u8]) -> Result<u32> {
+        AddSecretMagic::try_from_bytes(asrcb)?;
+        BinReqValues::get(asrcb).map(|v| v.version())
+    }
+
+    /// Get the plaintext add-secret flags of the add secret request
+    pub fn bin_flags(asrcb: &[u8]) -> Result<u64> {
+        AddSecretMagic::try_from_bytes(asrcb)?;
+        BinReqValues::get(asrcb).and_then(|req| {
+            req.req_dep_aad::<U64<BigEndian>>()
+                .map(|f| f.get())
+                .ok_or(Error::BinRequestSmall)
+        })
+    }
+
+    /// Get the type of the secret in the add secret request
+    pub fn bin_secret_type(asrcb: &[u8]) -> Result<ListableSecretType> {
+        AddSecretMagic::try_from_bytes(asrcb)?;
+        let req = BinReqValues::get(asrcb)?;
+        // The secret header follows the user data, the customer public key and the keyslots.
+        // The secret type is its second half word.
+        let offs = size_of::<ReqAuthData>()
+            + USER_DATA_SIZE
+            + size_of::<EcPubKeyCoord>()
+            + req.nks() as usize * Keyslot::BIN_SIZE;
+        req.req_dep_aad_at::<[U16<BigEndian>; 2]>(offs)
+            .map(|hdr| hdr[1].into())
+            .ok_or(Error::BinRequestSmall)
+    }
+
     /// Get a copy of the add secret request tag
     pub fn bin_tag(asrcb: &[u8]) -> Result<Vec<u8>> {
         AddSecretMagic::try_from_bytes(asrcb)?;
@@ -2,7 +2,13 @@
 //
 // Copyright IBM Corp. 2023
 
-use crate::request::Confidential;
+use openssl::md::Md;
+
+use crate::{
+    crypto::hkdf_rfc_5869,
+    request::{BootHdrTags, Confidential},
+    Result,
+};
 
 /// Extension Secret for [`crate::secret::AddSecretRequest`]
 #[derive(Debug, Clone)]
@@ -12,3 +18,22 @@ pub enum ExtSecret {
     /// A secret that is derived from the Customer communication key from the SE-header
     Derived(Confidential<[u8; 32]>), // contains the cck
 }
+
+impl ExtSecret {
+    /// Returns the extension secret for the SE-guest with the given SE-header tags.
+    ///
+    /// Derived extension secrets are derived from the CCK and the SE-header tag.
+    pub(crate) fn value(&self, boot_tags: &BootHdrTags) -> Result<Confidential<[u8; 32]>> {
+        const DER_EXT_SECRET_INFO: &[u8] = "IBM Z Ultravisor Add-Secret".as_bytes();
+        match self {
+            Self::Simple(s) => Ok(s.clone()),
+            Self::Derived(cck) => Ok(hkdf_rfc_5869(
+                Md::sha512(),
+                cck.value(),
+                boot_tags.tag(),
+                DER_EXT_SECRET_INFO,
+            )?
+            .into()),
+        }
+    }
+}
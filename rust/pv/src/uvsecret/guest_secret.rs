@@ -408,10 +408,10 @@ impl SecretAuth {
 
 #[repr(C)]
 #[derive(Debug, IntoBytes, FromBytes, Immutable, KnownLayout)]
-struct NullSecretHdr {
+pub(crate) struct NullSecretHdr {
     res0: u16,
-    kind: U16<BigEndian>,
-    secret_len: U32<BigEndian>,
+    pub(crate) kind: U16<BigEndian>,
+    pub(crate) secret_len: U32<BigEndian>,
     res8: u64,
 }
 assert_size!(NullSecretHdr, 0x10);
@@ -15,7 +15,7 @@ use crate::{
 };
 use openssl::hash::MessageDigest;
 use openssl::nid::Nid;
-use zerocopy::{BigEndian, FromBytes, IntoBytes, KnownLayout, U16};
+use zerocopy::{BigEndian, FromBytes, Immutable, IntoBytes, KnownLayout, U16};
 
 /// User data.
 ///
@@ -59,7 +59,7 @@ pub(super) enum UserData {
 }
 
 #[repr(C)]
-#[derive(Debug, IntoBytes, FromBytes, KnownLayout)]
+#[derive(Debug, IntoBytes, FromBytes, KnownLayout, Immutable)]
 struct EcUserData {
     data: [u8; 256],
     signature: [u8; EC_SIGN_MAX_SIZE],
@@ -68,7 +68,7 @@ struct EcUserData {
     res_192: [u8; 110],
 }
 assert_size!(EcUserData, USER_DATA_SIZE);
-const USER_DATA_SIZE: usize = 0x200;
+pub(crate) const USER_DATA_SIZE: usize = 0x200;
 const EC_SIGN_MAX_SIZE: usize = 139;
 
 impl EcUserData {
@@ -90,6 +90,7 @@ impl EcUserData {
 #[derive(Debug, Clone)]
 pub(super) struct SignedUserData {
     sign_key: PKey<Private>,
+    kind: UserDataType,
     data: Vec<u8>,
 }
 
@@ -104,8 +105,11 @@ impl UserData {
                 None => Ok(false),
             }
         }
+        // Keys loaded from a provider, e.g. a PKCS#11 token, may not export their parameters.
+        // Check the public part instead.
+        let sign_key = PKey::public_key_from_der(&sign_key.public_key_to_der()?)?;
         match sign_key.id() {
-            Id::EC if check_curve(sign_key)? => Ok(UserDataType::SgnEcSECP521R1),
+            Id::EC if check_curve(&sign_key)? => Ok(UserDataType::SgnEcSECP521R1),
             Id::RSA if sign_key.rsa()?.size() == 2048 / 8 => Ok(UserDataType::SgnRsa2048),
             Id::RSA if sign_key.rsa()?.size() == 3072 / 8 => Ok(UserDataType::SgnRsa3072),
             _ => Err(Error::BinAsrcbUnsupportedUserDataSgnKey),
@@ -142,7 +146,11 @@ impl UserData {
             return Err(Error::AsrcbInvSgnUserData(kind));
         }
 
-        Ok(Self::Signed(SignedUserData { sign_key, data }))
+        Ok(Self::Signed(SignedUserData {
+            sign_key,
+            kind,
+            data,
+        }))
     }
 
     /// Signs data in buf, writes signature to `buf+user_data_offset+sign_offset` if applicable.
@@ -188,7 +196,7 @@ impl UserData {
         match self {
             Self::Null => UserDataType::Null,
             Self::Unsigned(_) => UserDataType::Unsigned,
-            Self::Signed(data) => Self::user_data_type(&data.sign_key).unwrap(),
+            Self::Signed(data) => data.kind,
         }
     }
 
@@ -310,6 +318,47 @@ pub fn verify_asrcb_and_get_user_data(
     }
 }
 
+/// Check that the user data of the add-secret request matches its type.
+///
+/// Without user data the user-data area must be all zeros. A signed user-data area must contain a
+/// signature. An EC signature must fit into the signature field. If `key` is given, the signature
+/// is verified with it.
+///
+/// # Errors
+///
+/// returns an error if the request is no add-secret request, the user data does not match its
+/// type, or the signature could not be verified with `key`.
+pub(crate) fn check_asrcb_user_data(asrcb: &[u8], key: Option<&PKey<Public>>) -> Result<()> {
+    let kind = AddSecretMagic::try_from_bytes(asrcb)?.kind();
+    let req_len = BinReqValues::get(asrcb)?.len();
+    if req_len < AddSecretRequest::V1_USER_DATA_OFFS + UserData::USER_DATA_SIZE {
+        return Err(pv_core::Error::NoAsrcb.into());
+    }
+    let user_data = &asrcb[AddSecretRequest::V1_USER_DATA_OFFS..][..UserData::USER_DATA_SIZE];
+
+    let sgn_ok = match kind {
+        UserDataType::Null if user_data.iter().any(|b| *b != 0) => {
+            return Err(Error::AsrcbInvSgnUserData(kind))
+        }
+        UserDataType::Null | UserDataType::Unsigned => return Ok(()),
+        UserDataType::SgnEcSECP521R1 => {
+            // Panic: will not panic, user_data is 512 bytes long
+            let (ec, _) = EcUserData::ref_from_prefix(user_data).unwrap();
+            (1..=EC_SIGN_MAX_SIZE).contains(&(ec.sgn_size.get() as usize))
+        }
+        UserDataType::SgnRsa2048 | UserDataType::SgnRsa3072 => {
+            user_data[kind.max()..].iter().any(|b| *b != 0)
+        }
+    };
+    if !sgn_ok {
+        return Err(Error::AsrcbUserDataSgnFail);
+    }
+    if let Some(key) = key {
+        verify_asrcb_and_get_user_data(asrcb.to_vec(), Some(key.clone()))?;
+    }
+    Ok(())
+}
+
 // Internal representation of the 512 bytes of user-data, signing-algorithm agnostic
 struct VerifiedUserData {
     data: Vec<u8>,
@@ -380,7 +429,12 @@ impl From<VerifiedUserData> for Vec<u8> {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::{get_test_asset, test_utils::get_test_keys};
+    use crate::{
+        get_test_asset,
+        request::{BootHdrTags, ReqEncrCtx, Request, SymKeyType},
+        secret::GuestSecret,
+        test_utils::get_test_keys,
+    };
 
     #[test]
     fn sign_null() {
@@ -544,6 +598,48 @@ mod test {
         assert!(matches!(res, Err(Error::AsrcbUserDataKeyMismatch { .. })));
     }
 
+    #[test]
+    fn check_asrcb() {
+        let mut asrcb = get_test_asset!("exp/asrcb/null_none_default_ncuid_one").to_vec();
+        check_asrcb_user_data(&asrcb, None).unwrap();
+        asrcb[AddSecretRequest::V1_USER_DATA_OFFS] = 1;
+        assert!(matches!(
+            check_asrcb_user_data(&asrcb, None),
+            Err(Error::AsrcbInvSgnUserData(UserDataType::Null))
+        ));
+
+        let rsa = get_test_asset!("keys/rsa2048key.pem");
+        let rsa = PKey::private_key_from_pem(rsa).unwrap();
+        let rsa_pub = PKey::public_key_from_pem(&rsa.public_key_to_pem().unwrap()).unwrap();
+        let (_, host_key) = get_test_keys();
+        let mut req = AddSecretRequest::new(
+            AddSecretVersion::One,
+            GuestSecret::Null,
+            BootHdrTags::new([1; 64], [2; 64], [3; 64], [4; 16]),
+            Default::default(),
+        );
+        req.add_hostkey(host_key);
+        req.set_user_data(vec![0x11; 0x100], Some(rsa)).unwrap();
+        let mut asrcb = req
+            .encrypt(&ReqEncrCtx::random(SymKeyType::Aes256Gcm).unwrap())
+            .unwrap();
+        check_asrcb_user_data(&asrcb, None).unwrap();
+        check_asrcb_user_data(&asrcb, Some(&rsa_pub)).unwrap();
+
+        let (_, ec) = get_test_keys();
+        assert!(matches!(
+            check_asrcb_user_data(&asrcb, Some(&ec)),
+            Err(Error::AsrcbUserDataKeyMismatch { .. })
+        ));
+
+        let sgn_offs = AddSecretRequest::V1_USER_DATA_OFFS + UserDataType::SgnRsa2048.max();
+        asrcb[sgn_offs..AddSecretRequest::V1_USER_DATA_OFFS + USER_DATA_SIZE].fill(0);
+        assert!(matches!(
+            check_asrcb_user_data(&asrcb, None),
+            Err(Error::AsrcbUserDataSgnFail)
+        ));
+    }
+
     #[test]
     fn kind() {
         let (ec, _) = get_test_keys();
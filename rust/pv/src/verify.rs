@@ -11,18 +11,24 @@ use openssl::x509::store::X509Store;
 use openssl::x509::{CrlStatus, X509NameRef, X509Ref, X509StoreContext, X509StoreContextRef, X509};
 use std::path::Path;
 
-#[cfg(not(test))]
-use helper::download_first_crl_from_x509;
-#[cfg(test)]
-use test::download_first_crl_from_x509;
+#[cfg(all(feature = "network", not(test)))]
+use helper::download_crl;
+#[cfg(all(feature = "network", test))]
+use test::download_crl;
 
 use crate::error::bail_hkd_verify;
 use crate::misc::{read_certs, read_file};
 use crate::Result;
 
+mod crl;
 mod helper;
 mod test;
 
+#[cfg(feature = "network")]
+pub use crl::HttpCrlFetcher;
+pub use crl::{CrlFetcher, DirCrlFetcher, NoCrlFetcher};
+pub use helper::x509_dist_points;
+
 /// A `HkdVerifier` verifies that a host-key document(HKD) can be trusted.
 ///
 /// If the verification fails the HKD should not be used to create requests.
@@ -49,7 +55,8 @@ impl HkdVerifier for NoVerifyHkd {
 pub struct CertVerifier {
     store: X509Store,
     ibm_z_sign_key: X509,
-    offline: bool,
+    fetcher: Box<dyn CrlFetcher>,
+    verify_time: Option<i64>,
 }
 
 impl std::fmt::Debug for CertVerifier {
@@ -58,6 +65,68 @@ impl std::fmt::Debug for CertVerifier {
     }
 }
 
+/// A step of the host-key document verification by [`CertVerifier`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HkdVerifyStep {
+    /// The host key has enough bits of security
+    SecurityLevel,
+    /// The host-key document is inside its validity period
+    Validity,
+    /// The Authority Key ID matches the IBM Z signing key
+    AuthorityKeyId,
+    /// The IBM Z signing key signed the host-key document
+    Signature,
+    /// At least one valid CRL of the IBM Z signing key is available
+    Crl,
+    /// The host-key document is not revoked
+    Revocation,
+}
+
+impl HkdVerifyStep {
+    /// All steps in the order [`CertVerifier`] performs them
+    pub const ALL: [Self; 6] = [
+        Self::SecurityLevel,
+        Self::Validity,
+        Self::AuthorityKeyId,
+        Self::Signature,
+        Self::Crl,
+        Self::Revocation,
+    ];
+}
+
+impl std::fmt::Display for HkdVerifyStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::SecurityLevel => "Security level",
+            Self::Validity => "Validity period",
+            Self::AuthorityKeyId => "Authority key ID",
+            Self::Signature => "Signature",
+            Self::Crl => "CRL",
+            Self::Revocation => "Revocation status",
+        })
+    }
+}
+
+/// Outcome of a single [`HkdVerifyStep`]
+#[derive(Debug)]
+pub enum HkdStepOutcome {
+    /// The step passed
+    Passed,
+    /// The step failed
+    Failed(crate::Error),
+    /// The step was not performed as a step it depends on failed
+    Skipped,
+}
+
+impl From<Result<()>> for HkdStepOutcome {
+    fn from(res: Result<()>) -> Self {
+        match res {
+            Ok(()) => Self::Passed,
+            Err(err) => Self::Failed(err),
+        }
+    }
+}
+
 impl HkdVerifier for CertVerifier {
     /// This function verifies a host-key
     /// document. To do so multiple steps are required:
@@ -66,36 +135,97 @@ impl HkdVerifier for CertVerifier {
     /// 2. Signature verification
     /// 3. @hkd must not be expired
     /// 4. @hkd must not be revoked
+    ///
+    /// The verification stops at the first failing [`HkdVerifyStep`].
     fn verify(&self, hkd: &X509Ref) -> Result<()> {
-        helper::verify_hkd_options(hkd, &self.ibm_z_sign_key)?;
+        helper::verify_hkd_security_bits(hkd)?;
+        helper::verify_hkd_validity(hkd, self.verify_time)?;
+        helper::verify_hkd_akid(hkd, &self.ibm_z_sign_key)?;
+        self.verify_signature(hkd)?;
+        let crls = self.verified_crls(hkd)?;
+        Self::verify_not_revoked(hkd, &crls)?;
+        debug!("HKD: verified");
+        Ok(())
+    }
+}
 
-        // verify that the HKD was signed with the key of the IBM signing key
+impl CertVerifier {
+    /// Verifies a host-key document and returns the outcome of each step.
+    ///
+    /// Other than [`HkdVerifier::verify`] this does not stop at the first failing step. All
+    /// steps are performed, in the order of [`HkdVerifyStep::ALL`]. Only the revocation status is
+    /// skipped if no valid CRL is available.
+    pub fn verify_steps(&self, hkd: &X509Ref) -> Vec<(HkdVerifyStep, HkdStepOutcome)> {
+        let mut steps = vec![
+            (
+                HkdVerifyStep::SecurityLevel,
+                helper::verify_hkd_security_bits(hkd).into(),
+            ),
+            (
+                HkdVerifyStep::Validity,
+                helper::verify_hkd_validity(hkd, self.verify_time).into(),
+            ),
+            (
+                HkdVerifyStep::AuthorityKeyId,
+                helper::verify_hkd_akid(hkd, &self.ibm_z_sign_key).into(),
+            ),
+            (HkdVerifyStep::Signature, self.verify_signature(hkd).into()),
+        ];
+        match self.verified_crls(hkd) {
+            Ok(crls) => {
+                steps.push((HkdVerifyStep::Crl, HkdStepOutcome::Passed));
+                steps.push((
+                    HkdVerifyStep::Revocation,
+                    Self::verify_not_revoked(hkd, &crls).into(),
+                ));
+            }
+            Err(err) => {
+                steps.push((HkdVerifyStep::Crl, HkdStepOutcome::Failed(err)));
+                steps.push((HkdVerifyStep::Revocation, HkdStepOutcome::Skipped));
+            }
+        }
+        steps
+    }
+
+    /// Verify that the HKD was signed with the key of the IBM signing key
+    fn verify_signature(&self, hkd: &X509Ref) -> Result<()> {
         if !hkd.verify(self.ibm_z_sign_key.public_key()?.as_ref())? {
             bail_hkd_verify!(Signature);
         }
+        Ok(())
+    }
 
-        // Find matching CRL for sign key in the store or download them
-        let crls = self.hkd_crls(hkd)?;
+    /// Find matching CRLs for the sign key in the store or download them and keep the valid ones
+    ///
+    /// Error if no valid CRL is available.
+    fn verified_crls(&self, hkd: &X509Ref) -> Result<Vec<StackableX509Crl>> {
+        let mut crls = self.hkd_crls(hkd)?;
 
         // Verify that the CRLs are still valid
         let mut verified_crls = Vec::with_capacity(crls.len());
-        for crl in &crls {
-            if helper::verify_crl(crl, &self.ibm_z_sign_key).is_some() {
-                verified_crls.push(crl.to_owned());
+        while let Some(crl) = crls.pop() {
+            if helper::verify_crl(&crl, &self.ibm_z_sign_key, self.verify_time).is_some() {
+                verified_crls.push(crl);
+            } else {
+                self.fetcher.reject(hkd, &crl);
             }
         }
 
-        // Test if HKD was revoked (min1 required)
+        // min1 required
         if verified_crls.is_empty() {
             bail_hkd_verify!(NoCrl);
         }
-        for crl in verified_crls {
+        Ok(verified_crls)
+    }
+
+    /// Test if HKD was revoked
+    fn verify_not_revoked(hkd: &X509Ref, crls: &[StackableX509Crl]) -> Result<()> {
+        for crl in crls {
             match crl.get_by_serial(hkd.serial_number()) {
                 CrlStatus::NotRevoked => (),
                 _ => bail_hkd_verify!(HkdRevoked),
             }
         }
-        debug!("HKD: verified");
         Ok(())
     }
 }
@@ -140,7 +270,7 @@ impl CertVerifier {
         Stack::new()
     }
 
-    /// Download the CRLs that a HKD refers to.
+    /// Get the CRLs that a HKD refers to from the store or the [`CrlFetcher`].
     pub fn hkd_crls(&self, hkd: &X509Ref) -> Result<Stack<StackableX509Crl>> {
         let mut ctx = X509StoreContext::new()?;
         // Unfortunately we cannot use a dedicated function here and have to use a closure (E0434)
@@ -151,11 +281,9 @@ impl CertVerifier {
             Self::quirk_crls(ctx, subject)
         })?;
 
-        if !self.offline {
-            // Try to download a CRL if defined in the HKD
-            if let Some(crl) = download_first_crl_from_x509(hkd)? {
-                crl.into_iter().try_for_each(|c| crls.push(c.into()))?;
-            }
+        // Try to fetch a CRL if defined in the HKD
+        if let Some(crl) = self.fetcher.fetch(hkd)? {
+            crl.into_iter().try_for_each(|c| crls.push(c.into()))?;
         }
         Ok(crls)
     }
@@ -168,11 +296,12 @@ impl CertVerifier {
     /// * `crl_paths` - Paths to certificate revocation lists for the chain of trust
     /// * `root_ca_path` - Path to the root of trust
     /// * `offline` - if set to true the verification process will not try to download CRLs from the
-    ///               internet.
+    ///   internet.
     ///
     /// # Errors
     ///
-    /// This function will return an error if the chain of trust could not be established.
+    /// This function will return an error if the chain of trust could not be established, or if
+    /// `offline` is false and the `network` feature is disabled.
     pub fn new<P, Q, R>(
         cert_paths: &[P],
         crl_paths: &[Q],
@@ -184,15 +313,55 @@ impl CertVerifier {
         Q: AsRef<Path>,
         R: AsRef<Path>,
     {
-        let mut store = helper::store_setup(root_ca_path, crl_paths, cert_paths)?;
+        match offline {
+            true => Self::with_fetcher(cert_paths, crl_paths, root_ca_path, NoCrlFetcher, None),
+            #[cfg(feature = "network")]
+            false => Self::with_fetcher(
+                cert_paths,
+                crl_paths,
+                root_ca_path,
+                HttpCrlFetcher::new(),
+                None,
+            ),
+            #[cfg(not(feature = "network"))]
+            false => Err(crate::Error::NoNetwork("download CRLs")),
+        }
+    }
+
+    /// Create a `CertVerifier` that gets missing CRLs from `fetcher`.
+    ///
+    /// * `cert_paths` - Paths to certificates for the chain of trust
+    /// * `crl_paths` - Paths to certificate revocation lists for the chain of trust
+    /// * `root_ca_path` - Path to the root of trust
+    /// * `fetcher` - Provides the CRLs of the certificates and host-key documents
+    /// * `verify_time` - Point in time (seconds since the Epoch) at which the chain of trust, the
+    ///   CRLs, and the host-key documents must be valid. `None` verifies at the current time.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the chain of trust could not be established.
+    pub fn with_fetcher<P, Q, R, F>(
+        cert_paths: &[P],
+        crl_paths: &[Q],
+        root_ca_path: Option<R>,
+        fetcher: F,
+        verify_time: Option<i64>,
+    ) -> Result<Self>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+        R: AsRef<Path>,
+        F: CrlFetcher + 'static,
+    {
+        let mut store = helper::store_setup(root_ca_path, crl_paths, cert_paths, verify_time)?;
         let mut untr_certs = Vec::with_capacity(cert_paths.len());
+        let mut fetched = Vec::new();
         for path in cert_paths {
             let mut crt = read_certs(&read_file(path, "certificate")?)?;
-            if !offline {
-                for c in &crt {
-                    if let Some(crl) = download_first_crl_from_x509(c)? {
-                        crl.iter().try_for_each(|c| store.add_crl(c))?;
-                    }
+            for c in &crt {
+                if let Some(crl) = fetcher.fetch(c)? {
+                    crl.iter().try_for_each(|c| store.add_crl(c))?;
+                    fetched.push((c.clone(), crl));
                 }
             }
             untr_certs.append(&mut crt);
@@ -203,12 +372,19 @@ impl CertVerifier {
         let (ibm_z_sign_key, chain) = helper::extract_ibm_sign_key(untr_certs)?;
 
         let store = store.build();
-        helper::verify_chain(&store, &chain, slice::from_ref(&ibm_z_sign_key))?;
+        if let Err(e) = helper::verify_chain(&store, &chain, slice::from_ref(&ibm_z_sign_key)) {
+            // Any of the fetched CRLs might be the culprit, do not get them again
+            for (cert, crls) in &fetched {
+                crls.iter().for_each(|crl| fetcher.reject(cert, crl));
+            }
+            return Err(e);
+        }
 
         Ok(Self {
             store,
             ibm_z_sign_key,
-            offline,
+            fetcher: Box::new(fetcher),
+            verify_time,
         })
     }
 }
@@ -0,0 +1,345 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use super::helper::x509_dist_points;
+use crate::{
+    crypto::hash, misc::encode_hex, utils::read_crls, FileAccessErrorType, PvCoreError, Result,
+};
+use log::debug;
+use openssl::{
+    hash::MessageDigest,
+    x509::{X509Crl, X509CrlRef, X509Ref},
+};
+use std::{
+    fmt::Debug,
+    fs,
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "network")]
+use {super::download_crl, log::warn, openssl::asn1::Asn1Time, std::cmp::Ordering};
+
+/// A `CrlFetcher` provides the certificate revocation lists (CRLs) for a certificate.
+///
+/// Used by [`CertVerifier`](super::CertVerifier) to get the CRLs for the certificates of the
+/// chain of trust and the host-key documents that are not given explicitly.
+pub trait CrlFetcher: Debug {
+    /// Fetches the CRLs that `cert` refers to.
+    ///
+    /// Returns `None` if no CRL is available for `cert`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if something unexpected happened. CRLs that are not
+    /// available or have an unexpected format are no errors.
+    fn fetch(&self, cert: &X509Ref) -> Result<Option<Vec<X509Crl>>>;
+
+    /// Reports that `crl`, provided for `cert`, did not pass the verification.
+    ///
+    /// Fetchers that keep CRLs between calls must not provide this CRL again.
+    fn reject(&self, _cert: &X509Ref, _crl: &X509CrlRef) {}
+}
+
+/// A fetcher that never provides any CRL.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoCrlFetcher;
+
+impl<T: CrlFetcher + ?Sized> CrlFetcher for Box<T> {
+    fn fetch(&self, cert: &X509Ref) -> Result<Option<Vec<X509Crl>>> {
+        (**self).fetch(cert)
+    }
+
+    fn reject(&self, cert: &X509Ref, crl: &X509CrlRef) {
+        (**self).reject(cert, crl)
+    }
+}
+
+impl CrlFetcher for NoCrlFetcher {
+    fn fetch(&self, _cert: &X509Ref) -> Result<Option<Vec<X509Crl>>> {
+        Ok(None)
+    }
+}
+
+/// Name of the file that holds the CRL from `url` in a CRL directory.
+fn crl_file_name(url: &str) -> Result<String> {
+    Ok(format!(
+        "{}.crl",
+        encode_hex(hash(MessageDigest::sha256(), url.as_bytes())?)
+    ))
+}
+
+/// Returns true if no CRL has passed its nextUpdate time.
+#[cfg(feature = "network")]
+fn crls_fresh(crls: &[X509Crl]) -> Result<bool> {
+    let now = Asn1Time::days_from_now(0)?;
+    for crl in crls {
+        match crl.next_update() {
+            Some(next) if now.compare(next)? == Ordering::Less => (),
+            _ => return Ok(false),
+        }
+    }
+    Ok(true)
+}
+
+/// Reads the CRLs in `path`. Missing or unparsable files are mapped to `None`.
+fn read_crl_file(path: &Path) -> Option<Vec<X509Crl>> {
+    let crls = read_crls(fs::read(path).ok()?).ok()?;
+    (!crls.is_empty()).then_some(crls)
+}
+
+/// A fetcher that downloads CRLs from the distribution points of a certificate.
+///
+/// Stops after the first successful download. If a cache directory is given, downloaded CRLs
+/// are stored in it and reused until their nextUpdate time has passed or until they are
+/// rejected by the verification. The cache directory can be used as bundle for a
+/// [`DirCrlFetcher`].
+#[cfg(feature = "network")]
+#[derive(Debug, Default, Clone)]
+pub struct HttpCrlFetcher {
+    cache: Option<PathBuf>,
+}
+
+#[cfg(feature = "network")]
+impl HttpCrlFetcher {
+    /// Creates a new [`HttpCrlFetcher`] without cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new [`HttpCrlFetcher`] that caches the CRLs in `dir`.
+    ///
+    /// Creates the directory if it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the directory cannot be created.
+    pub fn with_cache<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir).map_err(|source| PvCoreError::FileAccess {
+            ty: FileAccessErrorType::Create,
+            path: dir.to_path_buf(),
+            source,
+        })?;
+        Ok(Self {
+            cache: Some(dir.to_path_buf()),
+        })
+    }
+
+    fn store(path: &Path, data: &[u8]) {
+        let tmp = path.with_extension("tmp");
+        if let Err(e) = fs::write(&tmp, data).and_then(|_| fs::rename(&tmp, path)) {
+            let _ = fs::remove_file(&tmp);
+            warn!("Cannot cache the CRL at '{}': {e}", path.display());
+        }
+    }
+}
+
+#[cfg(feature = "network")]
+impl CrlFetcher for HttpCrlFetcher {
+    fn fetch(&self, cert: &X509Ref) -> Result<Option<Vec<X509Crl>>> {
+        let mut stale = None;
+        for dist_point in x509_dist_points(cert) {
+            let cache = match &self.cache {
+                Some(dir) => Some(dir.join(crl_file_name(&dist_point)?)),
+                None => None,
+            };
+            if let Some(crls) = cache.as_deref().and_then(read_crl_file) {
+                if crls_fresh(&crls)? {
+                    debug!("Use cached CRL for '{dist_point}'");
+                    return Ok(Some(crls));
+                }
+                stale.get_or_insert(crls);
+            }
+
+            let data = match download_crl(&dist_point)? {
+                Some(data) => data,
+                None => continue,
+            };
+            match read_crls(&data) {
+                Ok(crls) if !crls.is_empty() => {
+                    if let Some(cache) = &cache {
+                        Self::store(cache, &data);
+                    }
+                    return Ok(Some(crls));
+                }
+                _ => continue,
+            }
+        }
+        if stale.is_some() {
+            debug!("Cannot download a CRL; use the outdated cached CRL");
+        }
+        Ok(stale)
+    }
+
+    fn reject(&self, cert: &X509Ref, crl: &X509CrlRef) {
+        let (Some(dir), Ok(der)) = (&self.cache, crl.to_der()) else {
+            return;
+        };
+        for dist_point in x509_dist_points(cert) {
+            let Ok(path) = crl_file_name(&dist_point).map(|name| dir.join(name)) else {
+                continue;
+            };
+            let cached = read_crl_file(&path).unwrap_or_default();
+            if cached.iter().any(|c| c.to_der().is_ok_and(|c| c == der)) {
+                debug!("Remove rejected CRL for '{dist_point}' from the cache");
+                if let Err(e) = fs::remove_file(&path) {
+                    warn!("Cannot remove the CRL at '{}': {e}", path.display());
+                }
+            }
+        }
+    }
+}
+
+/// A fetcher that reads CRLs from a directory of pre-fetched CRLs.
+///
+/// The directory may be the cache directory of a `HttpCrlFetcher`. Otherwise, all CRLs
+/// in the directory that are issued by the issuer of the certificate are used.
+#[derive(Debug, Clone)]
+pub struct DirCrlFetcher {
+    dir: PathBuf,
+}
+
+impl DirCrlFetcher {
+    /// Creates a new [`DirCrlFetcher`] that reads the CRLs from `dir`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `dir` is not a readable directory.
+    pub fn new<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref();
+        fs::read_dir(dir).map_err(|source| PvCoreError::FileAccess {
+            ty: FileAccessErrorType::Open,
+            path: dir.to_path_buf(),
+            source,
+        })?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+        })
+    }
+}
+
+impl CrlFetcher for DirCrlFetcher {
+    fn fetch(&self, cert: &X509Ref) -> Result<Option<Vec<X509Crl>>> {
+        for dist_point in x509_dist_points(cert) {
+            if let Some(crls) = read_crl_file(&self.dir.join(crl_file_name(&dist_point)?)) {
+                debug!("Use bundled CRL for '{dist_point}'");
+                return Ok(Some(crls));
+            }
+        }
+
+        let issuer = cert.issuer_name().to_der()?;
+        let mut res = vec![];
+        for entry in fs::read_dir(&self.dir)?.flatten() {
+            for crl in read_crl_file(&entry.path()).unwrap_or_default() {
+                if crl.issuer_name().to_der()? == issuer {
+                    res.push(crl);
+                }
+            }
+        }
+        Ok((!res.is_empty()).then_some(res))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::{get_cert_asset_path, load_gen_cert};
+
+    fn tmp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("pv_crl_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    #[cfg(feature = "network")]
+    fn http_cache() {
+        let dir = tmp_dir("http");
+        let crt = load_gen_cert("ibm.crt");
+
+        let fetcher = HttpCrlFetcher::with_cache(&dir).unwrap();
+        let crls = fetcher.fetch(&crt).unwrap().unwrap();
+        let cached = dir.join(crl_file_name("inter_ca.crl").unwrap());
+        assert!(cached.exists());
+        assert_eq!(
+            fetcher.fetch(&crt).unwrap().unwrap()[0].to_der().unwrap(),
+            crls[0].to_der().unwrap()
+        );
+
+        // the cache directory can be used as bundle
+        let bundle = DirCrlFetcher::new(&dir).unwrap();
+        assert_eq!(bundle.fetch(&crt).unwrap().unwrap().len(), crls.len());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "network")]
+    fn http_cache_evict_rejected() {
+        use crate::{
+            verify::{CertVerifier, HkdVerifier},
+            Error, HkdVerifyErrorType,
+        };
+
+        let dir = tmp_dir("evict");
+        let hkd = load_gen_cert("host.crt");
+        let certs = [
+            get_cert_asset_path("ibm.crt"),
+            get_cert_asset_path("inter_ca.crt"),
+        ];
+        let crls = [get_cert_asset_path("inter_ca.crl")];
+        let root_crt = get_cert_asset_path("root_ca.chained.crt");
+
+        // a fresh CRL with the issuer name of the IBM Z signing key but a different signer
+        let cached = dir.join(crl_file_name("ibm.crl").unwrap());
+        fs::create_dir_all(&dir).unwrap();
+        fs::copy(get_cert_asset_path("fake_ibm.crl"), &cached).unwrap();
+
+        let verifier = CertVerifier::with_fetcher(
+            &certs,
+            &crls,
+            Some(&root_crt),
+            HttpCrlFetcher::with_cache(&dir).unwrap(),
+            None,
+        )
+        .unwrap();
+        assert!(matches!(
+            verifier.verify(&hkd),
+            Err(Error::HkdVerify(HkdVerifyErrorType::NoCrl))
+        ));
+        assert!(!cached.exists());
+
+        // the next verification downloads the genuine CRL
+        verifier.verify(&hkd).unwrap();
+        assert_eq!(
+            fs::read(&cached).unwrap(),
+            fs::read(get_cert_asset_path("ibm.crl")).unwrap()
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn dir_by_issuer() {
+        let dir = tmp_dir("dir");
+        fs::create_dir_all(&dir).unwrap();
+        fs::copy(get_cert_asset_path("inter_ca.crl"), dir.join("inter.pem")).unwrap();
+        fs::write(dir.join("garbage"), b"no crl").unwrap();
+
+        let fetcher = DirCrlFetcher::new(&dir).unwrap();
+        assert_eq!(
+            fetcher
+                .fetch(&load_gen_cert("ibm.crt"))
+                .unwrap()
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(fetcher.fetch(&load_gen_cert("host.crt")).unwrap().is_none());
+        assert!(NoCrlFetcher
+            .fetch(&load_gen_cert("ibm.crt"))
+            .unwrap()
+            .is_none());
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(DirCrlFetcher::new(&dir).is_err());
+    }
+}
@@ -32,23 +32,27 @@ const SECURITY_BITS_ARRAY: [u32; 6] = [0, 80, 112, 128, 192, 256];
 const SECURITY_BITS: u32 = SECURITY_BITS_ARRAY[SECURITY_LEVEL];
 const SECURITY_CHAIN_MAX_LEN: c_int = 2;
 
-/// Verifies that the HKD
-/// * has enough security bits
-/// * is inside its validity period
-/// * the Authority Key ID matches the Signing Key ID of the  [`sign_key`]
-pub fn verify_hkd_options(hkd: &X509Ref, sign_key: &X509Ref) -> Result<()> {
+/// Verifies that the HKD has enough security bits
+pub fn verify_hkd_security_bits(hkd: &X509Ref) -> Result<()> {
     let hk_pkey = hkd.public_key()?;
     let security_bits = hk_pkey.security_bits();
 
     if SECURITY_BITS > 0 && SECURITY_BITS > security_bits {
         return Err(Error::HkdVerify(SecurityBits(security_bits, SECURITY_BITS)));
     }
+    Ok(())
+}
+
+/// Verifies that the HKD is inside its validity period at `at` (seconds since the Epoch) or now
+/// if `None`.
+pub fn verify_hkd_validity(hkd: &X509Ref, at: Option<i64>) -> Result<()> {
     // TODO rust-openssl fix X509::not.after/before() impl to return Option& not panic on nullptr
     // from C? try_... rust-openssl
-    // verify that the HKD is still valid
-    check_validity_period(hkd.not_before(), hkd.not_after())?;
+    check_validity_period(hkd.not_before(), hkd.not_after(), at)
+}
 
-    // verify that the AKID of the hkd matches the SKID of the issuer
+/// Verifies that the Authority Key ID of the HKD matches the Subject Key ID of [`sign_key`]
+pub fn verify_hkd_akid(hkd: &X509Ref, sign_key: &X509Ref) -> Result<()> {
     if let Some(akid) = hkd.akid() {
         if akid.check(sign_key) != AkidCheckResult::OK {
             bail_hkd_verify!(Akid);
@@ -57,11 +61,11 @@ pub fn verify_hkd_options(hkd: &X509Ref, sign_key: &X509Ref) -> Result<()> {
     Ok(())
 }
 
-pub fn verify_crl(crl: &X509CrlRef, issuer: &X509Ref) -> Option<()> {
+pub fn verify_crl(crl: &X509CrlRef, issuer: &X509Ref, at: Option<i64>) -> Option<()> {
     let last = crl.last_update();
     let next = crl.next_update()?;
 
-    check_validity_period(last, next).ok()?;
+    check_validity_period(last, next, at).ok()?;
     if let Some(akid) = crl.akid() {
         if akid.check(issuer) != AkidCheckResult::OK {
             return None;
@@ -74,10 +78,13 @@ pub fn verify_crl(crl: &X509CrlRef, issuer: &X509Ref) -> Option<()> {
 }
 
 /// Setup the x509Store such that it can be used it for verifying certificates
+///
+/// Certificates and CRLs are verified at `at` (seconds since the Epoch) or now if `None`.
 pub fn store_setup<P: AsRef<Path>, Q: AsRef<Path>, R: AsRef<Path>>(
     root_ca_path: Option<P>,
     crl_paths: &[Q],
     cert_w_crl_paths: &[R],
+    at: Option<i64>,
 ) -> Result<X509StoreBuilder> {
     let mut x509store = X509StoreBuilder::new()?;
 
@@ -113,6 +120,9 @@ pub fn store_setup<P: AsRef<Path>, Q: AsRef<Path>, R: AsRef<Path>>(
     param.set_auth_level(SECURITY_LEVEL as i32);
     param.set_purpose(X509PurposeId::ANY)?;
     param.set_flags(flags)?;
+    if let Some(at) = at {
+        param.set_time(at);
+    }
     x509store.set_param(&param)?;
 
     Ok(x509store)
@@ -273,7 +283,9 @@ fn load_crl_to_store<P: AsRef<Path>>(
     Ok(())
 }
 
-/// Run through the forest of the distribution points and find them
+/// Returns the URIs of all CRL distribution points of `cert`.
+///
+/// Runs through the forest of the distribution points and finds them.
 pub fn x509_dist_points(cert: &X509Ref) -> Vec<String> {
     let mut res = Vec::<String>::with_capacity(1);
     let dps = match cert.crl_distribution_points() {
@@ -299,14 +311,12 @@ pub fn x509_dist_points(cert: &X509Ref) -> Vec<String> {
     res
 }
 
-/// Searches for CRL Distribution points and downloads the CRL. Stops after the first successful
-/// download.
+/// Downloads the CRL at `url`.
 ///
 /// Error if something bad(=unexpected) happens
-/// CRL not available at all URIs and unexpected format at all URIs are mapped to Ok(None)
-#[cfg(not(test))]
-pub fn download_first_crl_from_x509(cert: &X509Ref) -> Result<Option<Vec<openssl::x509::X509Crl>>> {
-    use crate::utils::read_crls;
+/// CRL not available is mapped to Ok(None)
+#[cfg(all(feature = "network", not(test)))]
+pub fn download_crl(url: &str) -> Result<Option<Vec<u8>>> {
     use curl::easy::{Easy2, Handler, WriteError};
     use std::time::Duration;
     const CRL_TIMEOUT_MAX: Duration = Duration::from_secs(3);
@@ -319,29 +329,29 @@ pub fn download_first_crl_from_x509(cert: &X509Ref) -> Result<Option<Vec<openssl
         }
     }
 
-    for dist_point in x509_dist_points(cert) {
-        // A typical certificate is about 1200 bytes long
-        let mut handle = Easy2::new(Buf(Vec::with_capacity(1500)));
-        handle.url(&dist_point)?;
-        handle.get(true)?;
-        handle.follow_location(true)?;
-        handle.timeout(CRL_TIMEOUT_MAX)?;
-        handle.useragent("s390-tools-pv-crl")?;
-
-        if handle.perform().is_err() {
-            continue;
-        }
-        match read_crls(&handle.get_ref().0) {
-            Err(_) => continue,
-            Ok(crl) if crl.is_empty() => continue,
-            Ok(crl) => return Ok(Some(crl)),
-        }
+    // A typical certificate is about 1200 bytes long
+    let mut handle = Easy2::new(Buf(Vec::with_capacity(1500)));
+    handle.url(url)?;
+    handle.get(true)?;
+    handle.follow_location(true)?;
+    handle.timeout(CRL_TIMEOUT_MAX)?;
+    handle.useragent("s390-tools-pv-crl")?;
+
+    if handle.perform().is_err() {
+        return Ok(None);
     }
-    Ok(None)
+    Ok(Some(handle.get_mut().0.split_off(0)))
 }
 
-fn check_validity_period(not_before: &Asn1TimeRef, not_after: &Asn1TimeRef) -> Result<()> {
-    let now = Asn1Time::days_from_now(0)?;
+fn check_validity_period(
+    not_before: &Asn1TimeRef,
+    not_after: &Asn1TimeRef,
+    at: Option<i64>,
+) -> Result<()> {
+    let now = match at {
+        Some(at) => Asn1Time::from_unix(at)?,
+        None => Asn1Time::days_from_now(0)?,
+    };
     if let Ordering::Less = now.compare(not_before)? {
         bail_hkd_verify!(BeforeValidity);
     }
@@ -415,13 +425,26 @@ mod test {
         let yesterday = sys_to_asn1_time(SystemTime::now() - day);
         let tomorrow = sys_to_asn1_time(SystemTime::now() + day);
 
-        assert!(super::check_validity_period(&yesterday, &tomorrow).is_ok());
+        assert!(super::check_validity_period(&yesterday, &tomorrow, None).is_ok());
+        assert!(matches!(
+            super::check_validity_period(&tomorrow, &tomorrow, None),
+            Err(Error::HkdVerify(BeforeValidity))
+        ));
+        assert!(matches!(
+            super::check_validity_period(&yesterday, &yesterday, None),
+            Err(Error::HkdVerify(AfterValidity))
+        ));
+
+        // verification at a given point in time
+        let begin = Asn1Time::from_unix(1_000_000).unwrap();
+        let end = Asn1Time::from_unix(2_000_000).unwrap();
+        assert!(super::check_validity_period(&begin, &end, Some(1_500_000)).is_ok());
         assert!(matches!(
-            super::check_validity_period(&tomorrow, &tomorrow),
+            super::check_validity_period(&begin, &end, Some(999_999)),
             Err(Error::HkdVerify(BeforeValidity))
         ));
         assert!(matches!(
-            super::check_validity_period(&yesterday, &yesterday),
+            super::check_validity_period(&begin, &end, Some(2_000_000)),
             Err(Error::HkdVerify(AfterValidity))
         ));
     }
@@ -443,7 +466,7 @@ mod test {
         let ibm_wrong_subj = load_gen_cert("ibm_wrong_subject.crt");
         let no_sign_crt = load_gen_cert("inter_ca.crt");
 
-        assert!(super::get_ibm_z_sign_key(&[ibm_crt.clone()]).is_ok());
+        assert!(super::get_ibm_z_sign_key(std::slice::from_ref(&ibm_crt)).is_ok());
         assert!(matches!(
             super::get_ibm_z_sign_key(&[ibm_crt.clone(), ibm_crt.clone()]),
             Err(Error::HkdVerify(ManyIbmSignKeys))
@@ -453,7 +476,7 @@ mod test {
             Err(Error::HkdVerify(NoIbmSignKey))
         ));
         assert!(matches!(
-            super::get_ibm_z_sign_key(&[no_sign_crt.clone()]),
+            super::get_ibm_z_sign_key(std::slice::from_ref(&no_sign_crt)),
             Err(Error::HkdVerify(NoIbmSignKey))
         ));
         assert!(super::get_ibm_z_sign_key(&[ibm_crt, no_sign_crt]).is_ok(),);
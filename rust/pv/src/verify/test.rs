@@ -5,30 +5,15 @@
 #![cfg(test)]
 
 use super::{helper, helper::*, *};
-use crate::{utils::read_crls, Error, HkdVerifyErrorType::*};
-use openssl::{stack::Stack, x509::X509Crl};
-use std::path::Path;
+use crate::{Error, HkdVerifyErrorType::*};
+use openssl::stack::Stack;
 
 use crate::test_utils::*;
 
 // Mock function
-pub fn download_first_crl_from_x509(cert: &X509Ref) -> Result<Option<Vec<X509Crl>>> {
-    fn mock_download<P: AsRef<Path>>(path: P) -> Result<Vec<X509Crl>> {
-        read_crls(std::fs::read(path)?)
-    }
-
-    for dist_point in x509_dist_points(cert) {
-        {
-            let path = get_cert_asset_path(&dist_point);
-            let crls = if let Ok(buf) = mock_download(&path) {
-                buf
-            } else {
-                continue;
-            };
-            return Ok(Some(crls));
-        }
-    }
-    Ok(None)
+#[cfg(feature = "network")]
+pub fn download_crl(url: &str) -> Result<Option<Vec<u8>>> {
+    Ok(std::fs::read(get_cert_asset_path(url)).ok())
 }
 
 #[test]
@@ -37,11 +22,12 @@ fn store_setup() {
     let inter_path = get_cert_asset_path("inter.crt");
     let crls: [String; 0] = [];
 
-    let store = helper::store_setup(None::<String>, &crls, &[&ibm_path, &inter_path]);
+    let store = helper::store_setup(None::<String>, &crls, &[&ibm_path, &inter_path], None);
     assert!(store.is_ok());
 }
 
 #[test]
+#[cfg(feature = "network")]
 fn verify_chain_online() {
     let ibm_crt = get_cert_asset_path("ibm.crt");
     let inter_crt = get_cert_asset_path("inter_ca.crt");
@@ -52,6 +38,18 @@ fn verify_chain_online() {
     assert!(ret.is_ok(), "CertVerifier::new failed: {ret:?}");
 }
 
+#[test]
+#[cfg(not(feature = "network"))]
+fn verify_chain_no_network() {
+    let ibm_crt = get_cert_asset_path("ibm.crt");
+    let inter_crt = get_cert_asset_path("inter_ca.crt");
+    let root_crt = get_cert_asset_path("root_ca.chained.crt");
+    let crls: [String; 0] = [];
+
+    let ret = CertVerifier::new(&[&ibm_crt, &inter_crt], &crls, Some(&root_crt), false);
+    assert!(matches!(ret, Err(Error::NoNetwork(_))));
+}
+
 #[test]
 fn verify_chain_offline() {
     let ibm_crt = load_gen_cert("ibm.crt");
@@ -60,13 +58,19 @@ fn verify_chain_offline() {
     let root_crt = get_cert_asset_path("root_ca.chained.crt");
     let certs: [String; 0] = [];
 
-    let store = helper::store_setup(Some(&root_crt), &[&inter_crl], &certs)
+    let store = helper::store_setup(Some(&root_crt), &[&inter_crl], &certs, None)
         .unwrap()
         .build();
 
     let mut sk = Stack::<X509>::new().unwrap();
     sk.push(inter_crt).unwrap();
-    assert!(verify_chain(&store, &sk, &[ibm_crt]).is_ok());
+    assert!(verify_chain(&store, &sk, std::slice::from_ref(&ibm_crt)).is_ok());
+
+    // 2023-01-01: before the chain was issued
+    let store = helper::store_setup(Some(&root_crt), &[&inter_crl], &certs, Some(1_672_531_200))
+        .unwrap()
+        .build();
+    assert!(verify_chain(&store, &sk, &[ibm_crt]).is_err());
 }
 
 #[test]
@@ -111,6 +115,7 @@ fn verify(offline: bool, ibm_crt: &'static str, ibm_crl: &'static str, hkd: &'st
 }
 
 #[test]
+#[cfg(feature = "network")]
 fn verify_online() {
     verify(false, "ibm.crt", "ibm.crl", "host.crt")
 }
@@ -121,6 +126,38 @@ fn verify_offline() {
 }
 
 #[test]
+fn verify_at_time() {
+    let root_crt = get_cert_asset_path("root_ca.chained.crt");
+    let certs = [
+        get_cert_asset_path("ibm.crt"),
+        get_cert_asset_path("inter_ca.crt"),
+    ];
+    let crls = [
+        get_cert_asset_path("ibm.crl"),
+        get_cert_asset_path("inter_ca.crl"),
+    ];
+    let verifier_at =
+        |time| CertVerifier::with_fetcher(&certs, &crls, Some(&root_crt), NoCrlFetcher, time);
+
+    // 2030-01-01
+    let verifier = verifier_at(Some(1_893_456_000)).unwrap();
+    assert!(verifier.verify(&load_gen_cert("host.crt")).is_ok());
+    assert!(matches!(
+        verifier.verify(&load_gen_cert("host_rev.crt")),
+        Err(Error::HkdVerify(HkdRevoked))
+    ));
+    assert!(matches!(
+        verifier.verify(&load_gen_cert("host_crt_expired.crt")),
+        Err(Error::HkdVerify(AfterValidity))
+    ));
+
+    // 2023-01-01: before and 2389-01-01: after the validity of the chain of trust
+    assert!(verifier_at(Some(1_672_531_200)).is_err());
+    assert!(verifier_at(Some(13_222_396_800)).is_err());
+}
+
+#[test]
+#[cfg(feature = "network")]
 fn verify_armonk_crt_online() {
     verify(false, "ibm_armonk.crt", "ibm.crl", "host.crt")
 }
@@ -131,6 +168,7 @@ fn verify_armonk_crt_offline() {
 }
 
 #[test]
+#[cfg(feature = "network")]
 fn verify_armonk_crl_online() {
     verify(false, "ibm_armonk.crt", "ibm_armonk.crl", "host.crt")
 }
@@ -141,6 +179,7 @@ fn verify_armonk_crl_offline() {
 }
 
 #[test]
+#[cfg(feature = "network")]
 fn verify_armonk_hkd_online() {
     verify(false, "ibm_armonk.crt", "ibm_armonk.crl", "host_armonk.crt")
 }
@@ -149,3 +188,139 @@ fn verify_armonk_hkd_online() {
 fn verify_armonk_hkd_offline() {
     verify(true, "ibm_armonk.crt", "ibm_armonk.crl", "host_armonk.crt")
 }
+
+/// Issue a host-key document for `key` with the IBM Z signing key of the test chain.
+///
+/// The Authority Key ID is derived from `akid_issuer`, if any.
+fn issue_hkd<T: openssl::pkey::HasPublic>(
+    key: &openssl::pkey::PKeyRef<T>,
+    akid_issuer: Option<&X509Ref>,
+) -> X509 {
+    use openssl::{
+        asn1::Asn1Time, bn::BigNum, hash::MessageDigest, pkey::PKey,
+        x509::extension::AuthorityKeyIdentifier,
+    };
+
+    let ibm_crt = load_gen_cert("ibm.crt");
+    let ibm_key = PKey::private_key_from_pem(&get_cert_asset("ibm.key")).unwrap();
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    let serial = BigNum::from_u32(4711).unwrap().to_asn1_integer().unwrap();
+    builder.set_serial_number(&serial).unwrap();
+    builder
+        .set_subject_name(load_gen_cert("host.crt").subject_name())
+        .unwrap();
+    builder.set_issuer_name(ibm_crt.subject_name()).unwrap();
+    builder.set_pubkey(key).unwrap();
+    builder
+        .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::days_from_now(365).unwrap())
+        .unwrap();
+    if let Some(issuer) = akid_issuer {
+        let akid = AuthorityKeyIdentifier::new()
+            .keyid(true)
+            .build(&builder.x509v3_context(Some(issuer), None))
+            .unwrap();
+        builder.append_extension(akid).unwrap();
+    }
+    builder.sign(&ibm_key, MessageDigest::sha256()).unwrap();
+    builder.build()
+}
+
+/// Returns the indices of the failed and the skipped steps.
+fn failed_steps(steps: &[(HkdVerifyStep, HkdStepOutcome)]) -> (Vec<usize>, Vec<usize>) {
+    assert_eq!(
+        steps.iter().map(|(step, _)| *step).collect::<Vec<_>>(),
+        HkdVerifyStep::ALL
+    );
+    let indices = |f: fn(&HkdStepOutcome) -> bool| {
+        steps
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, outcome))| f(outcome))
+            .map(|(idx, _)| idx)
+            .collect()
+    };
+    (
+        indices(|o| matches!(o, HkdStepOutcome::Failed(_))),
+        indices(|o| matches!(o, HkdStepOutcome::Skipped)),
+    )
+}
+
+#[test]
+fn verify_steps() {
+    use openssl::{pkey::PKey, rsa::Rsa};
+
+    let root_crt = get_cert_asset_path("root_ca.chained.crt");
+    let certs = [
+        get_cert_asset_path("ibm.crt"),
+        get_cert_asset_path("inter_ca.crt"),
+    ];
+    let crls = [
+        get_cert_asset_path("ibm.crl"),
+        get_cert_asset_path("inter_ca.crl"),
+    ];
+    let verifier =
+        CertVerifier::with_fetcher(&certs, &crls, Some(&root_crt), NoCrlFetcher, None).unwrap();
+    let steps = |hkd: &X509| verifier.verify_steps(hkd);
+    let host_key = load_gen_cert("host.crt").public_key().unwrap();
+
+    assert_eq!(
+        failed_steps(&steps(&load_gen_cert("host.crt"))),
+        (vec![], vec![])
+    );
+    assert_eq!(
+        failed_steps(&steps(&issue_hkd(&host_key, None))),
+        (vec![], vec![])
+    );
+
+    let weak_key = PKey::from_rsa(Rsa::generate(1024).unwrap()).unwrap();
+    let res = steps(&issue_hkd(&weak_key, None));
+    assert_eq!(failed_steps(&res), (vec![0], vec![]));
+    assert!(matches!(
+        res[0].1,
+        HkdStepOutcome::Failed(Error::HkdVerify(SecurityBits(80, 112)))
+    ));
+
+    let res = steps(&load_gen_cert("host_crt_expired.crt"));
+    assert_eq!(failed_steps(&res), (vec![1], vec![]));
+    assert!(matches!(
+        res[1].1,
+        HkdStepOutcome::Failed(Error::HkdVerify(AfterValidity))
+    ));
+
+    let wrong_issuer = load_gen_cert("ibm_wrong_subject.crt");
+    let res = steps(&issue_hkd(&host_key, Some(&wrong_issuer)));
+    assert_eq!(failed_steps(&res), (vec![2], vec![]));
+    assert!(matches!(
+        res[2].1,
+        HkdStepOutcome::Failed(Error::HkdVerify(Akid))
+    ));
+
+    let res = steps(&load_gen_cert("host_invalid_signing_key.crt"));
+    assert_eq!(failed_steps(&res), (vec![2, 3], vec![]));
+    assert!(matches!(
+        res[3].1,
+        HkdStepOutcome::Failed(Error::HkdVerify(Signature))
+    ));
+
+    let res = steps(&load_gen_cert("host_rev.crt"));
+    assert_eq!(failed_steps(&res), (vec![5], vec![]));
+    assert!(matches!(
+        res[5].1,
+        HkdStepOutcome::Failed(Error::HkdVerify(HkdRevoked))
+    ));
+
+    // no CRL for the IBM Z signing key
+    let verifier =
+        CertVerifier::with_fetcher(&certs, &crls[1..], Some(&root_crt), NoCrlFetcher, None)
+            .unwrap();
+    let res = verifier.verify_steps(&load_gen_cert("host.crt"));
+    assert_eq!(failed_steps(&res), (vec![4], vec![5]));
+    assert!(matches!(
+        res[4].1,
+        HkdStepOutcome::Failed(Error::HkdVerify(NoCrl))
+    ));
+}
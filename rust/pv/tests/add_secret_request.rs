@@ -19,7 +19,7 @@ use s390_pv::{
         ExtSecret, GuestSecret,
     },
     test_utils::get_test_keys,
-    uv::ConfigUid,
+    uv::{ConfigUid, ListableSecretType},
     Result,
 };
 
@@ -259,3 +259,47 @@ fn verify_no_user_data() {
         Ok(None)
     ))
 }
+
+#[test]
+fn bin_values() {
+    let req = get_test_asset!("exp/asrcb/assoc_none_default_cuid_one");
+    assert_eq!(AddSecretRequest::bin_version(req).unwrap(), 0x100);
+    assert_eq!(AddSecretRequest::bin_flags(req).unwrap(), 0);
+    assert_eq!(
+        AddSecretRequest::bin_secret_type(req).unwrap(),
+        ListableSecretType::Association
+    );
+
+    let req = get_test_asset!("exp/asrcb/null_none_dump_cuid_one");
+    assert_eq!(AddSecretRequest::bin_flags(req).unwrap(), 1 << 63);
+    assert_eq!(
+        AddSecretRequest::bin_secret_type(req).unwrap(),
+        ListableSecretType::Invalid(ListableSecretType::NULL)
+    );
+
+    let req = get_test_asset!("exp/asrcb/null_none_default_cuid_seven");
+    assert_eq!(
+        AddSecretRequest::bin_secret_type(req).unwrap(),
+        ListableSecretType::Invalid(ListableSecretType::NULL)
+    );
+}
+
+#[test]
+fn bin_values_truncated() {
+    let req = get_test_asset!("exp/asrcb/assoc_none_default_cuid_one");
+    for len in [0, 0x10, 200, req.len() - 1] {
+        let req = &req[..len];
+        assert!(AddSecretRequest::bin_version(req).is_err());
+        assert!(AddSecretRequest::bin_flags(req).is_err());
+        assert!(AddSecretRequest::bin_secret_type(req).is_err());
+    }
+
+    // trailing data after the request is ignored
+    let mut long = req.to_vec();
+    long.extend_from_slice(&[0xff; 16]);
+    assert_eq!(AddSecretRequest::bin_version(&long).unwrap(), 0x100);
+    assert_eq!(
+        AddSecretRequest::bin_secret_type(&long).unwrap(),
+        ListableSecretType::Association
+    );
+}
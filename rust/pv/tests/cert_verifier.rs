@@ -8,6 +8,9 @@ use s390_pv::test_utils::*;
 use s390_pv::{Error, HkdVerifyErrorType::*};
 use std::ffi::c_int;
 
+// Try to download CRLs if the network feature is enabled
+const OFFLINE: bool = !cfg!(feature = "network");
+
 #[track_caller]
 fn verify_sign_error(exp_raw: c_int, obs: Error) {
     verify_sign_error_slice(&[exp_raw], obs)
@@ -52,10 +55,15 @@ fn verifier_new() {
         &[&inter_crt, &ibm_crt],
         &[&inter_crl],
         Some(&root_crt),
-        false,
+        OFFLINE,
     );
     verify_sign_error(3, verifier.unwrap_err());
-    let verifier = CertVerifier::new(&[&inter_crt, &ibm_crt], &empty, Some(&root_chn_crt), false);
+    let verifier = CertVerifier::new(
+        &[&inter_crt, &ibm_crt],
+        &empty,
+        Some(&root_chn_crt),
+        OFFLINE,
+    );
     verify_sign_error(3, verifier.unwrap_err());
 
     // Wrong intermediate (or ibm key)
@@ -77,12 +85,12 @@ fn verifier_new() {
         &[&inter_crt, &ibm_crt],
         &[&inter_crl],
         Some(&root_chn_crt),
-        false,
+        OFFLINE,
     )
     .unwrap();
 
     // No intermediate key
-    let verifier = CertVerifier::new(&[&ibm_crt], &empty, Some(&root_chn_crt), false);
+    let verifier = CertVerifier::new(&[&ibm_crt], &empty, Some(&root_chn_crt), OFFLINE);
     verify_sign_error(20, verifier.unwrap_err());
 
     // IBM Sign outdated
@@ -90,14 +98,14 @@ fn verifier_new() {
         &[&inter_crt, &ibm_early_crt],
         &[&inter_crl],
         Some(&root_chn_crt),
-        false,
+        OFFLINE,
     );
     assert!(matches!(verifier, Err(Error::HkdVerify(NoIbmSignKey))));
     let verifier = CertVerifier::new(
         &[&inter_crt, &ibm_late_crt],
         &[&inter_crl],
         Some(&root_chn_crt),
-        false,
+        OFFLINE,
     );
     assert!(matches!(verifier, Err(Error::HkdVerify(NoIbmSignKey))));
 
@@ -106,7 +114,7 @@ fn verifier_new() {
         &[&inter_crt, &ibm_rev_crt],
         &[&inter_crl],
         Some(&root_chn_crt),
-        false,
+        OFFLINE,
     );
     verify_sign_error(23, verifier.unwrap_err());
 }
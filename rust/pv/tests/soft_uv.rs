@@ -0,0 +1,299 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2024
+
+#![allow(missing_docs)]
+use s390_pv::{
+    attest::{
+        AttestationFlags, AttestationItems, AttestationMeasAlg, AttestationMeasurement,
+        AttestationRequest, AttestationVersion,
+    },
+    get_test_asset,
+    request::{
+        openssl::pkey::{PKey, Public},
+        BootHdrTags, ReqEncrCtx, Request, SymKeyType,
+    },
+    secret::{
+        AddSecretFlags, AddSecretRequest, AddSecretVersion, ExtSecret, GuestSecret, RetrievedSecret,
+    },
+    test_utils::{get_test_host_key_and_cert, get_test_keys},
+    testing::{SoftUv, SoftUvBackend},
+    uv::{
+        AddCmd, AttestationCmd, ConfigUid, ListCmd, LockCmd, RetrieveCmd, SecretList, UvCmd,
+        UvDevice, UvDeviceInfo,
+    },
+    Error, PvCoreError,
+};
+
+const TAGS: BootHdrTags = BootHdrTags::new([1; 64], [2; 64], [3; 64], [4; 16]);
+const CUID: ConfigUid = [0x42; 16];
+const CCK: [u8; 32] = [0x23; 32];
+
+fn soft_uv() -> (SoftUv, PKey<Public>) {
+    let (host_key, hkd) = get_test_host_key_and_cert();
+    let mut uv = SoftUv::new(vec![host_key], TAGS).unwrap();
+    uv.set_cuid(CUID);
+    (uv, hkd.public_key().unwrap())
+}
+
+fn asrcb(
+    secret: GuestSecret,
+    ext_secret: Option<ExtSecret>,
+    cuid: Option<ConfigUid>,
+    hkd: PKey<Public>,
+) -> Vec<u8> {
+    let mut asrcb = AddSecretRequest::new(
+        AddSecretVersion::One,
+        secret,
+        TAGS,
+        AddSecretFlags::default(),
+    );
+    if let Some(ext_secret) = ext_secret {
+        asrcb.set_ext_secret(ext_secret).unwrap();
+    }
+    if let Some(cuid) = cuid {
+        asrcb.set_cuid(cuid);
+    }
+    asrcb.set_user_data(b"user data".to_vec(), None).unwrap();
+    asrcb.add_hostkey(hkd);
+    asrcb
+        .encrypt(&ReqEncrCtx::random(SymKeyType::Aes256Gcm).unwrap())
+        .unwrap()
+}
+
+#[test]
+fn signed_user_data() {
+    let (mut uv, hkd) = soft_uv();
+    let rsa = PKey::private_key_from_pem(get_test_asset!("keys/rsa2048key.pem")).unwrap();
+    let rsa_pub = PKey::public_key_from_pem(get_test_asset!("keys/rsa2048key.pub.pem")).unwrap();
+    let mut req = AddSecretRequest::new(
+        AddSecretVersion::One,
+        GuestSecret::Null,
+        TAGS,
+        AddSecretFlags::default(),
+    );
+    req.set_user_data(b"signed user data".to_vec(), Some(rsa))
+        .unwrap();
+    req.add_hostkey(hkd);
+    let req = req
+        .encrypt(&ReqEncrCtx::random(SymKeyType::Aes256Gcm).unwrap())
+        .unwrap();
+
+    // without a key only the format of the signature is checked
+    uv.add_secret(&req).unwrap();
+    uv.set_user_data_key(rsa_pub);
+    uv.add_secret(&req).unwrap();
+
+    let (_, other) = get_test_keys();
+    uv.set_user_data_key(other);
+    assert!(matches!(
+        uv.add_secret(&req),
+        Err(Error::AsrcbUserDataKeyMismatch { .. })
+    ));
+}
+
+fn uv_rc(res: s390_pv::Result<impl std::fmt::Debug>) -> u16 {
+    match res {
+        Err(Error::PvCore(PvCoreError::Uv { rc, .. })) => rc,
+        res => panic!("expected an UV error, got {res:?}"),
+    }
+}
+
+#[test]
+fn secret_store_lifecycle() {
+    let (mut uv, hkd) = soft_uv();
+    let mut requests = vec![];
+
+    let assoc = GuestSecret::association("association", [0x11; 32]).unwrap();
+    requests.push(asrcb(assoc, None, Some(CUID), hkd.clone()));
+    let plain = GuestSecret::plaintext("plain", b"very secret".to_vec().into()).unwrap();
+    requests.push(asrcb(plain, None, None, hkd.clone()));
+    requests.push(asrcb(GuestSecret::Null, None, None, hkd.clone()));
+    for req in &requests {
+        uv.add_secret(req).unwrap();
+    }
+
+    // secret IDs must be unique
+    let dup = GuestSecret::association("association", [0x12; 32]).unwrap();
+    assert_eq!(
+        uv_rc(uv.add_secret(&asrcb(dup, None, None, hkd.clone()))),
+        0x010c
+    );
+
+    let list = uv.list();
+    assert_eq!(list.len(), 2);
+    let id = GuestSecret::name_to_id("plain").unwrap();
+    let entry = list.find(&id).unwrap();
+
+    let mut cmd = RetrieveCmd::from_entry(entry.clone()).unwrap();
+    let secret = uv.retrieve(entry.index()).unwrap();
+    cmd.data().unwrap().copy_from_slice(secret.value());
+    assert_eq!(RetrievedSecret::from_cmd(cmd).data(), b"very secret");
+
+    let assoc = list.find(&GuestSecret::name_to_id("association").unwrap());
+    assert_eq!(uv_rc(uv.retrieve(assoc.unwrap().index())), 0x0111);
+    assert_eq!(uv_rc(uv.retrieve(7)), 0x0110);
+
+    uv.lock().unwrap();
+    assert!(uv.locked());
+    assert_eq!(uv_rc(uv.lock()), 0x0102);
+    let late = asrcb(GuestSecret::Null, None, None, hkd);
+    assert_eq!(uv_rc(uv.add_secret(&late)), 0x0102);
+
+    // same calculation as 'pvattest check --secret'
+    let mut tags: Vec<u8> = requests
+        .iter()
+        .flat_map(|r| AddSecretRequest::bin_tag(r).unwrap())
+        .collect();
+    tags.push(1);
+    let exp = openssl::hash::hash(openssl::hash::MessageDigest::sha512(), &tags).unwrap();
+    assert_eq!(uv.secret_store_hash().unwrap(), exp.as_ref());
+}
+
+#[test]
+fn add_secret_binding() {
+    let (mut uv, hkd) = soft_uv();
+
+    let wrong_cuid = asrcb(GuestSecret::Null, None, Some([1; 16]), hkd.clone());
+    assert_eq!(uv_rc(uv.add_secret(&wrong_cuid)), 0x010c);
+
+    let mut other = AddSecretRequest::new(
+        AddSecretVersion::One,
+        GuestSecret::Null,
+        BootHdrTags::new([9; 64], [2; 64], [3; 64], [4; 16]),
+        AddSecretFlags::default(),
+    );
+    other.add_hostkey(hkd.clone());
+    let other = other
+        .encrypt(&ReqEncrCtx::random(SymKeyType::Aes256Gcm).unwrap())
+        .unwrap();
+    assert_eq!(uv_rc(uv.add_secret(&other)), 0x010c);
+
+    // request for another host
+    let (_, other_hkd) = get_test_keys();
+    let other_host = asrcb(GuestSecret::Null, None, None, other_hkd);
+    assert_eq!(uv_rc(uv.add_secret(&other_host)), 0x0108);
+
+    // extension secret derived from the CCK; updating the CCK changes it
+    uv.set_ext_secret(ExtSecret::Derived(CCK.into()));
+    let simple = asrcb(GuestSecret::Null, None, None, hkd.clone());
+    assert_eq!(uv_rc(uv.add_secret(&simple)), 0x010c);
+    let update = GuestSecret::update_cck([0x24; 32]);
+    let derived = Some(ExtSecret::Derived(CCK.into()));
+    uv.add_secret(&asrcb(update, derived.clone(), None, hkd.clone()))
+        .unwrap();
+    let old_cck = asrcb(GuestSecret::Null, derived, None, hkd.clone());
+    assert_eq!(uv_rc(uv.add_secret(&old_cck)), 0x010c);
+    let new_cck = Some(ExtSecret::Derived([0x24; 32].into()));
+    uv.add_secret(&asrcb(GuestSecret::Null, new_cck, None, hkd))
+        .unwrap();
+}
+
+#[test]
+fn attestation() {
+    let (mut uv, hkd) = soft_uv();
+    let assoc = GuestSecret::association("association", [0x11; 32]).unwrap();
+    let req = asrcb(assoc, None, None, hkd.clone());
+    uv.add_secret(&req).unwrap();
+
+    let mut flags = AttestationFlags::default();
+    flags.set_attest_phkh();
+    flags.set_secret_store_hash();
+    let mut arcb = AttestationRequest::new(
+        AttestationVersion::One,
+        AttestationMeasAlg::HmacSha512,
+        flags,
+    )
+    .unwrap();
+    arcb.add_hostkey(hkd);
+    let ctx = ReqEncrCtx::random(SymKeyType::Aes256Gcm).unwrap();
+    let arcb = arcb.encrypt(&ctx).unwrap();
+
+    let user_data = [0x77; 64];
+    let response = uv.attest(&arcb, Some(&user_data)).unwrap();
+    assert_eq!(response.cuid(), &CUID);
+    let additional = response.additional().unwrap();
+    assert_eq!(additional.len(), 32 + 64);
+    assert_eq!(&additional[32..], uv.secret_store_hash().unwrap());
+
+    // verify like 'pvattest verify'
+    let (auth, conf) = AttestationRequest::decrypt_bin(&arcb, ctx.prot_key()).unwrap();
+    let items = AttestationItems::new(
+        &TAGS,
+        response.cuid(),
+        Some(&user_data),
+        conf.nonce().as_ref().map(|n| n.value()),
+        response.additional(),
+    );
+    let meas_key = PKey::hmac(conf.measurement_key()).unwrap();
+    let measurement = AttestationMeasurement::calculate(items, auth.mai(), &meas_key).unwrap();
+    assert!(measurement.eq_secure(response.measurement()));
+
+    // another guest calculates another measurement
+    let (host_key, _) = get_test_host_key_and_cert();
+    let other = SoftUv::new(
+        vec![host_key],
+        BootHdrTags::new([0; 64], [0; 64], [0; 64], [0; 16]),
+    )
+    .unwrap();
+    let response = other.attest(&arcb, Some(&user_data)).unwrap();
+    assert!(!measurement.eq_secure(response.measurement()));
+
+    assert_eq!(uv_rc(uv.attest(&arcb, Some(&[0; 257]))), 0x0102);
+}
+
+#[test]
+fn uvdevice_backend() {
+    let (uv, hkd) = soft_uv();
+    let backend = SoftUvBackend::new(uv);
+    let uv = UvDevice::with_backend(backend.clone());
+    // no uvdevice info IOCTL; only attestation is assumed to be supported
+    assert!(UvDeviceInfo::get(&uv).is_ok());
+
+    let plain = GuestSecret::plaintext("plain", b"very secret".to_vec().into()).unwrap();
+    let req = asrcb(plain, None, None, hkd.clone());
+    uv.send_cmd(&mut AddCmd::new(&mut req.as_slice()).unwrap())
+        .unwrap();
+
+    let mut list = ListCmd::new();
+    uv.send_cmd(&mut list).unwrap();
+    let list = SecretList::try_from(list).unwrap();
+    let entry = list.find(&GuestSecret::name_to_id("plain").unwrap());
+    let mut cmd = RetrieveCmd::from_entry(entry.unwrap()).unwrap();
+    uv.send_cmd(&mut cmd).unwrap();
+    assert_eq!(RetrievedSecret::from_cmd(cmd).data(), b"very secret");
+
+    uv.send_cmd(&mut LockCmd).unwrap();
+    assert!(backend.soft_uv().locked());
+    assert!(matches!(
+        uv.send_cmd(&mut LockCmd),
+        Err(PvCoreError::Uv { rc: 0x0102, .. })
+    ));
+
+    let mut flags = AttestationFlags::default();
+    flags.set_secret_store_hash();
+    let mut arcb = AttestationRequest::new(
+        AttestationVersion::One,
+        AttestationMeasAlg::HmacSha512,
+        flags,
+    )
+    .unwrap();
+    arcb.add_hostkey(hkd);
+    let arcb = arcb
+        .encrypt(&ReqEncrCtx::random(SymKeyType::Aes256Gcm).unwrap())
+        .unwrap();
+    let user_data = vec![0x77; 16];
+    let mut cmd = AttestationCmd::new_request(
+        arcb.clone().into(),
+        Some(user_data.clone()),
+        64,
+        flags.expected_additional_size(),
+    )
+    .unwrap();
+    uv.send_cmd(&mut cmd).unwrap();
+
+    let exp = backend.soft_uv().attest(&arcb, Some(&user_data)).unwrap();
+    assert_eq!(cmd.measurement(), exp.measurement());
+    assert_eq!(cmd.additional(), exp.additional());
+    assert_eq!(cmd.cuid(), &CUID);
+}
@@ -6,7 +6,7 @@
 //
 
 use crate::{
-    utils::{pv_guest_bit_set, read_file_string, write_file},
+    utils::{pv_guest_bit_set, read_file_string, sysfs_path, write_file},
     Error, Result,
 };
 use regex::Regex;
@@ -57,7 +57,6 @@ pub struct ApqnInfoEp11 {
 
 /// Info on an APQN configured for CCA coprocessor
 #[derive(Debug, Clone)]
-#[allow(dead_code)]
 pub struct ApqnInfoCca {
     /// Serial number of the Crypto Express adapter as a case-sensitive ASCII string
     pub serialnr: String,
@@ -75,7 +74,6 @@ pub enum ApqnInfo {
     /// Info on an APQN configured for EP11 coprocessor
     Ep11(ApqnInfoEp11),
     /// Info on an APQN configured for CCA coprocessor
-    #[allow(dead_code)]
     Cca(ApqnInfoCca),
 }
 
@@ -205,7 +203,7 @@ impl TryFrom<&str> for Apqn {
         let domstr = caps.get(2).unwrap().as_str();
         let domain = u32::from_str_radix(domstr, 16).unwrap();
 
-        let path = format!("{PATH_SYS_DEVICES_AP}/card{cardstr}");
+        let path = format!("{}/card{cardstr}", sysfs_path(PATH_SYS_DEVICES_AP));
         let card_type =
             read_file_string(format!("{path}/type"), "card type").map(|s| s.trim().to_string())?;
         let caps = re_card_type
@@ -287,6 +285,16 @@ impl Apqn {
     }
 }
 
+fn queue_attr_path(card: u32, dom: u32, attr: &str) -> String {
+    format!(
+        "{}/card{:02x}/{:02x}.{:04x}/{attr}",
+        sysfs_path(PATH_SYS_DEVICES_AP),
+        card,
+        card,
+        dom
+    )
+}
+
 /// Bind state of an APQN
 #[derive(Debug, PartialEq, Eq)]
 pub enum BindState {
@@ -303,10 +311,7 @@ pub enum BindState {
 /// Returns a BindState enum as defined above or on failure
 /// an error string. Does NOT print any error messages.
 pub fn get_apqn_bind_state(card: u32, dom: u32) -> Result<BindState> {
-    let path = format!(
-        "{}/card{:02x}/{:02x}.{:04x}/se_bind",
-        PATH_SYS_DEVICES_AP, card, card, dom
-    );
+    let path = queue_attr_path(card, dom, "se_bind");
     let state_str = read_file_string(path, "se_bind attribute")?;
     let state = state_str.trim();
     match state {
@@ -331,10 +336,7 @@ pub fn get_apqn_bind_state(card: u32, dom: u32) -> Result<BindState> {
 /// Panics if a desired bind state other than Bound or Unbound is given.
 pub fn set_apqn_bind_state(card: u32, dom: u32, state: BindState) -> Result<()> {
     let ctx = "bind APQN";
-    let path = format!(
-        "{}/card{:02x}/{:02x}.{:04x}/se_bind",
-        PATH_SYS_DEVICES_AP, card, card, dom
-    );
+    let path = queue_attr_path(card, dom, "se_bind");
     match state {
         BindState::Bound => write_file(path, 1.to_string(), ctx),
         BindState::Unbound => write_file(path, 0.to_string(), ctx),
@@ -374,10 +376,7 @@ pub enum AssocState {
 /// Returns an AssocState enum as defined above or on failure
 /// an error string. Does NOT print any error messages.
 pub fn get_apqn_associate_state(card: u32, dom: u32) -> Result<AssocState> {
-    let path = format!(
-        "{}/card{:02x}/{:02x}.{:04x}/se_associate",
-        PATH_SYS_DEVICES_AP, card, card, dom
-    );
+    let path = queue_attr_path(card, dom, "se_associate");
     let state_str = read_file_string(path, "se_associate attribute")?;
     let state = state_str.trim();
     match state.strip_prefix("associated ") {
@@ -392,10 +391,7 @@ pub fn get_apqn_associate_state(card: u32, dom: u32) -> Result<AssocState> {
 }
 
 fn set_apqn_associate_state_associate(card: u32, dom: u32, idx: u16) -> Result<()> {
-    let path = format!(
-        "{}/card{:02x}/{:02x}.{:04x}/se_associate",
-        PATH_SYS_DEVICES_AP, card, card, dom
-    );
+    let path = queue_attr_path(card, dom, "se_associate");
     write_file(path, idx.to_string(), "associate APQN")?;
     let mut ms: u64 = 0;
     loop {
@@ -422,10 +418,7 @@ fn set_apqn_associate_state_associate(card: u32, dom: u32, idx: u16) -> Result<(
 }
 
 fn set_apqn_associate_state_unbind(card: u32, dom: u32) -> Result<()> {
-    let bindpath = format!(
-        "{}/card{:02x}/{:02x}.{:04x}/se_bind",
-        PATH_SYS_DEVICES_AP, card, card, dom
-    );
+    let bindpath = queue_attr_path(card, dom, "se_bind");
     write_file(bindpath, 0.to_string(), "unbind APQN")?;
     let mut ms: u64 = 0;
     loop {
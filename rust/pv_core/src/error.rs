@@ -107,6 +107,21 @@ pub enum Error {
     #[error("Failure parsing {subject} '{content}'.")]
     ParseError { subject: String, content: String },
 
+    #[error("Unsupported Ultravisor snapshot version {0}.")]
+    UnsupportedSnapshotVersion(u32),
+
+    #[error("The sysfs root is already set to '{current}', cannot change it to '{requested}'")]
+    SysfsRootSet { current: String, requested: String },
+
+    #[error("Ultravisor backend: {0}")]
+    UvBackend(String),
+
+    #[error("Invalid target key hash")]
+    InvalidTargetKeyHash,
+
+    #[error("Invalid UV key hashes")]
+    InvalidUvKeyHashes,
+
     // errors from other crates
     #[error(transparent)]
     Io(#[from] std::io::Error),
@@ -9,6 +9,8 @@ mod macros;
 mod utils;
 mod uvattest;
 mod uvdevice;
+mod uvkeys;
+mod uvquery;
 mod uvsecret;
 
 pub use error::{Error, FileAccessErrorType, FileIoErrorType, Result};
@@ -25,6 +27,7 @@ pub mod misc {
     pub use crate::utils::{decode_hex, encode_hex, parse_hex};
     pub use crate::utils::{read, write};
     pub use crate::utils::{read_exact_file, read_file, read_file_string, write_file};
+    pub use crate::utils::{set_sysfs_root, sysfs_path, sysfs_root_is_set};
     pub use crate::utils::{to_u16, to_u32, try_parse_u128, try_parse_u64};
     pub use crate::utils::{Flags, Lsb0Flags64, Msb0Flags64};
 }
@@ -35,11 +38,21 @@ pub mod misc {
 /// [`crate::uv::UvCmd`]
 pub mod uv {
     pub use crate::uvdevice::attest::AttestationCmd;
+    pub use crate::uvdevice::backend::{
+        ioctl_nr, AttestationPayload, OwnedUvcPayload, UvBackend, UvBackendSpec, UvcPayload, UvcRc,
+    };
     pub use crate::uvdevice::retr_secret::RetrievableSecret;
     pub use crate::uvdevice::retr_secret::{AesSizes, AesXtsSizes, EcCurves, HmacShaSizes};
     pub use crate::uvdevice::secret::{AddCmd, ListCmd, LockCmd, RetrieveCmd};
     pub use crate::uvdevice::secret_list::{ListableSecretType, SecretEntry, SecretId, SecretList};
     pub use crate::uvdevice::{ConfigUid, UvCmd, UvDevice, UvDeviceInfo, UvFlags, UvcSuccess};
+    pub use crate::uvdevice::{RecordingBackend, RemoteBackend, RemoteStream, ReplayBackend};
+    pub use crate::uvkeys::{UvKeyHashV1, UvKeyHashesV1};
+    pub use crate::uvquery::{SeMode, UvBitmask, UvLimits, UvQuery, UvSnapshot};
+    /// Descriptions of the bits of the Ultravisor query attributes
+    pub mod query_desc {
+        pub use crate::uvquery::desc::*;
+    }
 }
 
 /// Functionalities to verify UV requests
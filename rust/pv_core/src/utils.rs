@@ -5,6 +5,7 @@ use std::{
     fs::File,
     io::{Read, Write},
     path::Path,
+    sync::OnceLock,
 };
 
 use zerocopy::{BigEndian, FromBytes, Immutable, IntoBytes, U64};
@@ -82,7 +83,7 @@ impl From<Msb0Flags64> for u64 {
 ///
 /// Wraps an u64 to set/get individual bits
 #[repr(C)]
-#[derive(Debug, Clone, Copy, Default, IntoBytes, FromBytes, Immutable)]
+#[derive(Debug, Clone, Copy, Default, IntoBytes, FromBytes, Eq, PartialEq, Immutable)]
 pub struct Lsb0Flags64(U64<BigEndian>);
 impl Flags<u64> for Lsb0Flags64 {
     #[track_caller]
@@ -401,27 +402,101 @@ pub fn parse_hex(hex_str: &str) -> Vec<u8> {
     }
     bytes
 }
+const PATH_SYS: &str = "/sys";
+
+static SYSFS_ROOT: OnceLock<String> = OnceLock::new();
+
+/// Use a different directory as root of the sysfs tree instead of `/sys`.
+///
+/// Intended for testing against a synthetic sysfs tree. The root is process-wide
+/// and should be set before any sysfs access. Setting the same root again is a
+/// no-op.
+///
+/// # Errors
+///
+/// Returns an error if a different root has already been set.
+pub fn set_sysfs_root<P: AsRef<Path>>(root: P) -> Result<()> {
+    let root = root
+        .as_ref()
+        .to_string_lossy()
+        .trim_end_matches('/')
+        .to_string();
+    let current = SYSFS_ROOT.get_or_init(|| root.clone());
+    if *current != root {
+        return Err(Error::SysfsRootSet {
+            current: current.clone(),
+            requested: root,
+        });
+    }
+    Ok(())
+}
+
+/// Report if the sysfs root has been changed with [`set_sysfs_root`].
+pub fn sysfs_root_is_set() -> bool {
+    SYSFS_ROOT.get().is_some()
+}
+
+/// Translate an absolute sysfs path (starting with `/sys`) into the configured sysfs tree.
+///
+/// Paths not starting with `/sys` are returned unchanged.
+pub fn sysfs_path(path: &str) -> String {
+    relocate_sysfs_path(SYSFS_ROOT.get().map(String::as_str), path)
+}
+
+fn relocate_sysfs_path(root: Option<&str>, path: &str) -> String {
+    match (root, path.strip_prefix(PATH_SYS)) {
+        (Some(root), Some(rest)) if rest.is_empty() || rest.starts_with('/') => {
+            format!("{root}{rest}")
+        }
+        _ => path.to_string(),
+    }
+}
+
 /// Report if the `prot_virt_guest` sysfs entry is one.
 ///
 /// If the entry does not exist returns false.
 ///
 /// for non-s390-architectures:
-/// Returns always false
+/// Returns always false, unless a sysfs root is set with [`set_sysfs_root`].
 /// A non-s390 system cannot be a secure execution guest.
 #[allow(unreachable_code)]
 pub fn pv_guest_bit_set() -> bool {
     #[cfg(not(target_arch = "s390x"))]
-    return false;
+    if !sysfs_root_is_set() {
+        return false;
+    }
     // s390 branch
-    let v = std::fs::read("/sys/firmware/uv/prot_virt_guest").unwrap_or_else(|_| vec![0]);
-    let v: u8 = String::from_utf8_lossy(&v[..1]).parse().unwrap_or(0);
-    v == 1
+    let v = std::fs::read(sysfs_path("/sys/firmware/uv/prot_virt_guest")).unwrap_or_default();
+    pv_guest_bit(&v)
+}
+
+/// A missing or empty entry counts as zero.
+fn pv_guest_bit(v: &[u8]) -> bool {
+    v.first() == Some(&b'1')
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn pv_guest_bit_parse() {
+        assert!(!pv_guest_bit(b""));
+        assert!(!pv_guest_bit(b"0\n"));
+        assert!(pv_guest_bit(b"1\n"));
+        assert!(pv_guest_bit(b"1"));
+    }
+
+    #[test]
+    fn sysfs_path_relocate() {
+        assert_eq!(relocate_sysfs_path(None, "/sys/bus/ap"), "/sys/bus/ap");
+        let root = Some("/tmp/fake");
+        assert_eq!(relocate_sysfs_path(root, "/sys/bus/ap"), "/tmp/fake/bus/ap");
+        assert_eq!(relocate_sysfs_path(root, "/sys"), "/tmp/fake");
+        assert_eq!(relocate_sysfs_path(root, "/sysfoo"), "/sysfoo");
+        assert_eq!(relocate_sysfs_path(root, "/dev/uv"), "/dev/uv");
+    }
+
     #[test]
     fn msb_flags() {
         let v = 17;
@@ -11,7 +11,9 @@ use std::{
     ffi::c_ulong,
     fs::File,
     os::unix::prelude::{AsRawFd, RawFd},
+    path::Path,
 };
+use zerocopy::IntoBytes;
 
 #[cfg(not(test))]
 use ::libc::ioctl;
@@ -22,14 +24,20 @@ use test::mock_libc::ioctl;
 /// from kernel version: 6.5 verify
 mod ffi;
 mod info;
+mod record;
+mod remote;
 mod test;
 pub(crate) use ffi::uv_ioctl;
 pub mod attest;
+pub mod backend;
 pub mod retr_secret;
 pub mod secret;
 pub mod secret_list;
 
+use backend::{UvBackend, UvcPayload, UvcRc};
 pub use info::UvDeviceInfo;
+pub use record::{RecordingBackend, ReplayBackend};
+pub use remote::{RemoteBackend, RemoteStream};
 
 /// User data for the attestation UVC
 pub type AttestationUserData = [u8; ffi::UVIO_ATT_USER_DATA_LEN];
@@ -113,6 +121,16 @@ pub trait UvCmd {
     fn data(&mut self) -> Option<&mut [u8]> {
         None
     }
+
+    /// Returns the buffers passed to the [`UvBackend`].
+    ///
+    /// Defaults to the buffer provided by [`UvCmd::data`].
+    fn payload(&mut self) -> UvcPayload<'_> {
+        match self.data() {
+            Some(data) => UvcPayload::Data(data),
+            None => UvcPayload::None,
+        }
+    }
 }
 
 /// [`UvDevice`] IOCTL control block.
@@ -179,7 +197,8 @@ impl UvcSuccess {
 /// userspace.
 ///
 /// On s390 machines with Ultravisor enabled (Secure Execution guest & hosts) the device at
-/// `/dev/uv` will accept ioctls.
+/// `/dev/uv` will accept ioctls. Other transports or emulations of the Ultravisor can be plugged
+/// in by providing an [`UvBackend`] to [`UvDevice::with_backend`].
 ///
 /// # Example
 ///
@@ -200,7 +219,7 @@ impl UvcSuccess {
 /// // do something with the result
 /// ```
 #[derive(Debug)]
-pub struct UvDevice(File);
+pub struct UvDevice(Box<dyn UvBackend>);
 
 impl UvDevice {
     const PATH: &'static str = "/dev/uv";
@@ -213,17 +232,32 @@ impl UvDevice {
     ///
     /// This function will return an error if the device file cannot be opened.
     pub fn open() -> Result<Self> {
-        Ok(Self(
+        Self::open_with(Self::PATH)
+    }
+
+    /// Open the uvdevice located at `path`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the device file cannot be opened.
+    pub fn open_with<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        Ok(Self::with_backend(DeviceBackend(
             std::fs::OpenOptions::new()
                 .read(true)
                 .write(true)
-                .open(Self::PATH)
+                .open(path)
                 .map_err(|e| Error::FileAccess {
                     ty: FileAccessErrorType::Open,
-                    path: (Self::PATH).into(),
+                    path: path.into(),
                     source: e,
                 })?,
-        ))
+        )))
+    }
+
+    /// Create an uvdevice that sends all Ultravisor commands to `backend`
+    pub fn with_backend<B: UvBackend + 'static>(backend: B) -> Self {
+        Self(Box::new(backend))
     }
 
     /// Send an Ultravisor Command via this uvdevice.
@@ -239,10 +273,9 @@ impl UvDevice {
     ///
     /// [`UvcSuccess`] if the UVC executed successfully
     pub fn send_cmd<C: UvCmd>(&self, cmd: &mut C) -> Result<UvcSuccess> {
-        let mut cb = IoctlCb::new(cmd.data())?;
-        ioctl_raw(self.0.as_raw_fd(), cmd.cmd(), &mut cb)?;
+        let UvcRc { rc, rrc } = self.0.call(cmd.cmd(), cmd.payload())?;
 
-        match (cb.rc(), cb.rrc()) {
+        match (rc, rrc) {
             (Self::RC_SUCCESS, _) => Ok(UvcSuccess::RC_SUCCESS),
             (Self::RC_MORE_DATA, _) => Ok(UvcSuccess::RC_MORE_DATA),
             (rc, rrc) => Err(Error::Uv {
@@ -253,3 +286,34 @@ impl UvDevice {
         }
     }
 }
+
+/// [`UvBackend`] that sends IOCTLs to a uvdevice file.
+#[derive(Debug)]
+struct DeviceBackend(File);
+
+impl UvBackend for DeviceBackend {
+    fn call(&self, cmd: u64, payload: UvcPayload<'_>) -> Result<UvcRc> {
+        let fd = self.0.as_raw_fd();
+        let cb = match payload {
+            UvcPayload::None => {
+                let mut cb = IoctlCb::new(None)?;
+                ioctl_raw(fd, cmd, &mut cb)?;
+                cb
+            }
+            UvcPayload::Data(data) => {
+                let mut cb = IoctlCb::new(Some(data))?;
+                ioctl_raw(fd, cmd, &mut cb)?;
+                cb
+            }
+            UvcPayload::Attestation(mut att) => {
+                let mut uvio_attest = att.ioctl_cb()?;
+                let mut cb = IoctlCb::new(Some(uvio_attest.as_mut_bytes()))?;
+                ioctl_raw(fd, cmd, &mut cb)?;
+                *att.additional_len = uvio_attest.add_data_len;
+                *att.cuid = uvio_attest.config_uid;
+                cb
+            }
+        };
+        Ok(UvcRc::new(cb.rc(), cb.rrc()))
+    }
+}
@@ -1,7 +1,10 @@
 // SPDX-License-Identifier: MIT
 //
 // Copyright IBM Corp. 2024
-use super::{ffi, AttestationUserData, ConfigUid, UvCmd};
+use super::{
+    backend::{AttestationPayload, UvcPayload},
+    ffi, AttestationUserData, ConfigUid, UvCmd,
+};
 use crate::{Error, Result};
 use std::ptr;
 use zerocopy::{FromZeros, IntoBytes};
@@ -44,7 +47,7 @@ pub struct AttestationCmd {
     // all sizes are guaranteed to fit in the exchange format/UV-Call at any time
     // attestation data, these must not changed by this tooling, this is an invariant of this
     // struct, so that the raw pointer stay valid through the lifetime of this struct.
-    // no mutable references are ever passed from this struct
+    // mutable references are only passed as part of the UvcPayload, which cannot reallocate
     arcb: Box<[u8]>,
     measurement: Vec<u8>,
     additional: Option<Vec<u8>>,
@@ -224,6 +227,18 @@ impl UvCmd for AttestationCmd {
     fn data(&mut self) -> Option<&mut [u8]> {
         Some(self.uvio_attest.as_mut_bytes())
     }
+
+    fn payload(&mut self) -> UvcPayload<'_> {
+        let user_len = self.uvio_attest.user_data_len as usize;
+        UvcPayload::Attestation(AttestationPayload {
+            arcb: &self.arcb,
+            user_data: &self.uvio_attest.user_data[..user_len],
+            measurement: &mut self.measurement,
+            additional: self.additional.as_deref_mut(),
+            additional_len: &mut self.uvio_attest.add_data_len,
+            cuid: &mut self.uvio_attest.config_uid,
+        })
+    }
 }
 
 fn opt_to_mut_ptr_u64(opt: &mut Option<&mut [u8]>) -> u64 {
@@ -245,7 +260,7 @@ impl ffi::uvio_attest {
     ///  - `additional.len() < u32::MAX`
     ///  - `user_len() <= 256`
     ///  - pointer fits into an u64
-    unsafe fn new(
+    pub(super) unsafe fn new(
         arcb: &[u8],
         measurement: &mut [u8],
         mut additional: Option<&mut [u8]>,
@@ -0,0 +1,365 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use super::{ffi, AttestationUserData, ConfigUid, UvDevice};
+use crate::{Error, Result};
+use std::{fmt::Debug, path::PathBuf, str::FromStr};
+
+/// Return codes of an Ultravisor call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct UvcRc {
+    /// Ultravisor return code
+    pub rc: u16,
+    /// Ultravisor return reason code
+    pub rrc: u16,
+}
+
+impl UvcRc {
+    /// Creates a new [`UvcRc`].
+    pub const fn new(rc: u16, rrc: u16) -> Self {
+        Self { rc, rrc }
+    }
+}
+
+/// Returns the uvdevice IOCTL number encoded in `cmd`.
+pub const fn ioctl_nr(cmd: u64) -> u8 {
+    cmd as u8
+}
+
+/// Backend of an [`UvDevice`].
+///
+/// A backend receives the IOCTL command and a view on the buffers of a
+/// [`UvCmd`](super::UvCmd) and executes the Ultravisor call. The default backend sends the IOCTL
+/// to the uvdevice at `/dev/uv`. Other backends may emulate the Ultravisor, record or replay
+/// calls, or forward them to another system.
+///
+/// Implementations report failures of the transport as [`Error`] and the return codes of the
+/// Ultravisor as [`UvcRc`]. Output buffers are updated in place.
+pub trait UvBackend: Debug + Send + Sync {
+    /// Execute the Ultravisor call `cmd` with the buffers in `payload`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the call could not be delivered.
+    fn call(&self, cmd: u64, payload: UvcPayload<'_>) -> Result<UvcRc>;
+}
+
+impl UvBackend for UvDevice {
+    fn call(&self, cmd: u64, payload: UvcPayload<'_>) -> Result<UvcRc> {
+        self.0.call(cmd, payload)
+    }
+}
+
+/// Buffers of the _Retrieve Attestation Measurement_ UVC
+#[derive(Debug)]
+pub struct AttestationPayload<'a> {
+    /// Attestation request control block (in)
+    pub arcb: &'a [u8],
+    /// User data (in)
+    pub user_data: &'a [u8],
+    /// Attestation measurement (out)
+    pub measurement: &'a mut [u8],
+    /// Additional data buffer (out)
+    pub additional: Option<&'a mut [u8]>,
+    /// Length of the additional data written by the Ultravisor (out)
+    pub additional_len: &'a mut u32,
+    /// Configuration Unique ID (out)
+    pub cuid: &'a mut ConfigUid,
+}
+
+/// Buffers passed to an [`UvBackend`]
+#[derive(Debug)]
+pub enum UvcPayload<'a> {
+    /// The command has no data
+    None,
+    /// Single buffer used for input and output
+    Data(&'a mut [u8]),
+    /// Buffers of the attestation command
+    Attestation(AttestationPayload<'a>),
+}
+
+impl UvcPayload<'_> {
+    /// Reborrow the buffers, e.g. to pass them to another backend.
+    pub fn reborrow(&mut self) -> UvcPayload<'_> {
+        match self {
+            Self::None => UvcPayload::None,
+            Self::Data(d) => UvcPayload::Data(d),
+            Self::Attestation(a) => UvcPayload::Attestation(AttestationPayload {
+                arcb: a.arcb,
+                user_data: a.user_data,
+                measurement: a.measurement,
+                additional: a.additional.as_deref_mut(),
+                additional_len: a.additional_len,
+                cuid: a.cuid,
+            }),
+        }
+    }
+
+    /// Copies the current content of all buffers.
+    pub fn to_owned(&self) -> OwnedUvcPayload {
+        match self {
+            Self::None => OwnedUvcPayload::None,
+            Self::Data(d) => OwnedUvcPayload::Data(d.to_vec()),
+            Self::Attestation(a) => OwnedUvcPayload::Attestation {
+                arcb: a.arcb.to_vec(),
+                user_data: a.user_data.to_vec(),
+                measurement: a.measurement.to_vec(),
+                additional: a.additional.as_deref().map(<[u8]>::to_vec),
+                additional_len: *a.additional_len,
+                cuid: *a.cuid,
+            },
+        }
+    }
+
+    /// Overwrites the output buffers with the content of `other`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the kind or the buffer sizes do not match.
+    pub fn update_from(&mut self, other: &OwnedUvcPayload) -> Result<()> {
+        fn copy(dst: &mut [u8], src: &[u8]) -> Result<()> {
+            if dst.len() != src.len() {
+                return Err(Error::LengthMismatch {
+                    expected: dst.len(),
+                    actual: src.len(),
+                });
+            }
+            dst.copy_from_slice(src);
+            Ok(())
+        }
+
+        match (self, other) {
+            (Self::None, OwnedUvcPayload::None) => Ok(()),
+            (Self::Data(dst), OwnedUvcPayload::Data(src)) => copy(dst, src),
+            (
+                Self::Attestation(dst),
+                OwnedUvcPayload::Attestation {
+                    measurement,
+                    additional,
+                    additional_len,
+                    cuid,
+                    ..
+                },
+            ) => {
+                copy(dst.measurement, measurement)?;
+                match (dst.additional.as_deref_mut(), additional) {
+                    (Some(d), Some(s)) => copy(d, s)?,
+                    (None, None) => (),
+                    _ => return Err(Error::UvBackend("additional data mismatch".to_string())),
+                }
+                *dst.additional_len = *additional_len;
+                *dst.cuid = *cuid;
+                Ok(())
+            }
+            _ => Err(Error::UvBackend("payload kind mismatch".to_string())),
+        }
+    }
+}
+
+/// Owned copy of an [`UvcPayload`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum OwnedUvcPayload {
+    None,
+    Data(Vec<u8>),
+    Attestation {
+        arcb: Vec<u8>,
+        user_data: Vec<u8>,
+        measurement: Vec<u8>,
+        additional: Option<Vec<u8>>,
+        additional_len: u32,
+        cuid: ConfigUid,
+    },
+}
+
+impl OwnedUvcPayload {
+    /// Borrow the buffers as [`UvcPayload`].
+    pub fn as_payload(&mut self) -> UvcPayload<'_> {
+        match self {
+            Self::None => UvcPayload::None,
+            Self::Data(d) => UvcPayload::Data(d),
+            Self::Attestation {
+                arcb,
+                user_data,
+                measurement,
+                additional,
+                additional_len,
+                cuid,
+            } => UvcPayload::Attestation(AttestationPayload {
+                arcb,
+                user_data,
+                measurement,
+                additional: additional.as_deref_mut(),
+                additional_len,
+                cuid,
+            }),
+        }
+    }
+}
+
+impl AttestationPayload<'_> {
+    /// Creates the IOCTL struct pointing to the buffers of this payload.
+    ///
+    /// The returned struct must not outlive `self`.
+    pub(super) fn ioctl_cb(&mut self) -> Result<ffi::uvio_attest> {
+        let too_large = |field| Error::UvBackend(format!("{field} too large"));
+        if self.user_data.len() > ffi::UVIO_ATT_USER_DATA_LEN {
+            return Err(too_large("user data"));
+        }
+        if self.arcb.len() > ffi::UVIO_ATT_ARCB_MAX_LEN {
+            return Err(too_large("attestation request"));
+        }
+        if self.measurement.len() > ffi::UVIO_ATT_MEASUREMENT_MAX_LEN {
+            return Err(too_large("measurement"));
+        }
+        if self.additional.as_ref().map_or(0, |a| a.len()) > ffi::UVIO_ATT_ADDITIONAL_MAX_LEN {
+            return Err(too_large("additional data"));
+        }
+        let mut user_data: AttestationUserData = [0; ffi::UVIO_ATT_USER_DATA_LEN];
+        user_data[..self.user_data.len()].copy_from_slice(self.user_data);
+
+        // SAFETY: all sizes were checked above.
+        Ok(unsafe {
+            ffi::uvio_attest::new(
+                self.arcb,
+                self.measurement,
+                self.additional.as_deref_mut(),
+                Some(user_data),
+                Some(self.user_data.len() as u16),
+            )
+        })
+    }
+}
+
+/// Description of an [`UvBackend`] as used on the command line
+///
+/// | Specification   | Backend                                                 |
+/// |-----------------|---------------------------------------------------------|
+/// | `device:PATH`   | uvdevice at `PATH`                                      |
+/// | `record:DIR`    | uvdevice at `/dev/uv`, record all calls into `DIR`      |
+/// | `replay:DIR`    | replay the calls recorded in `DIR`                      |
+/// | `remote:SOCKET` | forward the calls to the server listening at `SOCKET`   |
+/// | `soft:DIR`      | software Ultravisor set up from the files in `DIR`      |
+///
+/// The software Ultravisor is implemented by `s390_pv::testing::SoftUv`, which also opens the
+/// `soft:DIR` backend. [`UvBackendSpec::open`] cannot open it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UvBackendSpec {
+    /// uvdevice at the given path
+    Device(PathBuf),
+    /// uvdevice at `/dev/uv`, record all calls into the directory
+    Record(PathBuf),
+    /// Replay recorded calls from the directory
+    Replay(PathBuf),
+    /// Forward calls to a server listening on the Unix socket
+    Remote(PathBuf),
+    /// Software Ultravisor set up from the files in the directory
+    Soft(PathBuf),
+}
+
+impl FromStr for UvBackendSpec {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (kind, arg) = s
+            .split_once(':')
+            .filter(|(_, arg)| !arg.is_empty())
+            .ok_or_else(|| Error::UvBackend(format!("invalid backend specification '{s}'")))?;
+        let arg = PathBuf::from(arg);
+        match kind {
+            "device" => Ok(Self::Device(arg)),
+            "record" => Ok(Self::Record(arg)),
+            "replay" => Ok(Self::Replay(arg)),
+            "remote" => Ok(Self::Remote(arg)),
+            "soft" => Ok(Self::Soft(arg)),
+            _ => Err(Error::UvBackend(format!("unknown backend '{kind}'"))),
+        }
+    }
+}
+
+impl UvBackendSpec {
+    /// Open an [`UvDevice`] using the specified backend.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the backend cannot be opened, or is a
+    /// [`UvBackendSpec::Soft`] backend.
+    pub fn open(&self) -> Result<UvDevice> {
+        match self {
+            Self::Device(path) => UvDevice::open_with(path),
+            Self::Record(dir) => Ok(UvDevice::with_backend(super::RecordingBackend::new(
+                UvDevice::open()?,
+                dir,
+            )?)),
+            Self::Replay(dir) => Ok(UvDevice::with_backend(super::ReplayBackend::open(dir)?)),
+            Self::Remote(socket) => Ok(UvDevice::with_backend(super::RemoteBackend::connect(
+                socket,
+            )?)),
+            Self::Soft(dir) => Err(Error::UvBackend(format!(
+                "the software Ultravisor in '{}' is not available",
+                dir.display()
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn spec_from_str() {
+        assert_eq!(
+            "device:/dev/uv1".parse::<UvBackendSpec>().unwrap(),
+            UvBackendSpec::Device("/dev/uv1".into())
+        );
+        assert_eq!(
+            "replay:a:b".parse::<UvBackendSpec>().unwrap(),
+            UvBackendSpec::Replay("a:b".into())
+        );
+        assert_eq!(
+            "soft:dir".parse::<UvBackendSpec>().unwrap(),
+            UvBackendSpec::Soft("dir".into())
+        );
+        assert!("device:".parse::<UvBackendSpec>().is_err());
+        assert!("/dev/uv".parse::<UvBackendSpec>().is_err());
+        assert!("tcp:localhost".parse::<UvBackendSpec>().is_err());
+    }
+
+    #[test]
+    fn payload_update() {
+        let mut cuid = [0; 16];
+        let mut len = 4;
+        let mut meas = [0; 8];
+        let mut add = [0; 4];
+        let mut payload = UvcPayload::Attestation(AttestationPayload {
+            arcb: &[1, 2],
+            user_data: &[],
+            measurement: &mut meas,
+            additional: Some(&mut add),
+            additional_len: &mut len,
+            cuid: &mut cuid,
+        });
+        let mut owned = payload.to_owned();
+        if let OwnedUvcPayload::Attestation {
+            measurement,
+            additional_len,
+            cuid,
+            ..
+        } = &mut owned
+        {
+            measurement.fill(0x42);
+            *additional_len = 2;
+            cuid.fill(0x17);
+        }
+        payload.update_from(&owned).unwrap();
+        assert_eq!(payload.to_owned(), owned);
+        assert!(payload
+            .update_from(&OwnedUvcPayload::Data(vec![0; 8]))
+            .is_err());
+        assert_eq!(meas, [0x42; 8]);
+        assert_eq!(len, 2);
+        assert_eq!(cuid, [0x17; 16]);
+    }
+}
@@ -24,7 +24,7 @@ use zerocopy::{FromZeros, IntoBytes};
 ///
 /// Note that bit 0 is always zero for `supp_uv_cmds`
 /// as there is no corresponding Info UV-call.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct UvDeviceInfo {
     supp_uvio_cmds: Lsb0Flags64,
     supp_uv_cmds: Option<Lsb0Flags64>,
@@ -59,6 +59,24 @@ impl UvDeviceInfo {
             Err(e) => Err(e),
         }
     }
+
+    /// Create the information from raw bitmasks, e.g. from a snapshot of another system.
+    pub fn from_raw(supp_uvio_cmds: u64, supp_uv_cmds: Option<u64>) -> Self {
+        Self {
+            supp_uvio_cmds: supp_uvio_cmds.into(),
+            supp_uv_cmds: supp_uv_cmds.map(Into::into),
+        }
+    }
+
+    /// Bitmask of the IOCTLs supported by the uvdevice (LSB0 ordering).
+    pub fn supp_uvio_cmds(&self) -> u64 {
+        self.supp_uvio_cmds.into()
+    }
+
+    /// Bitmask of the corresponding Ultravisor calls (LSB0 ordering), if available.
+    pub fn supp_uv_cmds(&self) -> Option<u64> {
+        self.supp_uv_cmds.map(Into::into)
+    }
 }
 
 impl From<uvio_uvdev_info> for UvDeviceInfo {
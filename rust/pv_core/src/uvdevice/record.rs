@@ -0,0 +1,420 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use super::{
+    backend::{OwnedUvcPayload, UvBackend, UvcPayload, UvcRc},
+    UvDevice,
+};
+use crate::{
+    misc::{decode_hex, encode_hex},
+    Error, FileAccessErrorType, FileIoErrorType, Result,
+};
+use log::debug;
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// One Ultravisor call or its response in the exchange format.
+///
+/// The exchange format is a block of `KEY VALUE` lines terminated by an empty line. Binary
+/// values are hex encoded. It is used by the recording files and the remote protocol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct UvcBlock {
+    pub cmd: u64,
+    pub rc: Option<UvcRc>,
+    pub errno: Option<i32>,
+    pub payload: OwnedUvcPayload,
+}
+
+impl UvcBlock {
+    pub fn request(cmd: u64, payload: OwnedUvcPayload) -> Self {
+        Self {
+            cmd,
+            rc: None,
+            errno: None,
+            payload,
+        }
+    }
+
+    /// Creates the response to a call.
+    ///
+    /// Only the return codes and errno values of failed IOCTLs can be transferred; other
+    /// errors are returned.
+    pub fn response(cmd: u64, res: Result<UvcRc>, payload: OwnedUvcPayload) -> Result<Self> {
+        let (rc, errno) = match res {
+            Ok(rc) => (Some(rc), None),
+            Err(Error::Io(e)) if e.raw_os_error().is_some() => (None, e.raw_os_error()),
+            Err(e) => return Err(e),
+        };
+        Ok(Self {
+            cmd,
+            rc,
+            errno,
+            payload,
+        })
+    }
+
+    /// Returns the result of the call described by this response.
+    pub fn result(&self) -> Result<UvcRc> {
+        match (self.rc, self.errno) {
+            (_, Some(errno)) => Err(std::io::Error::from_raw_os_error(errno).into()),
+            (Some(rc), None) => Ok(rc),
+            (None, None) => Err(Self::err("response without return code")),
+        }
+    }
+
+    pub fn write<W: Write>(&self, w: &mut W) -> std::io::Result<()> {
+        writeln!(w, "cmd {:#x}", self.cmd)?;
+        if let Some(UvcRc { rc, rrc }) = self.rc {
+            writeln!(w, "rc {rc:#06x} {rrc:#06x}")?;
+        }
+        if let Some(errno) = self.errno {
+            writeln!(w, "errno {errno}")?;
+        }
+        match &self.payload {
+            OwnedUvcPayload::None => writeln!(w, "payload none")?,
+            OwnedUvcPayload::Data(d) => {
+                writeln!(w, "payload data")?;
+                writeln!(w, "data {}", encode_hex(d))?;
+            }
+            OwnedUvcPayload::Attestation {
+                arcb,
+                user_data,
+                measurement,
+                additional,
+                additional_len,
+                cuid,
+            } => {
+                writeln!(w, "payload attestation")?;
+                writeln!(w, "arcb {}", encode_hex(arcb))?;
+                writeln!(w, "user-data {}", encode_hex(user_data))?;
+                writeln!(w, "measurement {}", encode_hex(measurement))?;
+                if let Some(additional) = additional {
+                    writeln!(w, "additional {}", encode_hex(additional))?;
+                }
+                writeln!(w, "additional-len {additional_len}")?;
+                writeln!(w, "cuid {}", encode_hex(cuid))?;
+            }
+        }
+        writeln!(w)?;
+        w.flush()
+    }
+
+    /// Reads the next block.
+    ///
+    /// Returns `None` if the reader is at EOF.
+    pub fn read<R: BufRead>(r: &mut R) -> Result<Option<Self>> {
+        let mut fields = Vec::new();
+        loop {
+            let mut line = String::new();
+            if r.read_line(&mut line)? == 0 {
+                if fields.is_empty() {
+                    return Ok(None);
+                }
+                return Err(Self::err("unexpected end of block"));
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            fields.push((key.to_string(), value.to_string()));
+        }
+        Self::from_fields(&fields).map(Some)
+    }
+
+    fn err(msg: &str) -> Error {
+        Error::UvBackend(format!("invalid exchange format: {msg}"))
+    }
+
+    fn from_fields(fields: &[(String, String)]) -> Result<Self> {
+        let get = |key: &str| {
+            fields
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.as_str())
+        };
+        let req = |key: &str| get(key).ok_or_else(|| Self::err(&format!("missing '{key}'")));
+        let hex = |key: &str| req(key).and_then(decode_hex);
+
+        let cmd = crate::misc::try_parse_u64(req("cmd")?, "cmd")?;
+        let rc = match get("rc") {
+            None => None,
+            Some(rc) => {
+                let (rc, rrc) = rc
+                    .split_once(' ')
+                    .ok_or_else(|| Self::err("invalid 'rc'"))?;
+                let parse = |v: &str| {
+                    u16::from_str_radix(v.trim_start_matches("0x"), 16)
+                        .map_err(|_| Self::err("invalid 'rc'"))
+                };
+                Some(UvcRc::new(parse(rc)?, parse(rrc)?))
+            }
+        };
+        let errno = get("errno")
+            .map(|e| e.parse().map_err(|_| Self::err("invalid 'errno'")))
+            .transpose()?;
+        let payload = match req("payload")? {
+            "none" => OwnedUvcPayload::None,
+            "data" => OwnedUvcPayload::Data(hex("data")?),
+            "attestation" => OwnedUvcPayload::Attestation {
+                arcb: hex("arcb")?,
+                user_data: hex("user-data")?,
+                measurement: hex("measurement")?,
+                additional: get("additional").map(decode_hex).transpose()?,
+                additional_len: req("additional-len")?
+                    .parse()
+                    .map_err(|_| Self::err("invalid 'additional-len'"))?,
+                cuid: hex("cuid")?
+                    .try_into()
+                    .map_err(|_| Self::err("invalid 'cuid'"))?,
+            },
+            kind => return Err(Self::err(&format!("unknown payload '{kind}'"))),
+        };
+        Ok(Self {
+            cmd,
+            rc,
+            errno,
+            payload,
+        })
+    }
+}
+
+fn record_path(dir: &Path, seq: usize) -> PathBuf {
+    dir.join(format!("{seq:04}.uvc"))
+}
+
+/// Backend that records all Ultravisor calls.
+///
+/// Forwards all calls to another [`UvDevice`] and writes the request and response of each call
+/// into the file `NNNN.uvc` of the recording directory, with `NNNN` being the sequence number of
+/// the call. The recording can be replayed with [`ReplayBackend`].
+///
+/// # Note
+///
+/// The recording contains all data exchanged with the Ultravisor in plain, including
+/// retrieved secrets.
+#[derive(Debug)]
+pub struct RecordingBackend {
+    inner: UvDevice,
+    dir: PathBuf,
+    seq: Mutex<usize>,
+}
+
+impl RecordingBackend {
+    /// Creates a new [`RecordingBackend`] that records into `dir`.
+    ///
+    /// Creates the directory if it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the directory cannot be created.
+    pub fn new<P: AsRef<Path>>(inner: UvDevice, dir: P) -> Result<Self> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir).map_err(|source| Error::FileAccess {
+            ty: FileAccessErrorType::Create,
+            path: dir.to_path_buf(),
+            source,
+        })?;
+        Ok(Self {
+            inner,
+            dir: dir.to_path_buf(),
+            seq: Mutex::new(0),
+        })
+    }
+
+    fn write_record(path: &Path, request: &UvcBlock, response: &UvcBlock) -> Result<()> {
+        let map_err = |source| Error::FileIo {
+            ty: FileIoErrorType::Write,
+            ctx: "Ultravisor call recording".to_string(),
+            path: path.to_path_buf(),
+            source,
+        };
+        let file = File::create(path).map_err(|source| Error::FileAccess {
+            ty: FileAccessErrorType::Create,
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let mut w = BufWriter::new(file);
+        request.write(&mut w).map_err(map_err)?;
+        response.write(&mut w).map_err(map_err)
+    }
+}
+
+impl UvBackend for RecordingBackend {
+    fn call(&self, cmd: u64, mut payload: UvcPayload<'_>) -> Result<UvcRc> {
+        let mut seq = self.seq.lock().unwrap_or_else(|e| e.into_inner());
+        let request = UvcBlock::request(cmd, payload.to_owned());
+        let res = self.inner.call(cmd, payload.reborrow());
+        let response = UvcBlock::response(cmd, res, payload.to_owned())?;
+
+        let path = record_path(&self.dir, *seq);
+        debug!("recording Ultravisor call to {}", path.display());
+        Self::write_record(&path, &request, &response)?;
+        *seq += 1;
+        response.result()
+    }
+}
+
+/// Backend that replays Ultravisor calls recorded by [`RecordingBackend`].
+///
+/// Each call must match the next recorded request exactly; the recorded response is
+/// then copied into the output buffers.
+#[derive(Debug)]
+pub struct ReplayBackend {
+    dir: PathBuf,
+    seq: Mutex<usize>,
+}
+
+impl ReplayBackend {
+    /// Opens the recording in `dir`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `dir` is not a readable directory.
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref();
+        std::fs::read_dir(dir).map_err(|source| Error::FileAccess {
+            ty: FileAccessErrorType::Open,
+            path: dir.to_path_buf(),
+            source,
+        })?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            seq: Mutex::new(0),
+        })
+    }
+
+    fn read_record(path: &Path) -> Result<(UvcBlock, UvcBlock)> {
+        let file = File::open(path).map_err(|source| Error::FileAccess {
+            ty: FileAccessErrorType::Open,
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let mut r = BufReader::new(file);
+        let missing = || UvcBlock::err("incomplete recording");
+        let request = UvcBlock::read(&mut r)?.ok_or_else(missing)?;
+        let response = UvcBlock::read(&mut r)?.ok_or_else(missing)?;
+        Ok((request, response))
+    }
+}
+
+impl UvBackend for ReplayBackend {
+    fn call(&self, cmd: u64, mut payload: UvcPayload<'_>) -> Result<UvcRc> {
+        let mut seq = self.seq.lock().unwrap_or_else(|e| e.into_inner());
+        let path = record_path(&self.dir, *seq);
+        debug!("replaying Ultravisor call from {}", path.display());
+        let (request, response) = Self::read_record(&path)?;
+
+        if request.cmd != cmd || request.payload != payload.to_owned() {
+            return Err(Error::UvBackend(format!(
+                "call does not match the recording at {}",
+                path.display()
+            )));
+        }
+        *seq += 1;
+        let rc = response.result()?;
+        payload.update_from(&response.payload)?;
+        Ok(rc)
+    }
+}
+
+#[cfg(test)]
+pub(super) mod test {
+    use super::*;
+    use crate::uv::{ListCmd, LockCmd, UvCmd, UvDeviceInfo};
+
+    /// Backend answering list requests with `0x42` and rejecting everything else
+    #[derive(Debug)]
+    pub struct FakeBackend;
+    impl UvBackend for FakeBackend {
+        fn call(&self, cmd: u64, payload: UvcPayload<'_>) -> Result<UvcRc> {
+            match (super::super::backend::ioctl_nr(cmd), payload) {
+                (3, UvcPayload::Data(d)) => {
+                    d.fill(0x42);
+                    Ok(UvcRc::new(0x0001, 0))
+                }
+                (0, _) => Err(std::io::Error::from_raw_os_error(libc::ENOTTY).into()),
+                _ => Ok(UvcRc::new(0x0102, 0)),
+            }
+        }
+    }
+
+    #[test]
+    fn record_replay() {
+        let dir = std::env::temp_dir().join(format!("pv_core_record_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let uv = UvDevice::with_backend(
+            RecordingBackend::new(UvDevice::with_backend(FakeBackend), &dir).unwrap(),
+        );
+        let mut list = ListCmd::with_pages(1);
+        uv.send_cmd(&mut list).unwrap();
+        assert!(matches!(
+            uv.send_cmd(&mut LockCmd),
+            Err(Error::Uv { rc: 0x0102, .. })
+        ));
+        assert!(UvDeviceInfo::get(&uv).is_ok());
+        assert!(dir.join("0002.uvc").exists());
+
+        let uv = UvDevice::with_backend(ReplayBackend::open(&dir).unwrap());
+        let mut replayed = ListCmd::with_pages(1);
+        uv.send_cmd(&mut replayed).unwrap();
+        assert_eq!(replayed.data(), list.data());
+        // order of calls must match the recording
+        assert!(matches!(UvDeviceInfo::get(&uv), Err(Error::UvBackend(_))));
+        assert!(matches!(
+            uv.send_cmd(&mut LockCmd),
+            Err(Error::Uv { rc: 0x0102, .. })
+        ));
+        assert!(UvDeviceInfo::get(&uv).is_ok());
+        assert!(uv.send_cmd(&mut LockCmd).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn block_roundtrip() {
+        let blocks = [
+            UvcBlock::request(0xc0407504, OwnedUvcPayload::None),
+            UvcBlock::response(
+                0xc0407500,
+                Err(std::io::Error::from_raw_os_error(libc::ENOTTY).into()),
+                OwnedUvcPayload::Data(vec![]),
+            )
+            .unwrap(),
+            UvcBlock::response(
+                0xc0407503,
+                Ok(UvcRc::new(0x0100, 0)),
+                OwnedUvcPayload::Data(vec![1, 2, 3]),
+            )
+            .unwrap(),
+            UvcBlock {
+                cmd: 0xc0407501,
+                rc: Some(UvcRc::new(0x0001, 0x0017)),
+                errno: None,
+                payload: OwnedUvcPayload::Attestation {
+                    arcb: vec![0xaa; 12],
+                    user_data: vec![],
+                    measurement: vec![0x42; 64],
+                    additional: Some(vec![0; 16]),
+                    additional_len: 8,
+                    cuid: [0x17; 16],
+                },
+            },
+        ];
+        let mut buf = Vec::new();
+        for block in &blocks {
+            block.write(&mut buf).unwrap();
+        }
+        let mut r = buf.as_slice();
+        for block in &blocks {
+            assert_eq!(&UvcBlock::read(&mut r).unwrap().unwrap(), block);
+        }
+        assert!(UvcBlock::read(&mut r).unwrap().is_none());
+        assert!(UvcBlock::read(&mut "cmd 0x1\n\n".as_bytes()).is_err());
+    }
+}
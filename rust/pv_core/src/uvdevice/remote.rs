@@ -0,0 +1,113 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use super::{
+    backend::{UvBackend, UvcPayload, UvcRc},
+    record::UvcBlock,
+};
+use crate::{Error, FileAccessErrorType, Result};
+use log::debug;
+use std::{
+    io::{BufReader, Read, Write},
+    os::unix::net::UnixStream,
+    path::Path,
+    sync::Mutex,
+};
+
+/// Transport for [`RemoteBackend`]
+pub trait RemoteStream: Read + Write + Send + std::fmt::Debug {}
+impl<T: Read + Write + Send + std::fmt::Debug> RemoteStream for T {}
+
+/// Backend that forwards Ultravisor calls to a remote system.
+///
+/// The request of each call is sent over a stream, the server executes it with its
+/// [`UvBackend`] and sends back the response, see [`RemoteBackend::serve`]. Requests and
+/// responses use the same line based format as the recordings of the
+/// [`RecordingBackend`](super::RecordingBackend).
+#[derive(Debug)]
+pub struct RemoteBackend {
+    stream: Mutex<BufReader<Box<dyn RemoteStream>>>,
+}
+
+impl RemoteBackend {
+    /// Creates a new [`RemoteBackend`] that uses `stream` as transport.
+    pub fn new<S: RemoteStream + 'static>(stream: S) -> Self {
+        Self {
+            stream: Mutex::new(BufReader::new(Box::new(stream))),
+        }
+    }
+
+    /// Connects to the server listening at the Unix socket `path`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the connection cannot be established.
+    pub fn connect<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let stream = UnixStream::connect(path).map_err(|source| Error::FileAccess {
+            ty: FileAccessErrorType::Open,
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Ok(Self::new(stream))
+    }
+
+    /// Serves the requests received over `stream` with `backend` until the stream is closed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the stream fails or contains malformed requests.
+    /// Failed IOCTLs of `backend` are forwarded to the client.
+    pub fn serve<S: Read + Write, B: UvBackend + ?Sized>(stream: S, backend: &B) -> Result<()> {
+        let mut stream = BufReader::new(stream);
+        while let Some(mut request) = UvcBlock::read(&mut stream)? {
+            let res = backend.call(request.cmd, request.payload.as_payload());
+            debug!("served remote Ultravisor call {:#x}: {res:?}", request.cmd);
+            UvcBlock::response(request.cmd, res, request.payload)?.write(stream.get_mut())?;
+        }
+        Ok(())
+    }
+}
+
+impl UvBackend for RemoteBackend {
+    fn call(&self, cmd: u64, mut payload: UvcPayload<'_>) -> Result<UvcRc> {
+        let mut stream = self.stream.lock().unwrap_or_else(|e| e.into_inner());
+        UvcBlock::request(cmd, payload.to_owned()).write(stream.get_mut())?;
+
+        let response = UvcBlock::read(&mut *stream)?
+            .ok_or_else(|| Error::UvBackend("remote closed the connection".to_string()))?;
+        if response.cmd != cmd {
+            return Err(Error::UvBackend("unexpected remote response".to_string()));
+        }
+        let rc = response.result()?;
+        payload.update_from(&response.payload)?;
+        Ok(rc)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::uv::{ListCmd, LockCmd, UvCmd, UvDevice, UvDeviceInfo};
+    use crate::uvdevice::record::test::FakeBackend;
+
+    #[test]
+    fn remote() {
+        let (client, server) = UnixStream::pair().unwrap();
+        let handle = std::thread::spawn(move || RemoteBackend::serve(server, &FakeBackend));
+
+        let uv = UvDevice::with_backend(RemoteBackend::new(client));
+        let mut list = ListCmd::new();
+        uv.send_cmd(&mut list).unwrap();
+        assert!(list.data().unwrap().iter().all(|b| *b == 0x42));
+        assert!(matches!(
+            uv.send_cmd(&mut LockCmd),
+            Err(Error::Uv { rc: 0x0102, .. })
+        ));
+        assert!(UvDeviceInfo::get(&uv).is_ok());
+
+        drop(uv);
+        handle.join().unwrap().unwrap();
+    }
+}
@@ -132,7 +132,7 @@ impl UvDevice {
     /// Use this file as backing file for  `uvdevice`. This is OK, as the ioctl is mocked and never touches the
     /// passed file
     fn test_dev() -> Self {
-        Self(File::open(".").unwrap())
+        Self::with_backend(DeviceBackend(File::open(".").unwrap()))
     }
 }
 
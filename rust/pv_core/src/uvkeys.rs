@@ -4,51 +4,43 @@
 
 use std::io::{BufRead, BufReader, Read};
 
-use enum_dispatch::enum_dispatch;
-use pv::misc::decode_hex;
-
-use super::try_copy_slice_to_array;
-use crate::error::{Error, Result};
-
-/// The `enum_dispatch` macros needs at least one local trait to be implemented.
-#[allow(unused)]
-#[enum_dispatch(UvKeyHashes)]
-trait UvKeyHashTrait: AsRef<[u8]> {}
+use crate::{
+    misc::decode_hex,
+    {Error, Result},
+};
 
+/// Hash of an Ultravisor key as shown in `/sys/firmware/uv/keys`
 #[derive(Debug, PartialEq, Eq)]
 pub struct UvKeyHashV1([u8; 32]);
 
-#[non_exhaustive]
-#[enum_dispatch]
-#[derive(PartialEq, Eq, Debug)]
-pub enum UvKeyHash {
-    UvKeyHashV1(UvKeyHashV1),
-}
-
-impl AsRef<[u8]> for UvKeyHash {
-    fn as_ref(&self) -> &[u8] {
-        match self {
-            Self::UvKeyHashV1(hash) => hash.as_ref(),
-        }
-    }
-}
-
-impl UvKeyHashV1 {
-    pub fn new<T: AsRef<[u8]>>(data: T) -> Result<Self> {
-        let array = try_copy_slice_to_array(data.as_ref())?;
-        Ok(Self(array))
-    }
-}
-
+/// Key hashes of the Ultravisor as shown in `/sys/firmware/uv/keys/all`
 #[derive(Debug, PartialEq, Eq)]
 pub struct UvKeyHashesV1 {
+    /// Public host-key hash
     pub pchkh: UvKeyHashV1,
+    /// Backup public host-key hash
     pub pbhkh: UvKeyHashV1,
+    /// Reserved key hashes
     pub res: [UvKeyHashV1; 13],
 }
 
 impl UvKeyHashV1 {
+    /// An all-zero key hash
     pub const UV_KEY_HASH_NULL: Self = Self([0x0_u8; 32]);
+
+    /// Creates a key hash from 32 bytes.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `data` is not 32 bytes long.
+    pub fn new<T: AsRef<[u8]>>(data: T) -> Result<Self> {
+        let data = data.as_ref();
+        let array = data.try_into().map_err(|_| Error::LengthMismatch {
+            expected: 32,
+            actual: data.len(),
+        })?;
+        Ok(Self(array))
+    }
 }
 
 impl AsRef<[u8]> for UvKeyHashV1 {
@@ -62,11 +54,8 @@ impl TryFrom<&str> for UvKeyHashV1 {
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         let bytes = decode_hex(value)?;
-        if bytes.len() != 32 {
-            return Err(Error::InvalidTargetKeyHash);
-        }
-
-        Ok(Self(bytes.try_into().unwrap()))
+        let array = bytes.try_into().map_err(|_| Error::InvalidTargetKeyHash)?;
+        Ok(Self(array))
     }
 }
 
@@ -79,6 +68,7 @@ impl TryFrom<String> for UvKeyHashV1 {
 }
 
 impl UvKeyHashesV1 {
+    /// Location of the key hashes of the Ultravisor
     pub const SYS_UV_KEYS_ALL: &'static str = "/sys/firmware/uv/keys/all";
 
     /// Reads a `UvKeyHashesV1` from an [`std::io::Read`].
@@ -116,9 +106,7 @@ impl UvKeyHashesV1 {
 mod tests {
     use std::io::Cursor;
 
-    use pv::misc::decode_hex;
-
-    use crate::{pv_utils::uv_keys::UvKeyHashV1, uvdata::UvKeyHashesV1};
+    use super::*;
 
     #[test]
     fn from_reader() {
@@ -155,5 +143,13 @@ mod tests {
                 res: [UvKeyHashV1::UV_KEY_HASH_NULL; 13],
             }
         );
+        assert!(matches!(
+            UvKeyHashesV1::read_from_io(Cursor::new(&data[..65 * 14])),
+            Err(Error::InvalidUvKeyHashes)
+        ));
+        assert!(matches!(
+            UvKeyHashV1::try_from("0b72"),
+            Err(Error::InvalidTargetKeyHash)
+        ));
     }
 }
@@ -0,0 +1,445 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2024
+
+pub mod desc;
+mod snapshot;
+
+pub use snapshot::UvSnapshot;
+
+use crate::{
+    utils::{sysfs_path, try_parse_u64},
+    uv::ListableSecretType,
+    Error, FileAccessErrorType, Result,
+};
+use serde::Serialize;
+use std::{fmt::Display, fs, path::Path};
+
+/// Secure Execution mode of a system
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SeMode {
+    /// The system is a Secure Execution guest
+    Guest,
+    /// The system is a Secure Execution host
+    Host,
+    /// Secure Execution is not enabled
+    Disabled,
+    /// Guest and host mode are reported at the same time
+    Invalid,
+}
+
+impl Display for SeMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Guest => write!(f, "Secure Execution Guest Mode"),
+            Self::Host => write!(f, "Secure Execution Host Mode"),
+            Self::Disabled => write!(f, "Secure Execution is disabled"),
+            Self::Invalid => write!(f, "Configuration error: both Guest and Host enabled"),
+        }
+    }
+}
+
+/// Bitmask of an Ultravisor query attribute in MSB0 ordering
+///
+/// Most attributes are a single 64 bit word, the installed Ultravisor calls
+/// (`facilities`) span multiple words. Bit 0 is the most significant bit of
+/// the first word.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UvBitmask(Vec<u64>);
+
+impl UvBitmask {
+    /// Parse the content of a sysfs attribute.
+    ///
+    /// The content consists of whitespace separated hexadecimal words with
+    /// an optional `0x` prefix.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a word is not a valid 64 bit
+    /// hexadecimal number.
+    pub fn parse(content: &str) -> Result<Self> {
+        content
+            .split_whitespace()
+            .map(|w| try_parse_u64(w, "Ultravisor query attribute"))
+            .collect::<Result<Vec<_>>>()
+            .map(Self)
+    }
+
+    /// Number of bits in this mask
+    pub fn len(&self) -> usize {
+        self.0.len() * 64
+    }
+
+    /// Returns `true` if the mask has no bits at all
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Test if `bit` is set. Bits beyond the mask are not set.
+    pub fn is_set(&self, bit: usize) -> bool {
+        self.0
+            .get(bit / 64)
+            .is_some_and(|w| w & (1 << (63 - bit % 64)) != 0)
+    }
+
+    /// Iterate over all set bits in ascending order.
+    pub fn iter_set(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len()).filter(|b| self.is_set(*b))
+    }
+}
+
+impl From<u64> for UvBitmask {
+    fn from(value: u64) -> Self {
+        Self(vec![value])
+    }
+}
+
+/// Version encoded by bit `bit` of a version mask: bit 0 is version 0x100.
+fn bit_to_version(bit: usize) -> u32 {
+    (bit as u32 + 1) * 0x100
+}
+
+/// Bit of a version mask encoding `version`, if any.
+fn version_to_bit(version: u32) -> Option<usize> {
+    match version {
+        v if v >= 0x100 && v % 0x100 == 0 => Some((v / 0x100 - 1) as usize),
+        _ => None,
+    }
+}
+
+/// Limits reported by the Ultravisor
+///
+/// A limit is `None` if the system does not report it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct UvLimits {
+    /// Maximal address for a Secure Execution guest
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_address: Option<u64>,
+    /// Maximal number of associated secrets
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_assoc_secrets: Option<u64>,
+    /// Maximal number of CPUs in one Secure Execution guest
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_cpus: Option<u64>,
+    /// Maximal number of Secure Execution guests
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_guests: Option<u64>,
+    /// Maximal number of retrievable secrets
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_retr_secrets: Option<u64>,
+    /// Maximal number of secrets in the system
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_secrets: Option<u64>,
+}
+
+/// Ultravisor information as provided in `/sys/firmware/uv`
+///
+/// An attribute is `None` if the system does not provide it, e.g. because
+/// the kernel or the Ultravisor is too old.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UvQuery {
+    /// Secure Execution mode of the system
+    pub se_mode: Option<SeMode>,
+    /// Installed Ultravisor calls
+    pub facilities: Option<UvBitmask>,
+    /// Ultravisor feature indications
+    pub feature_indications: Option<UvBitmask>,
+    /// Supported plaintext add-secret flags
+    pub supp_add_secret_pcf: Option<UvBitmask>,
+    /// Supported add-secret request versions
+    pub supp_add_secret_req_ver: Option<UvBitmask>,
+    /// Supported attestation request versions
+    pub supp_att_req_hdr_ver: Option<UvBitmask>,
+    /// Supported plaintext attestation flags
+    pub supp_att_pflags: Option<UvBitmask>,
+    /// Supported plaintext control flags of the SE header
+    pub supp_se_hdr_pcf: Option<UvBitmask>,
+    /// Supported SE header versions
+    pub supp_se_hdr_ver: Option<UvBitmask>,
+    /// Supported secret types
+    pub supp_secret_types: Option<UvBitmask>,
+    /// Limits of the Ultravisor
+    pub limits: UvLimits,
+}
+
+/// Getter for an Ultravisor attribute relative to the Ultravisor directory,
+/// e.g. `query/facilities`. Returns `None` if the attribute does not exist.
+type AttrFn<'a> = &'a dyn Fn(&str) -> Result<Option<String>>;
+
+/// Read an attribute below `dir`, `None` if it does not exist.
+fn read_attr(dir: &Path, name: &str) -> Result<Option<String>> {
+    let path = dir.join(name);
+    match fs::read_to_string(&path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(source) => Err(Error::FileAccess {
+            ty: FileAccessErrorType::Open,
+            path,
+            source,
+        }),
+    }
+}
+
+fn read_bitmask(attr: AttrFn, name: &str) -> Result<Option<UvBitmask>> {
+    attr(name)?
+        .map(|c| {
+            UvBitmask::parse(&c).map_err(|_| Error::ParseError {
+                subject: format!("Ultravisor query attribute {name}"),
+                content: c.trim().to_string(),
+            })
+        })
+        .transpose()
+}
+
+fn read_number(attr: AttrFn, name: &str) -> Result<Option<u64>> {
+    attr(name)?
+        .map(|c| {
+            c.trim().parse().map_err(|_| Error::ParseError {
+                subject: format!("Ultravisor query attribute {name}"),
+                content: c.trim().to_string(),
+            })
+        })
+        .transpose()
+}
+
+fn read_flag(attr: AttrFn, name: &str) -> Result<Option<bool>> {
+    Ok(read_number(attr, name)?.map(|v| v == 1))
+}
+
+impl UvQuery {
+    /// Ultravisor directory in sysfs
+    pub const SYS_UV_DIR: &'static str = "/sys/firmware/uv";
+
+    /// Read the Ultravisor information of the running system.
+    ///
+    /// The sysfs root can be relocated with [`crate::misc::set_sysfs_root`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `/sys/firmware/uv` does not
+    /// exist or an attribute cannot be read or parsed.
+    pub fn read() -> Result<Self> {
+        Self::read_from(sysfs_path(Self::SYS_UV_DIR))
+    }
+
+    /// Read the Ultravisor information from `dir`, which has the layout of
+    /// `/sys/firmware/uv`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `dir` does not exist or an
+    /// attribute cannot be read or parsed.
+    pub fn read_from<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref();
+        fs::metadata(dir).map_err(|source| Error::FileAccess {
+            ty: FileAccessErrorType::Open,
+            path: dir.to_path_buf(),
+            source,
+        })?;
+        Self::from_attrs(&|name| read_attr(dir, name))
+    }
+
+    /// Interpret the Ultravisor attributes provided by `attr`.
+    pub(crate) fn from_attrs(attr: AttrFn) -> Result<Self> {
+        let se_mode = match (
+            read_flag(attr, "prot_virt_guest")?,
+            read_flag(attr, "prot_virt_host")?,
+        ) {
+            (None, None) => None,
+            (guest, host) => Some(match (guest == Some(true), host == Some(true)) {
+                (true, false) => SeMode::Guest,
+                (false, true) => SeMode::Host,
+                (false, false) => SeMode::Disabled,
+                (true, true) => SeMode::Invalid,
+            }),
+        };
+
+        Ok(Self {
+            se_mode,
+            facilities: read_bitmask(attr, "query/facilities")?,
+            feature_indications: read_bitmask(attr, "query/feature_indications")?,
+            supp_add_secret_pcf: read_bitmask(attr, "query/supp_add_secret_pcf")?,
+            supp_add_secret_req_ver: read_bitmask(attr, "query/supp_add_secret_req_ver")?,
+            supp_att_req_hdr_ver: read_bitmask(attr, "query/supp_att_req_hdr_ver")?,
+            supp_att_pflags: read_bitmask(attr, "query/supp_att_pflags")?,
+            supp_se_hdr_pcf: read_bitmask(attr, "query/supp_se_hdr_pcf")?,
+            supp_se_hdr_ver: read_bitmask(attr, "query/supp_se_hdr_ver")?,
+            supp_secret_types: read_bitmask(attr, "query/supp_secret_types")?,
+            limits: UvLimits {
+                max_address: read_number(attr, "query/max_address")?,
+                max_assoc_secrets: read_number(attr, "query/max_assoc_secrets")?,
+                max_cpus: read_number(attr, "query/max_cpus")?,
+                max_guests: read_number(attr, "query/max_guests")?,
+                max_retr_secrets: read_number(attr, "query/max_retr_secrets")?,
+                max_secrets: read_number(attr, "query/max_secrets")?,
+            },
+        })
+    }
+
+    fn versions(mask: &Option<UvBitmask>) -> Vec<u32> {
+        mask.iter()
+            .flat_map(|m| m.iter_set())
+            .map(bit_to_version)
+            .collect()
+    }
+
+    fn supports_version(mask: &Option<UvBitmask>, version: u32) -> Option<bool> {
+        mask.as_ref()
+            .map(|m| version_to_bit(version).is_some_and(|b| m.is_set(b)))
+    }
+
+    /// Supported add-secret request versions, e.g. `0x100`
+    pub fn add_secret_req_versions(&self) -> Vec<u32> {
+        Self::versions(&self.supp_add_secret_req_ver)
+    }
+
+    /// Supported attestation request versions, e.g. `0x100`
+    pub fn att_req_versions(&self) -> Vec<u32> {
+        Self::versions(&self.supp_att_req_hdr_ver)
+    }
+
+    /// Supported SE header versions, e.g. `0x100`
+    pub fn se_hdr_versions(&self) -> Vec<u32> {
+        Self::versions(&self.supp_se_hdr_ver)
+    }
+
+    /// Supported secret types
+    pub fn secret_types(&self) -> Vec<ListableSecretType> {
+        self.supp_secret_types
+            .iter()
+            .flat_map(|m| m.iter_set())
+            .filter_map(|b| u16::try_from(b).ok())
+            .map(ListableSecretType::from)
+            .collect()
+    }
+
+    /// Test if the Ultravisor call `bit` of the facilities is installed.
+    ///
+    /// Returns `None` if the system does not report the facilities.
+    pub fn has_facility(&self, bit: usize) -> Option<bool> {
+        self.facilities.as_ref().map(|m| m.is_set(bit))
+    }
+
+    /// Test if add-secret requests with `version` are supported.
+    ///
+    /// Returns `None` if the system does not report the supported versions.
+    pub fn supports_add_secret_req_version(&self, version: u32) -> Option<bool> {
+        Self::supports_version(&self.supp_add_secret_req_ver, version)
+    }
+
+    /// Test if attestation requests with `version` are supported.
+    ///
+    /// Returns `None` if the system does not report the supported versions.
+    pub fn supports_att_req_version(&self, version: u32) -> Option<bool> {
+        Self::supports_version(&self.supp_att_req_hdr_ver, version)
+    }
+
+    /// Test if SE headers with `version` are supported.
+    ///
+    /// Returns `None` if the system does not report the supported versions.
+    pub fn supports_se_hdr_version(&self, version: u32) -> Option<bool> {
+        Self::supports_version(&self.supp_se_hdr_ver, version)
+    }
+
+    /// Test if all plaintext control flags set in `pcf` are supported.
+    ///
+    /// Returns `None` if the system does not report the supported flags.
+    pub fn supports_se_hdr_pcf(&self, pcf: u64) -> Option<bool> {
+        Self::supports_flags(&self.supp_se_hdr_pcf, pcf)
+    }
+
+    /// Test if all plaintext add-secret flags set in `flags` are supported.
+    ///
+    /// Returns `None` if the system does not report the supported flags.
+    pub fn supports_add_secret_pcf(&self, flags: u64) -> Option<bool> {
+        Self::supports_flags(&self.supp_add_secret_pcf, flags)
+    }
+
+    /// Test if all plaintext attestation flags set in `flags` are supported.
+    ///
+    /// Returns `None` if the system does not report the supported flags.
+    pub fn supports_att_pflags(&self, flags: u64) -> Option<bool> {
+        Self::supports_flags(&self.supp_att_pflags, flags)
+    }
+
+    fn supports_flags(mask: &Option<UvBitmask>, flags: u64) -> Option<bool> {
+        mask.as_ref()
+            .map(|m| flags & !m.0.first().copied().unwrap_or(0) == 0)
+    }
+
+    /// Test if secrets of type `ty` are supported.
+    ///
+    /// Returns `None` if the system does not report the supported types.
+    pub fn supports_secret_type(&self, ty: ListableSecretType) -> Option<bool> {
+        let ty: u16 = ty.into();
+        self.supp_secret_types.as_ref().map(|m| m.is_set(ty.into()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::uv::{AesSizes, RetrievableSecret};
+
+    #[test]
+    fn bitmask() {
+        let m = UvBitmask::parse("0xa000000000000000\n0x0000000000000001\n").unwrap();
+        assert_eq!(m.len(), 128);
+        assert_eq!(m.iter_set().collect::<Vec<_>>(), [0, 2, 127]);
+        assert!(!m.is_set(128));
+        assert!(UvBitmask::parse("0xnothex").is_err());
+        assert!(UvBitmask::parse("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn versions() {
+        assert_eq!(bit_to_version(0), 0x100);
+        assert_eq!(bit_to_version(1), 0x200);
+        assert_eq!(version_to_bit(0x200), Some(1));
+        assert_eq!(version_to_bit(0x101), None);
+        assert_eq!(version_to_bit(0), None);
+    }
+
+    #[test]
+    fn read_from() {
+        let dir = std::env::temp_dir().join(format!("pv_core_uvquery_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("query")).unwrap();
+        fs::write(dir.join("prot_virt_guest"), "1\n").unwrap();
+        fs::write(dir.join("prot_virt_host"), "0\n").unwrap();
+        fs::write(dir.join("query/supp_se_hdr_ver"), "c000000000000000\n").unwrap();
+        fs::write(dir.join("query/supp_secret_types"), "3600000000000000\n").unwrap();
+        fs::write(dir.join("query/supp_se_hdr_pcf"), "0000000030000000\n").unwrap();
+        fs::write(dir.join("query/max_cpus"), "248\n").unwrap();
+
+        let q = UvQuery::read_from(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(q.se_mode, Some(SeMode::Guest));
+        assert_eq!(q.se_hdr_versions(), [0x100, 0x200]);
+        assert_eq!(q.supports_se_hdr_version(0x200), Some(true));
+        assert_eq!(q.supports_se_hdr_version(0x300), Some(false));
+        assert_eq!(q.supports_att_req_version(0x100), None);
+        assert_eq!(q.supports_se_hdr_pcf(0x10000000), Some(true));
+        assert_eq!(q.supports_se_hdr_pcf(0x1), Some(false));
+        assert_eq!(
+            q.secret_types(),
+            [
+                ListableSecretType::Association,
+                ListableSecretType::Retrievable(RetrievableSecret::PlainText),
+                ListableSecretType::Retrievable(RetrievableSecret::Aes(AesSizes::Bits192)),
+                ListableSecretType::Retrievable(RetrievableSecret::Aes(AesSizes::Bits256)),
+            ]
+        );
+        assert_eq!(
+            q.supports_secret_type(ListableSecretType::Association),
+            Some(true)
+        );
+        assert_eq!(q.limits.max_cpus, Some(248));
+        assert_eq!(q.limits.max_guests, None);
+        assert!(q.facilities.is_none());
+
+        assert!(UvQuery::read_from(dir).is_err());
+    }
+}
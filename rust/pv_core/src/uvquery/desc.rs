@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2024
+
+//! Descriptions of the bits reported by the Ultravisor query attributes
+//!
+//! Bits are numbered from the most significant bit (bit 0) of the mask as
+//! in the Ultravisor documentation. Bits without an entry are reserved.
+
+/// Meaning of a bit in an Ultravisor query bitmask
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitDesc {
+    /// Bit with a public description
+    Desc(&'static str),
+    /// Confidential bit which is reported as reserved
+    Confidential,
+}
+
+/// Look up the meaning of `bit` in `table`.
+///
+/// Returns `None` for reserved bits.
+pub fn describe(table: &[(usize, BitDesc)], bit: usize) -> Option<BitDesc> {
+    table.iter().find(|(b, _)| *b == bit).map(|(_, d)| *d)
+}
+
+/// Installed Ultravisor calls (`facilities`)
+pub const FACILITIES: &[(usize, BitDesc)] = &[
+    (0, BitDesc::Desc("Query Ultravisor Information")),
+    (1, BitDesc::Desc("Initialize Ultravisor")),
+    (2, BitDesc::Desc("Create Secure Configuration")),
+    (3, BitDesc::Desc("Destroy Secure Configuration")),
+    (4, BitDesc::Desc("Create Secure CPU")),
+    (5, BitDesc::Desc("Destroy Secure CPU")),
+    (6, BitDesc::Desc("Convert to Secure Storage")),
+    (7, BitDesc::Desc("Convert from Secure Storage")),
+    (8, BitDesc::Desc("Set Shared Access")),
+    (9, BitDesc::Desc("Remove Shared Access")),
+    (10, BitDesc::Confidential),
+    (11, BitDesc::Desc("Set Secure Parameters")),
+    (12, BitDesc::Desc("Destroy Secure Storage")),
+    (13, BitDesc::Desc("Unpack Image")),
+    (14, BitDesc::Desc("Verify Image")),
+    (15, BitDesc::Desc("Perform CPU Reset")),
+    (16, BitDesc::Desc("Perform Initial CPU Reset")),
+    (17, BitDesc::Desc("Set CPU State")),
+    (18, BitDesc::Desc("Prepare for Reset")),
+    (19, BitDesc::Desc("Perform CPU Clear Reset")),
+    (20, BitDesc::Desc("Unshare All")),
+    (21, BitDesc::Desc("Pin Shared Storage")),
+    (22, BitDesc::Desc("Unpin Shared Storage")),
+    (23, BitDesc::Desc("Destroy Secure Configuration Fast")),
+    (24, BitDesc::Desc("Initiate Configuration Dump")),
+    (25, BitDesc::Desc("Dump Configuration Storage State")),
+    (26, BitDesc::Desc("Dump CPU State")),
+    (27, BitDesc::Desc("Complete Configuration Dump")),
+    (28, BitDesc::Desc("Retrieve Attestation Measurement")),
+    (29, BitDesc::Desc("Add Secret")),
+    (30, BitDesc::Desc("List Secrets")),
+    (31, BitDesc::Desc("Lock Secrets")),
+    (32, BitDesc::Desc("Verify Large Frame")),
+    (33, BitDesc::Desc("Retrieve Secret")),
+];
+/// Ultravisor features (`feature_indications`)
+pub const FEATURE_INDICATIONS: &[(usize, BitDesc)] = &[
+    (0, BitDesc::Confidential),
+    (
+        1,
+        BitDesc::Desc("Adapter interrupt virtualization supported"),
+    ),
+    (2, BitDesc::Confidential),
+    (3, BitDesc::Confidential),
+    (4, BitDesc::Desc("AP passthrough supported")),
+    (5, BitDesc::Desc("AP interpretion passthrough supported")),
+];
+/// Plaintext control flags of the SE header (`supp_se_hdr_pcf`)
+pub const SE_HDR_PCF: &[(usize, BitDesc)] = &[
+    (34, BitDesc::Desc("Dumping of the configuration is allowed")),
+    (35, BitDesc::Desc("The unpack image UVC does not decrypt the content of the specified 4K-byte block of storage. The page-list digest, the address-list digest, and the tweak-list digest are still verified.")),
+    (56, BitDesc::Desc("PCKMO encrypt-DEA/TDEA-key functions allowed")),
+    (57, BitDesc::Desc("PCKMO encrypt-AES-key functions allowed")),
+    (58, BitDesc::Desc("PCKMO encrypt-ECC-key functions allowed")),
+    (62, BitDesc::Desc("Temporary backup-host-key use allowed")),
+];
+/// Plaintext attestation flags (`supp_att_pflags`)
+pub const ATT_PFLAGS: &[(usize, BitDesc)] = &[
+    (1, BitDesc::Desc("An optional nonce is located in the attestation request")),
+    (2, BitDesc::Desc("The 32-bytexSHA256 hash of the public host key corresponding to the private key used to unseal the SE header to start this secure configuration is added to the additional data area to be measured.")),
+    (3, BitDesc::Desc("The 32-bytexSHA256 hash of the public host key corresponding to the private key used to unseal this Attestation request header to start this secure configuration is added to the additional data area to be measured.")),
+    (4, BitDesc::Desc("Add-secret Request Stream Flag (ARSF) - a SHA512 hash of the the list of add-secret tags of the add secret requests of sucessful requests (in order of execution) followed by a byte indicating whether the store is locked.")),
+    (5, BitDesc::Desc("Firmware Components Flag (FWCF): the 320-byte measurement corresponding to attestation of firmware components is added to the additional data to be measured.")),
+];
+/// Secret types (`supp_secret_types`)
+pub const SECRET_TYPES: &[(usize, BitDesc)] = &[
+    (1, BitDesc::Desc("Meta")),
+    (2, BitDesc::Desc("AP-association")),
+    (3, BitDesc::Desc("Plaintext")),
+    (4, BitDesc::Desc("AES 128")),
+    (5, BitDesc::Desc("AES 192")),
+    (6, BitDesc::Desc("AES 256")),
+    (7, BitDesc::Desc("AES 128 XTS")),
+    (8, BitDesc::Desc("AES 256 XTS")),
+    (9, BitDesc::Desc("HMAC SHA 256")),
+    (10, BitDesc::Desc("HMAC SHA 512")),
+    (17, BitDesc::Desc("ECDSA P256 private key")),
+    (18, BitDesc::Desc("ECDSA P384 private key")),
+    (19, BitDesc::Desc("ECDSA P521 private key")),
+    (20, BitDesc::Desc("EdDSA Ed25529 private key")),
+    (21, BitDesc::Desc("EdDSA Ed448 private key")),
+    (22, BitDesc::Desc("Update-CCK")),
+];
+/// Plaintext add-secret flags (`supp_add_secret_pcf`)
+pub const ADD_SECRET_PCF: &[(usize, BitDesc)] = &[(0, BitDesc::Desc("Disable dumping"))];
@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2024
+
+use super::{read_attr, UvQuery};
+use crate::{
+    utils::sysfs_path,
+    uv::{UvDevice, UvDeviceInfo},
+    Error, FileAccessErrorType, Result,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// Raw uvdevice information of a [`UvSnapshot`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct UvDeviceSnapshot {
+    supp_uvio_cmds: u64,
+    supp_uv_cmds: Option<u64>,
+}
+
+/// Snapshot of the Ultravisor capabilities of a system
+///
+/// Contains the raw content of all attributes of `/sys/firmware/uv` and
+/// `/sys/firmware/uv/query` and the [`UvDeviceInfo`] if a uvdevice is
+/// available. The snapshot can be serialized, e.g. to inspect the
+/// capabilities of a system on another machine.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UvSnapshot {
+    version: u32,
+    attributes: BTreeMap<String, String>,
+    uvdevice: Option<UvDeviceSnapshot>,
+}
+
+impl UvSnapshot {
+    /// Current version of the snapshot format
+    pub const VERSION: u32 = 1;
+
+    /// Capture the Ultravisor capabilities of the running system.
+    ///
+    /// The sysfs root can be relocated with [`crate::misc::set_sysfs_root`].
+    /// The uvdevice information is omitted if the uvdevice is not available.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `/sys/firmware/uv` does not
+    /// exist or cannot be read.
+    pub fn capture() -> Result<Self> {
+        let info = UvDevice::open().and_then(|uv| UvDeviceInfo::get(&uv)).ok();
+        Self::capture_from(sysfs_path(UvQuery::SYS_UV_DIR), info.as_ref())
+    }
+
+    /// Capture the Ultravisor attributes from `dir`, which has the layout of
+    /// `/sys/firmware/uv`, and the given uvdevice information.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `dir` does not exist or cannot
+    /// be read.
+    pub fn capture_from<P: AsRef<Path>>(dir: P, info: Option<&UvDeviceInfo>) -> Result<Self> {
+        let dir = dir.as_ref();
+        let mut attributes = BTreeMap::new();
+        for sub in ["", "query"] {
+            let path = dir.join(sub);
+            let entries = match fs::read_dir(&path) {
+                Ok(entries) => entries,
+                // older systems do not provide the query directory
+                Err(e) if !sub.is_empty() && e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(source) => {
+                    return Err(Error::FileAccess {
+                        ty: FileAccessErrorType::Open,
+                        path,
+                        source,
+                    })
+                }
+            };
+            for entry in entries {
+                let entry = entry?;
+                if !entry.file_type()?.is_file() {
+                    continue;
+                }
+                let name = match sub {
+                    "" => entry.file_name().to_string_lossy().into_owned(),
+                    _ => format!("{sub}/{}", entry.file_name().to_string_lossy()),
+                };
+                // some attributes may not be readable, e.g. without privileges
+                if let Some(content) = read_attr(dir, &name).ok().flatten() {
+                    attributes.insert(name, content);
+                }
+            }
+        }
+
+        Ok(Self {
+            version: Self::VERSION,
+            attributes,
+            uvdevice: info.map(|info| UvDeviceSnapshot {
+                supp_uvio_cmds: info.supp_uvio_cmds(),
+                supp_uv_cmds: info.supp_uv_cmds(),
+            }),
+        })
+    }
+
+    /// Check that this snapshot has a version this library understands.
+    ///
+    /// Use this after deserializing a snapshot.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the version is not supported.
+    pub fn check_version(&self) -> Result<()> {
+        match self.version {
+            Self::VERSION => Ok(()),
+            v => Err(Error::UnsupportedSnapshotVersion(v)),
+        }
+    }
+
+    /// Interpret the captured Ultravisor attributes.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the version of the snapshot is
+    /// not supported or an attribute cannot be parsed.
+    pub fn query(&self) -> Result<UvQuery> {
+        self.check_version()?;
+        UvQuery::from_attrs(&|name| Ok(self.attributes.get(name).cloned()))
+    }
+
+    /// The captured uvdevice information, if any.
+    pub fn uvdevice_info(&self) -> Option<UvDeviceInfo> {
+        self.uvdevice
+            .map(|d| UvDeviceInfo::from_raw(d.supp_uvio_cmds, d.supp_uv_cmds))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::uv::SeMode;
+
+    #[test]
+    fn capture_from() {
+        let dir = std::env::temp_dir().join(format!("pv_core_snapshot_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("query")).unwrap();
+        fs::write(dir.join("prot_virt_host"), "1\n").unwrap();
+        fs::write(dir.join("query/supp_se_hdr_ver"), "c000000000000000\n").unwrap();
+        fs::write(dir.join("query/max_guests"), "1024\n").unwrap();
+
+        let info = UvDeviceInfo::from_raw(0x1f, Some(0x1e));
+        let snap = UvSnapshot::capture_from(&dir, Some(&info)).unwrap();
+        let exp = UvQuery::read_from(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(snap.attributes.len(), 3);
+        assert_eq!(snap.attributes["query/max_guests"], "1024\n");
+        let query = snap.query().unwrap();
+        assert_eq!(query, exp);
+        assert_eq!(query.se_mode, Some(SeMode::Host));
+        assert_eq!(query.se_hdr_versions(), [0x100, 0x200]);
+        assert_eq!(snap.uvdevice_info(), Some(info));
+
+        let mut snap = snap;
+        snap.version = 2;
+        assert!(matches!(
+            snap.query(),
+            Err(Error::UnsupportedSnapshotVersion(2))
+        ));
+    }
+}
@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2024
+
+//! Test support for synthetic sysfs trees
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+const POLL_MS: u64 = 100;
+
+/// Emulates the kernel side of the AP queue se_bind and se_associate attributes.
+///
+/// Within a synthetic sysfs tree no kernel processes the requests written to
+/// these attributes, the written value simply stays in the file. A background
+/// thread replaces such a raw request with the state the kernel would report
+/// after the request completed. As with the kernel, the completion is
+/// asynchronous. An unbind also resets the association state.
+///
+/// The emulation stops when the `FakeApKernel` is dropped.
+pub struct FakeApKernel {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl FakeApKernel {
+    /// Start the emulation for the AP queues within `root/devices/ap`.
+    pub fn start(root: &Path) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let devices_ap = root.join("devices/ap");
+        let thread = {
+            let stop = stop.clone();
+            thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    for queue in queue_dirs(&devices_ap) {
+                        complete_requests(&queue);
+                    }
+                    thread::sleep(Duration::from_millis(POLL_MS));
+                }
+            })
+        };
+        Self {
+            stop,
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for FakeApKernel {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            thread.join().unwrap();
+        }
+    }
+}
+
+fn queue_dirs(devices_ap: &Path) -> Vec<PathBuf> {
+    let subdirs = |dir: &Path| -> Vec<PathBuf> {
+        fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.is_dir())
+            .collect()
+    };
+    subdirs(devices_ap)
+        .iter()
+        .flat_map(|card| subdirs(card))
+        .collect()
+}
+
+fn complete_requests(queue: &Path) {
+    let bind = queue.join("se_bind");
+    let assoc = queue.join("se_associate");
+    match read_trimmed(&bind).as_deref() {
+        Some("1") => replace(&bind, "bound"),
+        Some("0") => {
+            replace(&bind, "unbound");
+            if read_trimmed(&assoc).is_some_and(|a| a != "-") {
+                replace(&assoc, "unassociated");
+            }
+        }
+        _ => {}
+    }
+    if let Some(idx) = read_trimmed(&assoc).and_then(|a| a.parse::<u16>().ok()) {
+        replace(&assoc, &format!("associated {idx}"));
+    }
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+/// Replace the file content atomically, so that readers never see a partial state.
+fn replace(path: &Path, content: &str) {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, content).unwrap();
+    fs::rename(&tmp, path).unwrap();
+}
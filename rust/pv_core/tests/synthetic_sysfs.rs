@@ -0,0 +1,104 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2024
+
+#![allow(missing_docs)]
+mod common;
+
+use std::{fs, path::Path};
+
+use common::FakeApKernel;
+
+use s390_pv_core::{
+    ap::{apqn_info, apqn_mode, assoc_state, bind_state, Apqn},
+    misc::{pv_guest_bit_set, set_sysfs_root, sysfs_path},
+    Error,
+};
+
+fn write(root: &Path, path: &str, content: &str) {
+    let path = root.join(path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+fn read(root: &Path, path: &str) -> String {
+    fs::read_to_string(root.join(path)).unwrap()
+}
+
+// The sysfs root is process global, so all steps are done in one test.
+#[test]
+fn synthetic_ap_bus() {
+    let root = std::env::temp_dir().join(format!("pv_core_sysfs_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    write(&root, "firmware/uv/prot_virt_guest", "1\n");
+    write(&root, "devices/ap/card0b/type", "CEX8P\n");
+    write(&root, "devices/ap/card0b/serialnr", "93AADFK719460083\n");
+    write(&root, "devices/ap/card0b/0b.0005/online", "1\n");
+    write(&root, "devices/ap/card0b/0b.0005/se_bind", "unbound\n");
+    write(
+        &root,
+        "devices/ap/card0b/0b.0005/se_associate",
+        "unassociated\n",
+    );
+    write(
+        &root,
+        "devices/ap/card0b/0b.0005/mkvps",
+        "WK CUR: valid 0xdb3c3b3c3f097dd55ec7eb0e7fdbcb93\nWK NEW: empty -\n",
+    );
+    write(&root, "devices/ap/card0c/type", "CEX8C\n");
+    write(&root, "devices/ap/card0c/0c.0005/online", "1\n");
+    write(&root, "devices/ap/card0c/0c.0005/se_bind", "unbound\n");
+
+    set_sysfs_root(&root).unwrap();
+    set_sysfs_root(format!("{}/", root.display())).unwrap();
+    assert!(set_sysfs_root("/").is_err());
+    assert_eq!(
+        sysfs_path("/sys/devices/ap"),
+        format!("{}/devices/ap", root.display())
+    );
+    assert!(pv_guest_bit_set());
+    let kernel = FakeApKernel::start(&root);
+
+    // the APQN is temporarily bound to read the info
+    let apqn = Apqn::try_from("0b.0005").unwrap();
+    assert_eq!(apqn.mode, apqn_mode::Ep11);
+    match apqn.info {
+        Some(apqn_info::Ep11(ref info)) => {
+            assert_eq!(info.serialnr, "93AADFK719460083");
+            assert_eq!(info.mkvp, "db3c3b3c3f097dd55ec7eb0e7fdbcb93");
+        }
+        _ => panic!("No EP11 info"),
+    }
+    assert_eq!(apqn.bind_state().unwrap(), bind_state::Unbound);
+
+    // CCA cards hang the info gathering within a SE guest
+    assert!(matches!(
+        Apqn::try_from("0c.0005"),
+        Err(Error::CcaSeIncompatible(0x0c))
+    ));
+    assert_eq!(
+        read(&root, "devices/ap/card0c/0c.0005/se_bind"),
+        "unbound\n"
+    );
+
+    apqn.set_bind_state(bind_state::Bound).unwrap();
+    assert_eq!(read(&root, "devices/ap/card0b/0b.0005/se_bind"), "bound");
+    apqn.set_associate_state(assoc_state::Associated(7))
+        .unwrap();
+    assert_eq!(
+        read(&root, "devices/ap/card0b/0b.0005/se_associate"),
+        "associated 7"
+    );
+
+    // an unbind also resets the association
+    apqn.set_associate_state(assoc_state::Unassociated).unwrap();
+    assert_eq!(apqn.bind_state().unwrap(), bind_state::Unbound);
+
+    // without the kernel a written request stays pending, reading does not change the tree
+    drop(kernel);
+    write(&root, "devices/ap/card0b/0b.0005/se_bind", "1");
+    assert!(apqn.bind_state().is_err());
+    assert_eq!(read(&root, "devices/ap/card0b/0b.0005/se_bind"), "1");
+
+    fs::remove_dir_all(&root).unwrap();
+}
@@ -3,7 +3,7 @@
 // Copyright IBM Corp. 2024
 // it under the terms of the MIT license. See LICENSE for details.
 
-use clap::{CommandFactory, ValueEnum};
+use clap::CommandFactory;
 use clap_complete::{generate_to, Shell};
 use std::env;
 use std::io::Error;
@@ -7,7 +7,7 @@
 
 use crate::helper::*;
 use pv_core::ap::*;
-use pv_core::misc::read_file_string;
+use pv_core::misc::{read_file_string, sysfs_path};
 use std::path::Path;
 use std::slice::Iter;
 use std::thread;
@@ -25,10 +25,9 @@ const SYS_BUS_AP_BINDINGS_POLL_MS: u64 = 500;
 /// Check if AP bus support is available.
 /// Returns Result with Ok(()) or Err(failurestring).
 pub fn check_ap_bus_support() -> Result<(), String> {
-    if !Path::new(PATH_SYS_BUS_AP).is_dir() {
-        return Err(format!(
-            "AP bus support missing (path {PATH_SYS_BUS_AP} is invalid)."
-        ));
+    let path = sysfs_path(PATH_SYS_BUS_AP);
+    if !Path::new(&path).is_dir() {
+        return Err(format!("AP bus support missing (path {path} is invalid)."));
     }
     Ok(())
 }
@@ -38,8 +37,8 @@ pub fn check_ap_bus_support() -> Result<(), String> {
 /// When APSB support is available returns Result
 /// with Ok(()) or otherwise Err(failurestring).
 pub fn ap_bus_has_apsb_support() -> Result<(), String> {
-    let features =
-        read_file_string(PATH_SYS_BUS_AP_FEATURES, "AP bus features").map_err(|e| e.to_string())?;
+    let features = read_file_string(sysfs_path(PATH_SYS_BUS_AP_FEATURES), "AP bus features")
+        .map_err(|e| e.to_string())?;
     match features.find("APSB") {
         Some(_) => Ok(()),
         None => Err("Missing AP bus feature APSB (SE AP pass-through not enabled ?).".to_string()),
@@ -62,7 +61,7 @@ pub fn ap_bus_has_apsb_support() -> Result<(), String> {
 pub fn wait_for_ap_bus_bindings_complete() -> bool {
     let mut counter = 0;
     loop {
-        match read_file_string(PATH_SYS_BUS_AP_BINDINGS, "AP bus bindings") {
+        match read_file_string(sysfs_path(PATH_SYS_BUS_AP_BINDINGS), "AP bus bindings") {
             Ok(s) => {
                 if s.contains("complete") {
                     return true;
@@ -122,19 +121,16 @@ impl ApqnList {
     /// On failure None is returned.
     pub fn gather_apqns() -> Option<Self> {
         let mut apqns: Vec<Apqn> = Vec::new();
-        let card_dirs =
-            match sysfs_get_list_of_subdirs_matching_regex(PATH_SYS_DEVICES_AP, RE_CARD_DIR) {
-                Ok(r) => r,
-                Err(err) => {
-                    eprintln!(
-                        "Failure reading AP devices {} ({:?}).",
-                        PATH_SYS_DEVICES_AP, err
-                    );
-                    return None;
-                }
-            };
+        let devices_ap = sysfs_path(PATH_SYS_DEVICES_AP);
+        let card_dirs = match sysfs_get_list_of_subdirs_matching_regex(&devices_ap, RE_CARD_DIR) {
+            Ok(r) => r,
+            Err(err) => {
+                eprintln!("Failure reading AP devices {} ({:?}).", devices_ap, err);
+                return None;
+            }
+        };
         for dir in card_dirs {
-            let path = format!("{PATH_SYS_DEVICES_AP}/{dir}");
+            let path = format!("{devices_ap}/{dir}");
             let queue_dirs = match sysfs_get_list_of_subdirs_matching_regex(&path, RE_QUEUE_DIR) {
                 Ok(r) => r,
                 Err(err) => {
@@ -179,7 +175,7 @@ impl ApqnList {
     /// Sort this Apqnlist by card generation:
     /// newest generation first, older generations last.
     pub fn sort_by_gen(&mut self) {
-        self.0.sort_unstable_by(|a, b| b.gen.cmp(&a.gen));
+        self.0.sort_unstable_by_key(|a| std::cmp::Reverse(a.gen));
     }
 
     /// Check MK restriction
@@ -191,18 +187,13 @@ impl ApqnList {
     pub fn check_mk_restriction(&self) -> bool {
         for a1 in self.0.iter() {
             for a2 in self.0.iter() {
-                if a1.card == a2.card
-                    && a1.domain < a2.domain
-                    && a1.mode == apqn_mode::Ep11
-                    && a1.info.is_some()
-                    && a2.info.is_some()
-                {
-                    let i1 = match a1.info.as_ref().unwrap() {
-                        apqn_info::Ep11(i) => i,
+                if a1.card == a2.card && a1.domain < a2.domain && a1.mode == apqn_mode::Ep11 {
+                    let i1 = match &a1.info {
+                        Some(apqn_info::Ep11(i)) => i,
                         _ => continue,
                     };
-                    let i2 = match a2.info.as_ref().unwrap() {
-                        apqn_info::Ep11(i) => i,
+                    let i2 = match &a2.info {
+                        Some(apqn_info::Ep11(i)) => i,
                         _ => continue,
                     };
                     if i1.mkvp.is_empty() || i2.mkvp.is_empty() {
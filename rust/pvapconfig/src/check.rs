@@ -0,0 +1,347 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2024
+//
+//! Offline check of a pvapconfig configuration file
+//
+
+use crate::config::{self, ApConfigEntry, ApConfigList};
+use openssl::sha::sha256;
+use pv_core::misc::encode_hex;
+use serde_yaml::Value;
+use std::fmt;
+use std::fs;
+
+/// Severity of a check finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The configuration can not be applied as intended
+    Error,
+    /// The configuration is questionable
+    Warning,
+}
+
+/// One finding of the config check
+#[derive(Debug, PartialEq, Eq)]
+pub struct Finding {
+    pub severity: Severity,
+    pub msg: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.severity {
+            Severity::Error => write!(f, "Error: {}", self.msg),
+            Severity::Warning => write!(f, "Warning: {}", self.msg),
+        }
+    }
+}
+
+/// Name and id of an association secret as written by 'pvsecret create'
+#[derive(Debug, PartialEq, Eq)]
+pub struct SecretInfo {
+    pub name: String,
+    pub id: String, // 64 hex lowercase characters
+    pub file: String,
+}
+
+fn error(msg: String) -> Finding {
+    Finding {
+        severity: Severity::Error,
+        msg,
+    }
+}
+
+fn warning(msg: String) -> Finding {
+    Finding {
+        severity: Severity::Warning,
+        msg,
+    }
+}
+
+fn entry_str(ci: usize, apc: &ApConfigEntry) -> String {
+    if !apc.name.is_empty() {
+        format!("#{} '{}'", ci + 1, apc.name)
+    } else {
+        format!("#{}", ci + 1)
+    }
+}
+
+/// Parse the yaml output of 'pvsecret create association'.
+///
+/// Returns None if the content is not an association secret.
+fn parse_secret_info(content: &str, file: &str) -> Option<SecretInfo> {
+    let value: Value = serde_yaml::from_str(content).ok()?;
+    // serde_yaml writes the secret type as yaml tag: !Association
+    let map = match &value {
+        Value::Tagged(t) if t.tag == "Association" => &t.value,
+        Value::Mapping(m) => m.get("Association")?,
+        _ => return None,
+    };
+    let name = map.get("name")?.as_str()?.to_string();
+    let id = map.get("id")?.as_str()?.trim().to_lowercase();
+    let id = id.strip_prefix("0x").unwrap_or(&id).to_string();
+    Some(SecretInfo {
+        name,
+        id,
+        file: file.to_string(),
+    })
+}
+
+/// Read all association secret yaml files from a directory.
+fn read_secret_infos(dir: &str, findings: &mut Vec<Finding>) -> Result<Vec<SecretInfo>, String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|err| format!("Failure reading secrets directory {dir}: {err:?}"))?;
+    let mut files: Vec<_> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            p.extension()
+                .is_some_and(|ext| ext == "yaml" || ext == "yml")
+        })
+        .collect();
+    files.sort();
+    let mut secrets = Vec::new();
+    for path in files {
+        let file = path.display().to_string();
+        let content = match fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(err) => {
+                findings.push(warning(format!("Failure reading {file}: {err:?}")));
+                continue;
+            }
+        };
+        match parse_secret_info(&content, &file) {
+            Some(secret) => secrets.push(secret),
+            None => findings.push(warning(format!(
+                "Ignoring {file} as it does not describe an association secret."
+            ))),
+        }
+    }
+    Ok(secrets)
+}
+
+/// Check the validated AP config entries for duplicates and conflicts.
+fn check_conflicts(entries: &[(usize, ApConfigEntry)], findings: &mut Vec<Finding>) {
+    for (i, (ci1, apc1)) in entries.iter().enumerate() {
+        for (ci2, apc2) in entries.iter().skip(i + 1) {
+            let is_accel = |apc: &ApConfigEntry| apc.mode == config::STR_MODE_ACCEL;
+            if !is_accel(apc1) && !is_accel(apc2) && apc1.secretid == apc2.secretid {
+                findings.push(error(format!(
+                    "AP config entries {} and {} use the same secret id {}, but an association secret can only be associated with one APQN.",
+                    entry_str(*ci1, apc1),
+                    entry_str(*ci2, apc2),
+                    apc1.secretid
+                )));
+            } else if is_accel(apc1) && is_accel(apc2) && apc1.mingen == apc2.mingen {
+                findings.push(warning(format!(
+                    "AP config entries {} and {} overlap: both are satisfied by the same accelerator APQNs.",
+                    entry_str(*ci1, apc1),
+                    entry_str(*ci2, apc2),
+                )));
+            }
+        }
+    }
+}
+
+/// Cross-check the secret ids of the AP config entries with the secrets.
+fn check_secrets(
+    entries: &[(usize, ApConfigEntry)],
+    secrets: &[SecretInfo],
+    findings: &mut Vec<Finding>,
+) {
+    for secret in secrets {
+        if encode_hex(sha256(secret.name.as_bytes())) != secret.id {
+            findings.push(warning(format!(
+                "Secret id in {} is not the sha256 hash over the secret name '{}'.",
+                secret.file, secret.name
+            )));
+        }
+    }
+    for (ci, apc) in entries {
+        if apc.mode == config::STR_MODE_ACCEL {
+            continue;
+        }
+        match secrets.iter().find(|s| s.id == apc.secretid) {
+            None => findings.push(error(format!(
+                "AP config entry {}: no association secret with secret id {} found.",
+                entry_str(*ci, apc),
+                apc.secretid
+            ))),
+            Some(secret) if !apc.name.is_empty() && apc.name != secret.name => {
+                findings.push(error(format!(
+                    "AP config entry {}: name does not match the secret name '{}' from {}.",
+                    entry_str(*ci, apc),
+                    secret.name,
+                    secret.file
+                )))
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+/// Check the AP config file and optionally cross-check the secret ids
+/// against a directory of 'pvsecret create association' yaml files.
+///
+/// Returns the list of findings or an Err(errorstring) if the files
+/// could not be read at all.
+pub fn check_config(fname: &str, secrets_dir: Option<&str>) -> Result<Vec<Finding>, String> {
+    let mut findings = Vec::new();
+    let mut entries = Vec::new();
+    for (ci, mut apc) in ApConfigList::read_yaml_file(fname)?.into_iter().enumerate() {
+        let estr = entry_str(ci, &apc);
+        match apc.validate() {
+            Ok(()) => entries.push((ci, apc)),
+            Err(err) => findings.push(error(format!("AP config entry {estr}: {err}"))),
+        }
+    }
+    check_conflicts(&entries, &mut findings);
+    if let Some(dir) = secrets_dir {
+        let secrets = read_secret_infos(dir, &mut findings)?;
+        check_secrets(&entries, &secrets, &mut findings);
+    }
+    Ok(findings)
+}
+
+/// Print the findings and a summary. Returns true if there is no error.
+pub fn report(fname: &str, findings: &[Finding]) -> bool {
+    for finding in findings {
+        println!("{finding}");
+    }
+    let errors = findings
+        .iter()
+        .filter(|f| f.severity == Severity::Error)
+        .count();
+    println!(
+        "AP config file '{}': {} error(s), {} warning(s).",
+        fname,
+        errors,
+        findings.len() - errors
+    );
+    errors == 0
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn check(yaml: &str) -> Vec<Finding> {
+        let mut entries = Vec::new();
+        let apcs: Vec<ApConfigEntry> = serde_yaml::from_str(yaml).unwrap();
+        for (ci, mut apc) in apcs.into_iter().enumerate() {
+            apc.validate().unwrap();
+            entries.push((ci, apc));
+        }
+        let mut findings = Vec::new();
+        check_conflicts(&entries, &mut findings);
+        findings
+    }
+
+    #[test]
+    fn test_check_conflicts() {
+        let findings = check(
+            "- name: apqn 1
+  mode: EP11
+  mkvp: 0xdb3c3b3c3f097dd55ec7eb0e7fdbcb93
+- name: apqn 1
+  mode: EP11
+  mkvp: 0x00112233445566778899aabbccddeeff
+- mode: Accel
+- mode: Accel
+  mingen: cex8
+- mode: Accel\n",
+        );
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].severity, Severity::Error);
+        assert!(findings[0].msg.contains("#1 'apqn 1' and #2 'apqn 1'"));
+        assert_eq!(findings[1].severity, Severity::Warning);
+        assert!(findings[1].msg.contains("#3 and #5"));
+
+        let findings = check(
+            "- name: apqn 1
+  mode: EP11
+  mkvp: 0xdb3c3b3c3f097dd55ec7eb0e7fdbcb93
+- name: apqn 2
+  mode: EP11
+  mkvp: 0xdb3c3b3c3f097dd55ec7eb0e7fdbcb93\n",
+        );
+        assert!(findings.is_empty());
+    }
+
+    #[test]
+    fn test_check_config_cca() {
+        let fname =
+            std::env::temp_dir().join(format!("check-config-test-{}.yaml", rand::random::<u32>()));
+        std::fs::write(
+            &fname,
+            "- name: apqn 1
+  mode: CCA
+- mode: Accel\n",
+        )
+        .unwrap();
+        let findings = check_config(fname.to_str().unwrap(), None);
+        std::fs::remove_file(&fname).ok();
+        let findings = findings.unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].severity, Severity::Error);
+        assert!(findings[0]
+            .msg
+            .starts_with("AP config entry #1 'apqn 1': Mode 'cca' is not supported"));
+    }
+
+    #[test]
+    fn test_parse_secret_info() {
+        let yaml = "!Association
+name: apqn 1
+id: 0x21A9F4E9B5CB9CAA4F6785A0C0B1A9E3B8B0C47D5EBA47E8A3C2B86DB51C8A63\n";
+        let secret = parse_secret_info(yaml, "apqn_1.yaml").unwrap();
+        assert_eq!(secret.name, "apqn 1");
+        assert_eq!(
+            secret.id,
+            "21a9f4e9b5cb9caa4f6785a0c0b1a9e3b8b0c47d5eba47e8a3c2b86db51c8a63"
+        );
+        assert!(parse_secret_info("!Retrievable\nname: a\nid: 0x00\n", "r.yaml").is_none());
+        assert!(parse_secret_info("cuid: 0x00\n", "c.yaml").is_none());
+    }
+
+    #[test]
+    fn test_check_secrets() {
+        let name = "apqn 1";
+        let id = encode_hex(sha256(name.as_bytes()));
+        let secrets = vec![SecretInfo {
+            name: name.to_string(),
+            id: id.clone(),
+            file: "apqn_1.yaml".to_string(),
+        }];
+        let mut apcs = vec![
+            ApConfigEntry {
+                name: name.to_string(),
+                mode: "ep11".to_string(),
+                mkvp: "db3c3b3c3f097dd55ec7eb0e7fdbcb93".to_string(),
+                ..Default::default()
+            },
+            ApConfigEntry {
+                mode: "ep11".to_string(),
+                mkvp: "db3c3b3c3f097dd55ec7eb0e7fdbcb93".to_string(),
+                secretid: "ff".repeat(32),
+                ..Default::default()
+            },
+        ];
+        let entries: Vec<_> = apcs
+            .drain(..)
+            .map(|mut apc| {
+                apc.validate().unwrap();
+                apc
+            })
+            .enumerate()
+            .collect();
+        let mut findings = Vec::new();
+        check_secrets(&entries, &secrets, &mut findings);
+        assert_eq!(findings.len(), 1);
+        assert!(findings[0]
+            .msg
+            .starts_with("AP config entry #2: no association secret"));
+    }
+}
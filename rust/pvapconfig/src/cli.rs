@@ -4,8 +4,9 @@
 //
 //
 
-use clap::Parser;
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use lazy_static::lazy_static;
+use pv_core::uv::UvBackendSpec;
 
 /// The default pvapconfig config file
 pub const PATH_DEFAULT_CONFIG_FILE: &str = "/etc/pvapconfig.yaml";
@@ -14,7 +15,7 @@ pub const PATH_DEFAULT_CONFIG_FILE: &str = "/etc/pvapconfig.yaml";
 #[derive(Parser, Clone)]
 pub struct Cli {
     /// Provide a custom config file (overwrites default /etc/pvapconfig.yaml).
-    #[arg(short, long, value_name = "FILE")]
+    #[arg(short, long, value_name = "FILE", global = true)]
     pub config: Option<String>,
 
     /// Dry run: display the actions but don't actually perform them on the APQNs.
@@ -33,13 +34,109 @@ pub struct Cli {
     #[arg(long, conflicts_with_all = ["config", "strict"])]
     pub unbind: bool,
 
+    /// Keep running and re-apply the AP configuration on changes.
+    ///
+    /// pvapconfig stays in the foreground, listens to AP bus uevents and
+    /// periodically re-lists the UV secrets. The AP configuration is applied
+    /// at startup, whenever APQNs appear, vanish or change and whenever the
+    /// list of UV secrets changes. Each action is logged to standard output.
+    /// This mode is intended to run as a systemd service.
+    #[arg(long, conflicts_with = "unbind")]
+    pub watch: bool,
+
+    /// Interval in seconds to re-list the UV secrets in watch mode.
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = 60,
+        requires = "watch",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub interval: u64,
+
+    /// Use DIR as root of the sysfs tree instead of /sys.
+    ///
+    /// Intended for testing against a synthetic sysfs tree. The bind and
+    /// associate requests written by pvapconfig are not processed, the test
+    /// harness needs to complete them as the kernel would do.
+    #[arg(long, value_name = "DIR", global = true)]
+    pub sysfs_root: Option<String>,
+
+    /// Read the list of UV secrets from FILE instead of querying the UV.
+    ///
+    /// FILE contains the binary secret list as written by
+    /// 'pvsecret list --format bin'. Intended for testing.
+    #[arg(long, value_name = "FILE", global = true)]
+    pub secret_list: Option<String>,
+
+    /// Use SPEC as backend for Ultravisor calls instead of /dev/uv.
+    ///
+    /// SPEC is one of 'device:PATH', 'record:DIR', 'replay:DIR', or
+    /// 'remote:SOCKET'. Hidden in user documentation.
+    #[arg(long, value_name = "SPEC", global = true, hide = true)]
+    pub uv_backend: Option<UvBackendSpec>,
+
     /// Provide more detailed output.
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     pub verbose: bool,
 
     /// Print version information and exit.
     #[arg(short = 'V', long)]
     pub version: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Subcommands of pvapconfig
+#[derive(Subcommand, Clone)]
+pub enum Command {
+    /// Report the AP configuration state without changing anything.
+    ///
+    /// For each AP config entry the APQN satisfying this entry is shown
+    /// together with its bind and association state. APQNs which are bound
+    /// but do not satisfy any AP config entry are listed separately.
+    Status(StatusArgs),
+
+    /// Check an AP config file without applying it.
+    ///
+    /// All AP config entries are validated and duplicate or conflicting
+    /// entries are reported. Neither AP bus nor UV are accessed, so the
+    /// check can be run on any system, for example before the AP config
+    /// file is deployed into an SE guest.
+    CheckConfig(CheckConfigArgs),
+}
+
+/// Arguments for the status subcommand
+#[derive(Args, Clone)]
+pub struct StatusArgs {
+    /// Output format of the status report.
+    #[arg(long, value_enum, default_value_t = StatusFormat::Yaml)]
+    pub format: StatusFormat,
+}
+
+/// Arguments for the check-config subcommand
+#[derive(Args, Clone)]
+pub struct CheckConfigArgs {
+    /// The AP config file to check.
+    #[arg(value_name = "FILE")]
+    pub file: String,
+
+    /// Cross-check the secret ids with association secrets in DIR.
+    ///
+    /// DIR contains the yaml files written by 'pvsecret create association'.
+    /// Each EP11 AP config entry needs a matching association secret.
+    #[arg(long, value_name = "DIR")]
+    pub secrets_dir: Option<String>,
+}
+
+/// Output formats of the status report
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatusFormat {
+    /// YAML
+    Yaml,
+    /// JSON
+    Json,
 }
 
 lazy_static! {
@@ -64,4 +161,22 @@ impl Cli {
     pub fn strict(&self) -> bool {
         self.strict
     }
+
+    /// secret_list returns the file given with the secret-list option
+    /// or None if the UV is to be queried.
+    pub fn secret_list(&self) -> Option<&str> {
+        self.secret_list.as_deref()
+    }
+
+    /// uv_backend returns the backend given with the uv-backend option
+    /// or None if /dev/uv is to be used.
+    pub fn uv_backend(&self) -> Option<&UvBackendSpec> {
+        self.uv_backend.as_ref()
+    }
+
+    /// watch returns true if the watch option was given, otherwise
+    /// false is returned.
+    pub fn watch(&self) -> bool {
+        self.watch
+    }
 }
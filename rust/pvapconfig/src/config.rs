@@ -15,6 +15,7 @@ use std::slice::Iter;
 
 pub const STR_MODE_EP11: &str = "ep11";
 pub const STR_MODE_ACCEL: &str = "accel";
+pub const STR_MODE_CCA: &str = "cca";
 
 const RE_EP11_MKVP_32: &str = r"^(0x)?([[:xdigit:]]{32})$";
 const RE_EP11_MKVP_64: &str = r"^(0x)?([[:xdigit:]]{64})$";
@@ -129,7 +130,9 @@ impl ApConfigEntry {
         Ok(())
     }
 
-    fn validate(&mut self) -> Result<(), String> {
+    /// Validate and normalize this AP config entry.
+    /// Returns Err(errorstring) if the entry is not valid.
+    pub fn validate(&mut self) -> Result<(), String> {
         // trim name
         self.name = self.name.trim().to_string();
         // mode is always required
@@ -143,6 +146,15 @@ impl ApConfigEntry {
                 self.mode = mode;
                 self.validate_accel_entry()?;
             }
+            // Gathering the info of CCA APQNs hangs within a Secure Execution
+            // guest, so the AP bus code skips them there. A CCA entry could
+            // never be satisfied.
+            STR_MODE_CCA => {
+                return Err(format!(
+                    "Mode '{mode}' is not supported, CCA APQNs are not usable within a \
+                     Secure Execution guest."
+                ))
+            }
             _ => return Err(format!("Unknown or invalid mode '{}'.", mode)),
         }
         Ok(())
@@ -171,7 +183,8 @@ impl ApConfigList {
         self.0.is_empty()
     }
 
-    fn read_yaml_file(fname: &str) -> Result<Vec<ApConfigEntry>, String> {
+    /// Read the AP config entries from a yaml file without validation.
+    pub fn read_yaml_file(fname: &str) -> Result<Vec<ApConfigEntry>, String> {
         let file = match File::open(fname) {
             Ok(f) => f,
             Err(err) => {
@@ -264,14 +277,14 @@ mod tests {
   secretid: 0x0767668dd22f23fa675c4641e04bb4e991f443be4df13ce3896b8eeca59fcc10\n",
     ];
 
-    const BAD_CONFIGS: [&str; 12] = [
+    const BAD_CONFIGS: [&str; 13] = [
         "# mode missing
 - name: bad test 1
   mkvp: 0xdb3c3b3c3f097dd55ec7eb0e7fdbcb93
   secretid: 0x0767668dd22f23fa675c4641e04bb4e991f443be4df13ce3896b8eeca59fcc10\n",
         "# invalid mode
 - name: bad test 2
-  mode: CCA
+  mode: EP12
   mkvp: 0xdb3c3b3c3f097dd55ec7eb0e7fdbcb93
   secretid: 0x0767668dd22f23fa675c4641e04bb4e991f443be4df13ce3896b8eeca59fcc10\n",
         "# Accelerator with wrong CEX3
@@ -323,6 +336,10 @@ mod tests {
   mkvp: 0x00112233445566778899aabbccddeeff
   serialnr: AABBCCDDEEFFGGHH
   secretid: 0x2ca853f959fc5ce5f1888cb48dae39514a27bb66520ac85f6073a7f678d262c0\n",
+        "# CCA is not supported
+- name: bad test 13
+  mode: CCA
+  secretid: 0x0767668dd22f23fa675c4641e04bb4e991f443be4df13ce3896b8eeca59fcc10\n",
     ];
 
     const BAD_DESERIALIZE: [&str; 2] = [
@@ -368,6 +385,15 @@ mod tests {
         }
     }
     #[test]
+    fn test_cca_rejected() {
+        let mut apc = ApConfigEntry {
+            mode: String::from("Cca"),
+            ..Default::default()
+        };
+        let err = apc.validate().unwrap_err();
+        assert!(err.contains("Mode 'cca' is not supported"), "{err}");
+    }
+    #[test]
     fn test_invalid_deserizalize() {
         for yaml in BAD_DESERIALIZE {
             let f = write_yaml_config_to_temp_file(yaml).unwrap();
@@ -7,10 +7,13 @@
 //
 
 mod ap;
+mod check;
 mod cli;
 mod config;
 mod helper;
+mod status;
 mod uv;
+mod watch;
 
 use ap::ApqnList;
 use cli::ARGS;
@@ -18,8 +21,10 @@ use config::{ApConfigEntry, ApConfigList};
 use helper::{LockFile, PATH_PVAPCONFIG_LOCK};
 use pv_core::ap::{self as pvap, Apqn};
 use pv_core::misc::encode_hex;
-use pv_core::uv::{ListableSecretType, SecretList};
+use pv_core::uv::{ListableSecretType, SecretList, UvBackendSpec};
+use status::{ApqnState, StatusReport};
 use std::process::ExitCode;
+use std::time::Duration;
 use utils::print_version;
 
 /// Simple macro for
@@ -50,8 +55,8 @@ macro_rules! println_and_exit_failure {
 /// returns with exit failure.
 macro_rules! on_error_print_and_exit {
     ($r:expr) => {
-        if $r.is_err() {
-            eprintln!("{}", $r.unwrap_err());
+        if let Err(e) = &$r {
+            eprintln!("{}", e);
             return ExitCode::FAILURE;
         }
     };
@@ -64,47 +69,46 @@ fn main() -> ExitCode {
         return ExitCode::SUCCESS;
     }
 
+    // synthetic sysfs tree for testing
+    if let Some(root) = &cli::ARGS.sysfs_root {
+        let r = pv_core::misc::set_sysfs_root(root);
+        on_error_print_and_exit!(r);
+    }
+
+    // offline check of an AP config file, needs neither AP bus nor UV
+    if let Some(cli::Command::CheckConfig(args)) = &cli::ARGS.command {
+        return check_config(args);
+    }
+
+    // status report, gathering the APQN info may temporarily bind APQNs
+    if let Some(cli::Command::Status(args)) = &cli::ARGS.command {
+        return status(args);
+    }
+
+    // watch mode, establishes the lock for each AP configuration pass
+    if cli::ARGS.watch() {
+        return watch();
+    }
+
     // make sure only one pvapconfig instance is running
     let r = LockFile::try_lock(PATH_PVAPCONFIG_LOCK);
     on_error_print_and_exit!(r);
     let _lockfile = r.unwrap();
 
-    // AP bus check
-    info!("Checking AP bus support and facilities...\n");
-    let r = ap::check_ap_bus_support();
-    on_error_print_and_exit!(r);
-    let r = ap::ap_bus_has_apsb_support();
-    on_error_print_and_exit!(r);
-    info!("AP bus support and facilities are ok.\n");
-
-    // UV check
-    info!("Checking UV support and environment...\n");
-    if !pv_core::misc::pv_guest_bit_set() {
-        println_and_exit_failure!("Failure: this is not a SE guest.");
-    }
-    let r = uv::has_list_secrets_facility();
+    let r = check_ap_and_uv();
     on_error_print_and_exit!(r);
-    info!("UV support and environment is ok.\n");
 
     let mut apconfig: ApConfigList = Default::default();
     if !cli::ARGS.unbind {
         // read configuration
-        let configfile: &str = match &cli::ARGS.config {
-            Some(f) => f,
-            _ => cli::PATH_DEFAULT_CONFIG_FILE,
-        };
-        info!(
-            "Reading AP configuration entries from file '{}'...\n",
-            configfile
-        );
-        apconfig = match ApConfigList::read_and_validate_yaml_file(configfile) {
+        apconfig = match read_apconfig() {
             Ok(apcfg) => apcfg,
             Err(err) => println_and_exit_failure!("{}", err),
         };
         if apconfig.is_empty() {
             println!(
                 "No AP configuration entries in config file '{}': Nothing to do.",
-                configfile
+                configfile()
             );
             return ExitCode::SUCCESS;
         }
@@ -115,7 +119,7 @@ fn main() -> ExitCode {
     let mut secrets = SecretList::new(0, Vec::new());
     if !cli::ARGS.unbind {
         info!("Fetching list of secrets from UV...\n");
-        secrets = match uv::gather_secrets() {
+        secrets = match uv::gather_secrets(ARGS.secret_list(), ARGS.uv_backend()) {
             Err(e) => println_and_exit_failure!("{}", e),
             Ok(los) => los,
         };
@@ -183,6 +187,132 @@ fn main() -> ExitCode {
     ExitCode::SUCCESS
 }
 
+/// Check AP bus and UV support and facilities.
+fn check_ap_and_uv() -> Result<(), String> {
+    // AP bus check
+    info!("Checking AP bus support and facilities...\n");
+    ap::check_ap_bus_support()?;
+    ap::ap_bus_has_apsb_support()?;
+    info!("AP bus support and facilities are ok.\n");
+
+    // UV check, not needed if the UV calls are replayed or forwarded
+    if matches!(
+        ARGS.uv_backend(),
+        Some(UvBackendSpec::Replay(_) | UvBackendSpec::Remote(_))
+    ) {
+        return Ok(());
+    }
+    info!("Checking UV support and environment...\n");
+    if !pv_core::misc::pv_guest_bit_set() {
+        return Err("Failure: this is not a SE guest.".to_string());
+    }
+    uv::has_list_secrets_facility()?;
+    info!("UV support and environment is ok.\n");
+    Ok(())
+}
+
+/// The AP config file given on the command line or the default one.
+fn configfile() -> &'static str {
+    match &cli::ARGS.config {
+        Some(f) => f,
+        _ => cli::PATH_DEFAULT_CONFIG_FILE,
+    }
+}
+
+/// Read and validate the AP config file.
+fn read_apconfig() -> Result<ApConfigList, String> {
+    let configfile = configfile();
+    info!(
+        "Reading AP configuration entries from file '{}'...\n",
+        configfile
+    );
+    ApConfigList::read_and_validate_yaml_file(configfile)
+}
+
+/// The status subcommand
+///
+/// Reports for each AP config entry the APQN which satisfies
+/// this entry and lists all bound APQNs not covered by any
+/// AP config entry. The APQN configuration is not changed. As the
+/// gathering of the APQN info temporarily binds APQNs within a SE
+/// guest, the lock is held like for an AP configuration pass.
+fn status(args: &cli::StatusArgs) -> ExitCode {
+    let r = LockFile::try_lock(PATH_PVAPCONFIG_LOCK);
+    on_error_print_and_exit!(r);
+    let _lockfile = r.unwrap();
+
+    let r = check_ap_and_uv();
+    on_error_print_and_exit!(r);
+
+    let apconfig = match read_apconfig() {
+        Ok(apcfg) => apcfg,
+        Err(err) => println_and_exit_failure!("{}", err),
+    };
+    info!("Fetching list of secrets from UV...\n");
+    let secrets = match uv::gather_secrets(ARGS.secret_list(), ARGS.uv_backend()) {
+        Err(e) => println_and_exit_failure!("{}", e),
+        Ok(los) => los,
+    };
+    info!("Waiting for AP bus bindings complete...\n");
+    if !ap::wait_for_ap_bus_bindings_complete() {
+        return ExitCode::FAILURE;
+    }
+    info!("Fetching list of available APQNs...\n");
+    let mut apqns: ApqnList = match ApqnList::gather_apqns() {
+        Some(l) => l,
+        None => return ExitCode::FAILURE,
+    };
+
+    let report = StatusReport::build(&mut apqns, &secrets, &apconfig, ApqnState::read);
+    match report.to_string(args.format) {
+        Ok(s) => print!("{s}"),
+        Err(err) => println_and_exit_failure!("Failure serializing the status report: {}", err),
+    }
+    ExitCode::SUCCESS
+}
+
+/// The check-config command
+///
+/// Validates the given AP config file and reports all findings.
+/// Fails if at least one error has been found.
+fn check_config(args: &cli::CheckConfigArgs) -> ExitCode {
+    let findings = match check::check_config(&args.file, args.secrets_dir.as_deref()) {
+        Ok(f) => f,
+        Err(err) => println_and_exit_failure!("{}", err),
+    };
+    if check::report(&args.file, &findings) {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// The watch mode
+///
+/// Runs until terminated and re-applies the AP configuration on
+/// AP bus uevents and on changes of the UV secrets.
+fn watch() -> ExitCode {
+    let r = check_ap_and_uv();
+    on_error_print_and_exit!(r);
+
+    let apconfig = match read_apconfig() {
+        Ok(apcfg) => apcfg,
+        Err(err) => println_and_exit_failure!("{}", err),
+    };
+    if apconfig.is_empty() {
+        println!(
+            "No AP configuration entries in config file '{}': Nothing to do.",
+            configfile()
+        );
+        return ExitCode::SUCCESS;
+    }
+    info!("Found {} AP configuration entries.\n", apconfig.len());
+
+    let r = watch::watch(&apconfig, Duration::from_secs(cli::ARGS.interval));
+    on_error_print_and_exit!(r);
+    ExitCode::SUCCESS
+}
+
 /// The real worker function
 ///
 /// This is the real algorithm which is trying to apply the
@@ -308,7 +438,9 @@ fn do_ap_config(
         match apqn.bind_state() {
             Err(err) => eprintln!("Warning: Failure reading APQN {apqn} bind state: {err}"),
             Ok(pvap::bind_state::Bound) => {
-                info!("Unbind APQN {apqn} as this bind/associate does not match to any AP config entry.\n");
+                if ARGS.verbose() || ARGS.watch() {
+                    println!("Unbind APQN {apqn} as this bind/associate does not match to any AP config entry.");
+                }
                 if !ARGS.dryrun() {
                     if let Err(err) = apqn.set_bind_state(pvap::bind_state::Unbound) {
                         return Err(format!("Failure unbinding APQN {apqn}: {err}"));
@@ -342,7 +474,7 @@ fn do_ap_config(
             match apqn.mode {
                 pvap::apqn_mode::Accel => {
                     // try to bind this accelerator APQN
-                    if ARGS.verbose() || fntest {
+                    if ARGS.verbose() || ARGS.watch() || fntest {
                         println!("Bind APQN {apqn} to match to AP config entry {cistr}.");
                     }
                     if !(ARGS.dryrun() || fntest) {
@@ -373,7 +505,7 @@ fn do_ap_config(
                         Some(se) => se,
                     };
                     // try to bind
-                    if ARGS.verbose() || fntest {
+                    if ARGS.verbose() || ARGS.watch() || fntest {
                         println!(
                             "Bind APQN {apqn} to match to AP config entry {cistr} (step 1/2)."
                         );
@@ -386,7 +518,7 @@ fn do_ap_config(
                         }
                     }
                     // try to associate
-                    if ARGS.verbose() || fntest {
+                    if ARGS.verbose() || ARGS.watch() || fntest {
                         println!(
 			    "Associate APQN {} with uv secrets index {} to match AP config entry {} (step 2/2).",
 			    apqn, se.index(), cistr
@@ -0,0 +1,377 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2024
+//
+//! Status report of the AP configuration
+//
+
+use crate::ap::ApqnList;
+use crate::cli::StatusFormat;
+use crate::config::{self, ApConfigEntry, ApConfigList};
+use crate::{config_and_apqn_match, uv};
+use pv_core::ap::{self as pvap, Apqn};
+use pv_core::misc::encode_hex;
+use pv_core::uv::{ListableSecretType, SecretList};
+use serde::Serialize;
+
+/// Bind and association state of an APQN as read from sysfs
+///
+/// A state is None if it could not be read.
+#[derive(Debug)]
+pub struct ApqnState {
+    pub bind: Option<pvap::bind_state::BindState>,
+    pub assoc: Option<pvap::assoc_state::AssocState>,
+}
+
+impl ApqnState {
+    /// Read bind and association state of the APQN.
+    /// Failures are reported as warning and result in an unknown state.
+    pub fn read(apqn: &Apqn) -> Self {
+        let bind = match apqn.bind_state() {
+            Ok(s) => Some(s),
+            Err(err) => {
+                eprintln!("Warning: Failure reading APQN {apqn} bind state: {err}");
+                None
+            }
+        };
+        // accelerators do not support association
+        let assoc = match apqn.mode {
+            pvap::apqn_mode::Accel => Some(pvap::assoc_state::NotSupported),
+            _ => match apqn.associate_state() {
+                Ok(s) => Some(s),
+                Err(err) => {
+                    eprintln!("Warning: Failure reading APQN {apqn} associate state: {err}");
+                    None
+                }
+            },
+        };
+        Self { bind, assoc }
+    }
+}
+
+/// Status of one APQN
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct ApqnStatus {
+    pub apqn: String,
+    pub card: u32,
+    pub domain: u32,
+    pub mode: &'static str,
+    pub gen: u32,
+    pub bind_state: &'static str,
+    pub assoc_state: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret_index: Option<u16>,
+}
+
+impl ApqnStatus {
+    fn new(apqn: &Apqn, state: &ApqnState) -> Self {
+        let mode = match apqn.mode {
+            pvap::apqn_mode::Accel => config::STR_MODE_ACCEL,
+            pvap::apqn_mode::Ep11 => config::STR_MODE_EP11,
+            pvap::apqn_mode::Cca => config::STR_MODE_CCA,
+        };
+        let bind_state = match state.bind {
+            Some(pvap::bind_state::Bound) => "bound",
+            Some(pvap::bind_state::Unbound) => "unbound",
+            Some(pvap::bind_state::NotSupported) => "not-supported",
+            None => "unknown",
+        };
+        let (assoc_state, secret_index) = match state.assoc {
+            Some(pvap::assoc_state::Associated(idx)) => ("associated", Some(idx)),
+            Some(pvap::assoc_state::AssociationPending) => ("pending", None),
+            Some(pvap::assoc_state::Unassociated) => ("unassociated", None),
+            Some(pvap::assoc_state::NotSupported) => ("not-supported", None),
+            None => ("unknown", None),
+        };
+        Self {
+            apqn: apqn.name.clone(),
+            card: apqn.card,
+            domain: apqn.domain,
+            mode,
+            gen: apqn.gen,
+            bind_state,
+            assoc_state,
+            secret_index,
+        }
+    }
+}
+
+/// Status of one AP config entry
+#[derive(Debug, Serialize)]
+pub struct EntryStatus {
+    pub entry: usize,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    pub mode: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub secretid: String,
+    pub satisfied: bool,
+    /// The APQN satisfying the entry or, if not satisfied, the first
+    /// remaining APQN matching the entry but not bound or associated as
+    /// required.
+    pub apqn: Option<ApqnStatus>,
+}
+
+/// Status report over all AP config entries and APQNs
+#[derive(Debug, Serialize)]
+pub struct StatusReport {
+    pub entries_total: usize,
+    pub entries_satisfied: usize,
+    pub entries: Vec<EntryStatus>,
+    pub uncovered_apqns: Vec<ApqnStatus>,
+}
+
+/// Check if the APQN with the given state satisfies the AP config entry.
+///
+/// The APQN needs to match the entry and needs to be bound. EP11 APQNs
+/// additionally need to be associated with the secret index of the
+/// entry's secret id.
+fn apqn_satisfies(
+    apc: &ApConfigEntry,
+    apqn: &Apqn,
+    state: &ApqnState,
+    secrets: &SecretList,
+) -> bool {
+    if !config_and_apqn_match(apc, apqn) {
+        return false;
+    }
+    match apqn.mode {
+        pvap::apqn_mode::Accel => state.bind == Some(pvap::bind_state::Bound),
+        pvap::apqn_mode::Ep11 => match state.assoc {
+            Some(pvap::assoc_state::Associated(idx)) => secrets.iter().any(|se| {
+                se.stype() == ListableSecretType::Association
+                    && se.id().len() == uv::AP_ASSOC_SECRET_ID_SIZE
+                    && se.index() == idx
+                    && encode_hex(se.id()) == apc.secretid
+            }),
+            _ => false,
+        },
+        // no AP config entry matches CCA APQNs
+        pvap::apqn_mode::Cca => false,
+    }
+}
+
+impl StatusReport {
+    /// Build the status report.
+    ///
+    /// This follows step 1 of the AP config algorithm: APQNs are examined
+    /// ordered by card generation (newest first) and each APQN may only
+    /// satisfy one AP config entry. The state of each APQN is retrieved
+    /// once with the given state function. AP config entries which are not
+    /// satisfied get the first remaining matching APQN assigned, so its
+    /// bind and association state show what is missing.
+    pub fn build<F>(
+        apqns: &mut ApqnList,
+        secrets: &SecretList,
+        apconfig: &ApConfigList,
+        state_fn: F,
+    ) -> Self
+    where
+        F: Fn(&Apqn) -> ApqnState,
+    {
+        apqns.sort_by_gen();
+        let states: Vec<ApqnState> = apqns.iter().map(state_fn).collect();
+        let mut apqn_done = vec![false; apqns.len()];
+
+        let mut entries = Vec::with_capacity(apconfig.len());
+        for (ci, apc) in apconfig.iter().enumerate() {
+            let found = apqns.iter().enumerate().find(|(ai, apqn)| {
+                !apqn_done[*ai] && apqn_satisfies(apc, apqn, &states[*ai], secrets)
+            });
+            let apqn = found.map(|(ai, apqn)| {
+                apqn_done[ai] = true;
+                ApqnStatus::new(apqn, &states[ai])
+            });
+            entries.push(EntryStatus {
+                entry: ci + 1,
+                name: apc.name.clone(),
+                mode: apc.mode.clone(),
+                secretid: apc.secretid.clone(),
+                satisfied: apqn.is_some(),
+                apqn,
+            });
+        }
+
+        // second pass: report a matching APQN for each unsatisfied entry
+        for (apc, entry) in apconfig.iter().zip(entries.iter_mut()) {
+            if entry.satisfied {
+                continue;
+            }
+            let found = apqns
+                .iter()
+                .enumerate()
+                .find(|(ai, apqn)| !apqn_done[*ai] && config_and_apqn_match(apc, apqn));
+            entry.apqn = found.map(|(ai, apqn)| {
+                apqn_done[ai] = true;
+                ApqnStatus::new(apqn, &states[ai])
+            });
+        }
+
+        let uncovered_apqns = apqns
+            .iter()
+            .enumerate()
+            .filter(|(ai, _)| !apqn_done[*ai] && states[*ai].bind == Some(pvap::bind_state::Bound))
+            .map(|(ai, apqn)| ApqnStatus::new(apqn, &states[ai]))
+            .collect();
+
+        Self {
+            entries_total: entries.len(),
+            entries_satisfied: entries.iter().filter(|e| e.satisfied).count(),
+            entries,
+            uncovered_apqns,
+        }
+    }
+
+    /// Serialize the report in the given format.
+    pub fn to_string(&self, format: StatusFormat) -> Result<String, String> {
+        match format {
+            StatusFormat::Yaml => serde_yaml::to_string(self).map_err(|e| e.to_string()),
+            StatusFormat::Json => serde_json::to_string_pretty(self)
+                .map(|s| s + "\n")
+                .map_err(|e| e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use pv_core::misc::decode_hex;
+    use pv_core::uv::SecretEntry;
+
+    const SECRETID: &str = "bc9d46c052bc3574454c5715757274629a283767ed237922cfb8651c0e77320a";
+
+    fn make_apqn(name: &str, card: u32, domain: u32, mode: pvap::apqn_mode::ApqnMode) -> Apqn {
+        Apqn {
+            name: String::from(name),
+            card,
+            domain,
+            gen: 8,
+            info: match mode {
+                pvap::apqn_mode::Accel => {
+                    Some(pvap::apqn_info::Accel(pvap::apqn_info::ApqnInfoAccel {}))
+                }
+                _ => Some(pvap::apqn_info::Ep11(pvap::apqn_info::ApqnInfoEp11 {
+                    serialnr: String::from("93AADFK719460083"),
+                    mkvp: String::from("db3c3b3c3f097dd55ec7eb0e7fdbcb93"),
+                })),
+            },
+            mode,
+        }
+    }
+
+    fn make_apconfig() -> ApConfigList {
+        ApConfigList::from_apconfigentry_vec(vec![
+            ApConfigEntry {
+                name: String::from("accel"),
+                mode: String::from(config::STR_MODE_ACCEL),
+                ..Default::default()
+            },
+            ApConfigEntry {
+                name: String::from("ep11"),
+                mode: String::from(config::STR_MODE_EP11),
+                mkvp: String::from("db3c3b3c3f097dd55ec7eb0e7fdbcb93"),
+                secretid: String::from(SECRETID),
+                ..Default::default()
+            },
+        ])
+    }
+
+    fn make_secrets() -> SecretList {
+        let id: [u8; 32] = decode_hex(SECRETID).unwrap().try_into().unwrap();
+        let secrets = vec![SecretEntry::new(
+            13,
+            ListableSecretType::Association,
+            id.into(),
+            32,
+        )];
+        SecretList::new(secrets.len() as u16, secrets)
+    }
+
+    fn state(bind: pvap::bind_state::BindState, assoc: pvap::assoc_state::AssocState) -> ApqnState {
+        ApqnState {
+            bind: Some(bind),
+            assoc: Some(assoc),
+        }
+    }
+
+    #[test]
+    fn test_status_satisfied() {
+        let mut apqns = ApqnList::from_apqn_vec(vec![
+            make_apqn("10.0007", 16, 7, pvap::apqn_mode::Accel),
+            make_apqn("11.0008", 17, 8, pvap::apqn_mode::Ep11),
+        ]);
+        let report =
+            StatusReport::build(&mut apqns, &make_secrets(), &make_apconfig(), |a| {
+                match a.mode {
+                    pvap::apqn_mode::Accel => {
+                        state(pvap::bind_state::Bound, pvap::assoc_state::NotSupported)
+                    }
+                    _ => state(pvap::bind_state::Bound, pvap::assoc_state::Associated(13)),
+                }
+            });
+        assert_eq!(report.entries_total, 2);
+        assert_eq!(report.entries_satisfied, 2);
+        assert!(report.uncovered_apqns.is_empty());
+        let ep11 = report.entries[1].apqn.as_ref().unwrap();
+        assert_eq!(ep11.apqn, "11.0008");
+        assert_eq!(ep11.assoc_state, "associated");
+        assert_eq!(ep11.secret_index, Some(13));
+    }
+
+    #[test]
+    fn test_status_drift() {
+        let mut apqns = ApqnList::from_apqn_vec(vec![
+            make_apqn("10.0007", 16, 7, pvap::apqn_mode::Accel),
+            make_apqn("11.0008", 17, 8, pvap::apqn_mode::Ep11),
+        ]);
+        // EP11 APQN is associated with the wrong secret index and
+        // the accelerator is not bound
+        let report =
+            StatusReport::build(&mut apqns, &make_secrets(), &make_apconfig(), |a| {
+                match a.mode {
+                    pvap::apqn_mode::Accel => {
+                        state(pvap::bind_state::Unbound, pvap::assoc_state::NotSupported)
+                    }
+                    _ => state(pvap::bind_state::Bound, pvap::assoc_state::Associated(3)),
+                }
+            });
+        assert_eq!(report.entries_satisfied, 0);
+        assert!(report.entries.iter().all(|e| !e.satisfied));
+        // the matching APQNs are reported with their current state
+        let accel = report.entries[0].apqn.as_ref().unwrap();
+        assert_eq!(accel.apqn, "10.0007");
+        assert_eq!(accel.bind_state, "unbound");
+        let ep11 = report.entries[1].apqn.as_ref().unwrap();
+        assert_eq!(ep11.apqn, "11.0008");
+        assert_eq!(ep11.secret_index, Some(3));
+        assert!(report.uncovered_apqns.is_empty());
+
+        let yaml = report.to_string(StatusFormat::Yaml).unwrap();
+        assert!(yaml.contains("bind_state: unbound"));
+        let json = report.to_string(StatusFormat::Json).unwrap();
+        assert!(json.contains("\"entries_satisfied\": 0"));
+    }
+
+    #[test]
+    fn test_status_uncovered() {
+        let mut apqns = ApqnList::from_apqn_vec(vec![
+            make_apqn("10.0007", 16, 7, pvap::apqn_mode::Accel),
+            make_apqn("11.0008", 17, 8, pvap::apqn_mode::Ep11),
+            make_apqn("12.0009", 18, 9, pvap::apqn_mode::Ep11),
+        ]);
+        let report =
+            StatusReport::build(&mut apqns, &make_secrets(), &make_apconfig(), |a| {
+                match a.card {
+                    16 => state(pvap::bind_state::Bound, pvap::assoc_state::NotSupported),
+                    17 => state(pvap::bind_state::Bound, pvap::assoc_state::Associated(13)),
+                    _ => state(pvap::bind_state::Bound, pvap::assoc_state::Associated(3)),
+                }
+            });
+        assert_eq!(report.entries_satisfied, 2);
+        assert_eq!(report.uncovered_apqns.len(), 1);
+        assert_eq!(report.uncovered_apqns[0].apqn, "12.0009");
+        assert_eq!(report.uncovered_apqns[0].secret_index, Some(3));
+    }
+}
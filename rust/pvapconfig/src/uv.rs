@@ -5,9 +5,10 @@
 //! UV related functions for pvapconfig
 //
 
-use pv_core::misc::read_file_string;
-use pv_core::uv::{ListCmd, SecretList, UvDevice, UvcSuccess};
+use pv_core::misc::{read_file, read_file_string, sysfs_path};
+use pv_core::uv::{ListCmd, SecretList, UvBackendSpec, UvDevice, UvcSuccess};
 use regex::Regex;
+use std::io::Cursor;
 
 /// The byte size of association secret of type 2 in struct SecretEntry
 pub const AP_ASSOC_SECRET_ID_SIZE: usize = 32;
@@ -25,8 +26,8 @@ const RE_UV_FAC_BIT_LIST_SECRETS: u32 = 30;
 /// Panics if the compilation of a static regular expression fails.
 /// Panics if RE_UV_FACILITIES does not match.
 pub fn has_list_secrets_facility() -> Result<(), String> {
-    let facstr =
-        read_file_string(PATH_SYS_FW_UV_FACILITIES, "UV facilities").map_err(|e| e.to_string())?;
+    let facstr = read_file_string(sysfs_path(PATH_SYS_FW_UV_FACILITIES), "UV facilities")
+        .map_err(|e| e.to_string())?;
     let re_uv_facilities = Regex::new(RE_UV_FACILITIES).unwrap();
     if !re_uv_facilities.is_match(&facstr) {
         Err(format!("Failure parsing UV facilities entry '{facstr}'."))
@@ -47,11 +48,23 @@ pub fn has_list_secrets_facility() -> Result<(), String> {
 }
 
 /// Fetch the list of secrets from the UV.
+/// If a secret list file is given, the binary list of secrets is read
+/// from this file instead, e.g. as written by 'pvsecret list --format bin'.
+/// If a backend is given, the UV is accessed via this backend instead of
+/// /dev/uv.
 /// Returns Err(errorstring) on error or
 /// Ok(SecretList) on success.
 /// The list may be empty if the UV doesn't have any secrets stored.
-pub fn gather_secrets() -> Result<SecretList, String> {
-    let uv = match UvDevice::open() {
+pub fn gather_secrets(
+    secret_list: Option<&str>,
+    uv_backend: Option<&UvBackendSpec>,
+) -> Result<SecretList, String> {
+    if let Some(fname) = secret_list {
+        let buf = read_file(fname, "secret list").map_err(|e| e.to_string())?;
+        return SecretList::decode(&mut Cursor::new(buf))
+            .map_err(|e| format!("Failure parsing secret list {fname}: {e}."));
+    }
+    let uv = match uv_backend.map_or_else(UvDevice::open, UvBackendSpec::open) {
         Err(e) => return Err(format!("Failed to open UV device: {:?}.", e)),
         Ok(u) => u,
     };
@@ -82,7 +95,7 @@ mod tests {
     // Simple invocation of the list_secrets function. Should not fail
     #[test]
     fn test_list_secrets() {
-        let r = gather_secrets();
+        let r = gather_secrets(None, None);
         if pv_core::misc::pv_guest_bit_set() {
             assert!(r.is_ok());
         } else {
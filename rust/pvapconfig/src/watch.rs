@@ -0,0 +1,324 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2024
+//
+//! Watch mode: re-apply the AP configuration on AP bus uevents
+//! and on changes of the UV secrets.
+//
+
+use crate::ap::{self, ApqnList};
+use crate::cli::ARGS;
+use crate::config::ApConfigList;
+use crate::do_ap_config;
+use crate::helper::{LockFile, PATH_PVAPCONFIG_LOCK};
+use crate::uv;
+use pv_core::uv::SecretList;
+use std::io;
+use std::mem;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Kernel uevent multicast group
+const UEVENT_KERNEL_GROUP: u32 = 1;
+/// Maximum size of one uevent message
+const UEVENT_BUFFER_SIZE: usize = 8192;
+/// Time to wait for further uevents before applying the configuration
+const UEVENT_SETTLE_MS: u64 = 1000;
+
+static TERMINATE: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_terminate_signal(_: libc::c_int) {
+    TERMINATE.store(true, Ordering::SeqCst);
+}
+
+/// Install handlers for SIGTERM and SIGINT.
+///
+/// The handlers only set a flag so that a running AP configuration
+/// pass is completed and the lock file is removed before terminating.
+fn install_signal_handlers() -> Result<(), String> {
+    for sig in [libc::SIGTERM, libc::SIGINT] {
+        // SAFETY: the sigaction struct is fully initialized and the
+        // handler only stores into an atomic which is async-signal-safe.
+        let rc = unsafe {
+            let mut sa: libc::sigaction = mem::zeroed();
+            sa.sa_sigaction =
+                on_terminate_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            libc::sigemptyset(&mut sa.sa_mask);
+            libc::sigaction(sig, &sa, std::ptr::null_mut())
+        };
+        if rc != 0 {
+            return Err(format!(
+                "Failure installing signal handler: {}",
+                io::Error::last_os_error()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// A uevent as sent by the kernel
+#[derive(Debug, PartialEq, Eq)]
+pub struct Uevent {
+    pub action: String,
+    pub devpath: String,
+    pub subsystem: String,
+}
+
+impl Uevent {
+    /// Parse a kernel uevent message.
+    ///
+    /// The message consists of a header "action@devpath" followed by
+    /// KEY=VALUE pairs, all separated by null bytes.
+    /// Returns None if the message is not a kernel uevent.
+    pub fn parse(buf: &[u8]) -> Option<Self> {
+        let mut fields = buf
+            .split(|&b| b == 0)
+            .filter(|f| !f.is_empty())
+            .map(String::from_utf8_lossy);
+        let header = fields.next()?;
+        let (action, devpath) = header.split_once('@')?;
+        let mut uevent = Self {
+            action: action.to_string(),
+            devpath: devpath.to_string(),
+            subsystem: String::new(),
+        };
+        for field in fields {
+            if let Some(subsystem) = field.strip_prefix("SUBSYSTEM=") {
+                uevent.subsystem = subsystem.to_string();
+            }
+        }
+        Some(uevent)
+    }
+
+    /// True if this uevent is about the AP bus or an AP device.
+    pub fn is_ap(&self) -> bool {
+        self.subsystem == "ap"
+    }
+}
+
+/// Netlink socket receiving kernel uevents
+#[derive(Debug)]
+struct UeventSocket {
+    fd: OwnedFd,
+}
+
+impl UeventSocket {
+    fn open() -> Result<Self, String> {
+        let err = |what: &str| {
+            format!(
+                "Failure {what} uevent socket: {}",
+                io::Error::last_os_error()
+            )
+        };
+        // SAFETY: plain socket creation, the fd is owned by OwnedFd on success
+        let fd = unsafe {
+            libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_DGRAM | libc::SOCK_CLOEXEC,
+                libc::NETLINK_KOBJECT_UEVENT,
+            )
+        };
+        if fd < 0 {
+            return Err(err("opening"));
+        }
+        // SAFETY: fd is a valid and otherwise unowned file descriptor
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+        // SAFETY: sockaddr_nl is fully initialized and the size matches
+        let rc = unsafe {
+            let mut addr: libc::sockaddr_nl = mem::zeroed();
+            addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+            addr.nl_groups = UEVENT_KERNEL_GROUP;
+            libc::bind(
+                fd.as_raw_fd(),
+                &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+                mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+            )
+        };
+        if rc != 0 {
+            return Err(err("binding"));
+        }
+        Ok(Self { fd })
+    }
+
+    /// Wait up to timeout for uevents and return all AP uevents received.
+    /// An interrupted wait (e.g. by a signal) returns an empty list.
+    fn wait(&self, timeout: Duration) -> Result<Vec<Uevent>, String> {
+        let mut pfd = libc::pollfd {
+            fd: self.fd.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let timeout_ms = timeout.as_millis().min(libc::c_int::MAX as u128) as libc::c_int;
+        // SAFETY: pfd is a valid pollfd array of length 1
+        let rc = unsafe { libc::poll(&mut pfd, 1, timeout_ms) };
+        if rc < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                return Ok(Vec::new());
+            }
+            return Err(format!("Failure waiting for uevents: {err}"));
+        }
+        let mut uevents = Vec::new();
+        let mut buf = vec![0u8; UEVENT_BUFFER_SIZE];
+        loop {
+            // SAFETY: buf is valid for writes of buf.len() bytes
+            let len = unsafe {
+                libc::recv(
+                    self.fd.as_raw_fd(),
+                    buf.as_mut_ptr() as *mut libc::c_void,
+                    buf.len(),
+                    libc::MSG_DONTWAIT,
+                )
+            };
+            if len < 0 {
+                let err = io::Error::last_os_error();
+                match err.kind() {
+                    io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted => break,
+                    _ => return Err(format!("Failure reading uevents: {err}")),
+                }
+            }
+            if let Some(uevent) = Uevent::parse(&buf[..len as usize]) {
+                if uevent.is_ap() {
+                    uevents.push(uevent);
+                }
+            }
+        }
+        Ok(uevents)
+    }
+}
+
+/// Outcome of one AP configuration pass
+enum Pass {
+    /// The configuration has been applied with the given secrets
+    Done(SecretList),
+    /// Another pvapconfig instance holds the lock
+    Locked,
+}
+
+/// Apply the AP configuration once.
+///
+/// The lock file is held during the whole pass. If the lock file can
+/// not be established, the pass is skipped.
+fn apply_once(apconfig: &ApConfigList) -> Result<Pass, String> {
+    let _lockfile = match LockFile::try_lock(PATH_PVAPCONFIG_LOCK) {
+        Ok(l) => l,
+        Err(err) => {
+            println!("Skipping AP configuration: {err}");
+            return Ok(Pass::Locked);
+        }
+    };
+    let secrets = uv::gather_secrets(ARGS.secret_list(), ARGS.uv_backend())?;
+    if !ap::wait_for_ap_bus_bindings_complete() {
+        return Err("AP bus bindings are not complete.".to_string());
+    }
+    let mut apqns = ApqnList::gather_apqns().ok_or("Failure gathering APQNs.")?;
+    if !apqns.check_mk_restriction() {
+        return Err("MK restriction check failed.".to_string());
+    }
+    let n = do_ap_config(&mut apqns, &secrets, apconfig, false)?;
+    println!(
+        "Applied {} out of {} AP config entries with {} APQNs and {} UV secrets.",
+        n,
+        apconfig.len(),
+        apqns.len(),
+        secrets.len()
+    );
+    Ok(Pass::Done(secrets))
+}
+
+/// Run in watch mode until terminated by SIGTERM or SIGINT.
+///
+/// The AP configuration is applied at startup, after AP bus uevents
+/// and whenever the periodically fetched list of UV secrets changes.
+/// A pass which failed or was skipped due to the lock file is retried
+/// after the next interval.
+pub fn watch(apconfig: &ApConfigList, interval: Duration) -> Result<(), String> {
+    install_signal_handlers()?;
+    let socket = UeventSocket::open()?;
+    println!(
+        "Watching for AP bus uevents and UV secret changes every {} seconds.",
+        interval.as_secs()
+    );
+
+    let mut last_secrets: Option<SecretList> = None;
+    let mut apply = true;
+    let mut next_check = Instant::now() + interval;
+    while !TERMINATE.load(Ordering::SeqCst) {
+        if apply {
+            apply = match apply_once(apconfig) {
+                Ok(Pass::Done(secrets)) => {
+                    last_secrets = Some(secrets);
+                    false
+                }
+                Ok(Pass::Locked) => true,
+                Err(err) => {
+                    eprintln!("Failure applying AP configuration: {err}");
+                    true
+                }
+            };
+            // a failed or skipped pass is retried with the next interval
+            if apply {
+                next_check = Instant::now() + interval;
+            }
+        }
+
+        let uevents = socket.wait(next_check.saturating_duration_since(Instant::now()))?;
+        if !uevents.is_empty() {
+            // let a burst of uevents (e.g. a new card with its queues) settle
+            thread::sleep(Duration::from_millis(UEVENT_SETTLE_MS));
+            let more = socket.wait(Duration::ZERO)?;
+            for uevent in uevents.iter().chain(more.iter()) {
+                println!("AP uevent: {} {}", uevent.action, uevent.devpath);
+            }
+            apply = true;
+        }
+
+        if Instant::now() >= next_check {
+            next_check = Instant::now() + interval;
+            if apply {
+                // retry of a failed or skipped pass
+                continue;
+            }
+            match uv::gather_secrets(ARGS.secret_list(), ARGS.uv_backend()) {
+                Ok(secrets) if last_secrets.as_ref() != Some(&secrets) => {
+                    println!("UV secrets changed.");
+                    apply = true;
+                }
+                Ok(_) => {}
+                Err(err) => eprintln!("{err}"),
+            }
+        }
+    }
+    println!("Terminating watch mode.");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_uevent_parse() {
+        let msg = b"add@/devices/ap/card0c/0c.0005\0ACTION=add\0DEVPATH=/devices/ap/card0c/0c.0005\0SUBSYSTEM=ap\0DEVTYPE=ap_queue\0SEQNUM=1234\0";
+        let uevent = Uevent::parse(msg).unwrap();
+        assert_eq!(
+            uevent,
+            Uevent {
+                action: String::from("add"),
+                devpath: String::from("/devices/ap/card0c/0c.0005"),
+                subsystem: String::from("ap"),
+            }
+        );
+        assert!(uevent.is_ap());
+
+        let msg = b"change@/devices/virtual/net/lo\0ACTION=change\0SUBSYSTEM=net\0";
+        assert!(!Uevent::parse(msg).unwrap().is_ap());
+
+        // udev messages do not have the kernel uevent header
+        assert!(Uevent::parse(b"libudev\0\xfe\xed\xca\xfe").is_none());
+        assert!(Uevent::parse(b"").is_none());
+    }
+}
@@ -4,8 +4,8 @@
 
 use std::path::PathBuf;
 
-use clap::{Args, Parser, Subcommand, ValueEnum, ValueHint};
-use utils::{CertificateOptions, DeprecatedVerbosityOptions};
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum, ValueHint};
+use utils::{CertificateOptions, DeprecatedVerbosityOptions, UvDeviceOptions};
 
 /// create, perform, and verify attestation measurements
 ///
@@ -56,11 +56,28 @@ pub enum Command {
     /// you verified the Attestation measurement.
     Verify(VerifyOpt),
 
+    /// Verify many attestation responses.
+    ///
+    /// Verify all attestation responses listed in a manifest in one run and produce a
+    /// consolidated report. Each manifest entry names an attestation response, the IBM Secure
+    /// Execution header of the attested image, and the protection key of the request. Optionally,
+    /// check the policies of 'pvattest check' on each verified response. Only verify attestation
+    /// requests in a trusted environment, such as your workstation.
+    VerifyBatch(VerifyBatchOpt),
+
     /// Check if the attestation result matches defined policies.
     ///
     /// After the attestation verification, check whether the attestation result complies with user-defined policies.
     Check(CheckOpt),
 
+    /// Display the content of an attestation request or response.
+    ///
+    /// Parses an exchange file as produced by 'pvattest create' or 'pvattest perform' and shows
+    /// its format version, the layout of all entries, the authenticated part of the attestation
+    /// request, and which user data and additional data are present. Does not decrypt or verify
+    /// the request or response.
+    Inspect(InspectOpt),
+
     /// Print version information and exit.
     #[command(aliases(["--version"]), hide(true))]
     Version,
@@ -93,6 +110,39 @@ pub struct CreateAttOpt {
         value_delimiter = ','
     )]
     pub add_data: Vec<AttAddFlags>,
+
+    /// Use the 16 bytes in FILE as nonce of the request.
+    ///
+    /// By default, a random nonce is generated for every request. Specify a nonce chosen by the
+    /// verifier to prove the freshness of the attestation response. Use 'pvattest verify
+    /// --expect-nonce' to check the nonce. Optional.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath,)]
+    pub nonce: Option<String>,
+
+    /// Add metadata to the request.
+    ///
+    /// The metadata contains the creation time, the version of pvattest, and the requested
+    /// additional data. Requests with metadata use the exchange format version 2, which older
+    /// versions of pvattest cannot read. The metadata is passed unchanged to the attestation
+    /// response by 'pvattest perform'.
+    #[arg(long)]
+    pub metadata: bool,
+
+    /// Record the hash of the IBM Secure Execution header in FILE in the metadata.
+    ///
+    /// Can be an IBM Secure Execution image created by genprotimg or an extracted IBM Secure
+    /// Execution header. 'pvattest verify' warns if the header used for the verification
+    /// differs. Implies '--metadata'.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath,)]
+    pub hdr: Option<String>,
+
+    /// Sign the request and its metadata with the private key in FILE.
+    ///
+    /// FILE must contain an EC or RSA private key in PEM or DER format. The signature proves
+    /// the creator of the request to the verifier. Use 'pvattest verify --signer-key' to verify
+    /// the signature. Requires the exchange format version 2.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath,)]
+    pub sign_key: Option<String>,
 }
 
 #[derive(Debug, ValueEnum, Clone, Copy)]
@@ -110,27 +160,22 @@ pub enum AttAddFlags {
     FirmwareState,
 }
 
-// all members s390x only
 #[derive(Args, Debug)]
 pub struct PerformAttOpt {
     /// Specify the request to be sent.
-    #[cfg(target_arch = "s390x")]
     #[arg(hide=true, short, long, value_name = "FILE", value_hint = ValueHint::FilePath,)]
     pub input: Option<String>,
 
     /// Specify the request to be sent.
-    #[cfg(target_arch = "s390x")]
     #[arg(value_name = "IN", value_hint = ValueHint::FilePath, required_unless_present("input"), conflicts_with("input"))]
     pub input_pos: Option<String>,
 
     /// Write the result to FILE.
-    #[cfg(target_arch = "s390x")]
     #[arg(hide=true, short, long, value_name = "FILE", value_hint = ValueHint::FilePath,)]
     pub output: Option<String>,
 
     /// Write the result to FILE.
     #[arg(value_name = "OUT", value_hint = ValueHint::FilePath, required_unless_present("output"), conflicts_with("output"))]
-    #[cfg(target_arch = "s390x")]
     pub output_pos: Option<String>,
 
     /// Provide up to 256 bytes of user input
@@ -140,17 +185,28 @@ pub struct PerformAttOpt {
     /// May be any arbitrary data, as long as it is less or equal to 256 bytes
     #[arg(short, long, value_name = "File", value_hint = ValueHint::FilePath,)]
     pub user_data: Option<String>,
+
+    /// Bind the challenge in FILE to the Attestation measurement.
+    ///
+    /// The challenge may be arbitrary data of any size chosen by the verifier. The user-data is
+    /// set to SHA-512("s390-pv-attestation-challenge-v1" || challenge). Use 'pvattest check
+    /// --challenge' to check the challenge. Mutually exclusive with '--user-data'.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath, conflicts_with("user_data"))]
+    pub challenge: Option<String>,
+
+    #[command(flatten)]
+    pub uv_device: UvDeviceOptions,
 }
 
-#[cfg(target_arch = "s390x")]
 #[derive(Debug)]
 pub struct PerformAttOptComb<'a> {
     pub input: &'a str,
     pub output: &'a str,
     pub user_data: Option<&'a str>,
+    pub challenge: Option<&'a str>,
+    pub uv_device: &'a UvDeviceOptions,
 }
 
-#[cfg(target_arch = "s390x")]
 impl<'a> From<&'a PerformAttOpt> for PerformAttOptComb<'a> {
     fn from(value: &'a PerformAttOpt) -> Self {
         let input = match (&value.input, &value.input_pos) {
@@ -166,10 +222,13 @@ impl<'a> From<&'a PerformAttOpt> for PerformAttOptComb<'a> {
             (None, None) => unreachable!(),
         };
         let user_data = value.user_data.as_deref();
+        let challenge = value.challenge.as_deref();
         Self {
             input,
             output,
             user_data,
+            challenge,
+            uv_device: &value.uv_device,
         }
     }
 }
@@ -210,6 +269,73 @@ pub struct VerifyOpt {
     /// Emits a warning if the response contains no user-data.
     #[arg(long, short ,value_name = "FILE", value_hint = ValueHint::FilePath,)]
     pub user_data: Option<String>,
+
+    /// Check that the request contains the 16 byte nonce in FILE.
+    ///
+    /// The nonce is part of the encrypted attestation request and covered by the attestation
+    /// measurement. The verification fails if the nonce of the request differs from the nonce
+    /// in FILE. Use together with 'pvattest create --nonce'.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath,)]
+    pub expect_nonce: Option<String>,
+
+    /// Verify the signature of the request creator with the public key in FILE.
+    ///
+    /// FILE must contain an EC or RSA public key in PEM or DER format. The verification fails if
+    /// the response contains no signature or the signature is not valid. Use together with
+    /// 'pvattest create --sign-key'.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath,)]
+    pub signer_key: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct VerifyBatchOpt {
+    /// Use FILE as manifest of the attestation responses to verify.
+    ///
+    /// FILE is a YAML or JSON list of entries with the keys 'response', 'hdr', 'arpk', and
+    /// optionally 'name' and 'expect-nonce'. Relative paths are relative to the directory of
+    /// FILE.
+    #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath,)]
+    pub manifest: PathBuf,
+
+    /// Write the report to FILE instead of stdout.
+    #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath,)]
+    pub output: Option<PathBuf>,
+
+    /// Define the output format.
+    #[arg(long, value_enum, default_value_t)]
+    pub format: OutputType,
+
+    /// Verify the signature of the request creators with the public key in FILE.
+    ///
+    /// FILE must contain an EC or RSA public key in PEM or DER format. The verification of an
+    /// entry fails if its response contains no signature or the signature is not valid.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath,)]
+    pub signer_key: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub policies: CheckPolicyOpt,
+}
+
+#[derive(Args, Debug)]
+pub struct InspectOpt {
+    /// Specify the attestation request or response to inspect.
+    #[arg(value_name = "FILE", value_hint = ValueHint::FilePath,)]
+    pub input: PathBuf,
+
+    /// Define the output format.
+    #[arg(long, value_enum, default_value_t)]
+    pub format: InspectOutputType,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug, Default)]
+pub enum InspectOutputType {
+    /// Use human readable format.
+    #[default]
+    Human,
+    /// Use yaml format.
+    Yaml,
+    /// Use json format.
+    Json,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug, Default)]
@@ -233,6 +359,13 @@ pub struct CheckOpt {
     #[arg(long, value_enum, default_value_t)]
     pub format: OutputType,
 
+    #[command(flatten)]
+    pub policies: CheckPolicyOpt,
+}
+
+#[derive(Args, Debug)]
+#[command(group(ArgGroup::new("host-keys").multiple(true)))]
+pub struct CheckPolicyOpt {
     /// Use FILE to check for a  host-key document.
     ///
     /// Verifies that the attestation response contains the host-key hash of one of the specified
@@ -245,17 +378,33 @@ pub struct CheckOpt {
         value_hint = ValueHint::FilePath,
         use_value_delimiter = true,
         value_delimiter = ',',
+        group = "host-keys",
         )]
     pub host_key_documents: Vec<PathBuf>,
 
+    /// Use FILE to check for the host-key hashes provided by the Ultravisor.
+    ///
+    /// FILE must have the format of '/sys/firmware/uv/keys/all' of the attested system. Verifies
+    /// that the attestation response contains one of the host-key hashes in FILE. The check fails
+    /// if none of the hashes match the hash in the response. Mutually exclusive with
+    /// '--host-key-document'.
+    #[arg(
+        long = "key-hashes",
+        value_name = "FILE",
+        value_hint = ValueHint::FilePath,
+        conflicts_with("host_key_documents"),
+        group = "host-keys",
+        )]
+    pub key_hashes: Option<PathBuf>,
+
     /// Define the host-key check policy
     ///
     /// By default, all host-key hashes are checked, and it is not considered a failure if a hash
     /// is missing from the attestation response. Use this policy switch to trigger a failure if no
-    /// corresponding hash is found. Requires at least one host-key document.
+    /// corresponding hash is found. Requires at least one host-key document or '--key-hashes'.
     #[arg(
         long = "host-key-check",
-        requires("host_key_documents"),
+        requires("host-keys"),
         use_value_delimiter = true,
         value_delimiter = ','
     )]
@@ -265,6 +414,14 @@ pub struct CheckOpt {
     #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath,)]
     pub user_data: Option<PathBuf>,
 
+    /// Check if the user data of the attestation response binds the challenge in FILE.
+    ///
+    /// Recalculates the user data from the challenge as done by 'pvattest perform --challenge'
+    /// and compares it with the user data from the attestation response. Mutually exclusive
+    /// with '--user-data'.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath, conflicts_with("user_data"))]
+    pub challenge: Option<PathBuf>,
+
     /// Use FILE to include as successful Add-secret request.
     ///
     /// Checks if the Attestation response contains the hash of all specified add secret
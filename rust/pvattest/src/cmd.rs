@@ -4,13 +4,16 @@
 //
 pub mod check;
 pub mod create;
-#[cfg(target_arch = "s390x")]
+pub mod inspect;
 pub mod perform;
 pub mod verify;
+pub mod verify_batch;
 
 pub use check::check;
 pub use create::create;
+pub use inspect::inspect;
 pub use verify::verify;
+pub use verify_batch::verify_batch;
 
 pub const CMD_FN: &[&str] = &["+create", "+verify"];
 // s390 branch
@@ -20,15 +23,18 @@ mod uv_cmd {
     pub const UV_CMD_FN: &[&str] = &["+perform"];
 }
 
-// non s390-branch
+// non s390-branch, only an Ultravisor backend can perform the attestation
 #[cfg(not(target_arch = "s390x"))]
 mod uv_cmd {
     use std::process::ExitCode;
 
     use anyhow::{bail, Result};
 
-    pub fn perform(_: &crate::cli::PerformAttOpt) -> Result<ExitCode> {
-        bail!("Command only available on s390x")
+    pub fn perform(opt: &crate::cli::PerformAttOpt) -> Result<ExitCode> {
+        if opt.uv_device.uv_backend.is_none() {
+            bail!("Command only available on s390x")
+        }
+        super::perform::perform(opt)
     }
     pub const UV_CMD_FN: &[&str] = &[];
 }
@@ -2,21 +2,27 @@
 //
 // Copyright IBM Corp. 2024
 
+#[cfg(feature = "network")]
 mod firmware;
 mod host_key;
 mod secret_store;
 
+#[cfg(feature = "network")]
+use self::firmware::firmware_check;
 use self::{
-    firmware::firmware_check,
     host_key::{host_key_check, HostKeyCheck},
     secret_store::secret_store_check,
     secret_store::SecretStoreCheck,
 };
-use crate::{additional::AttestationResult, cli::CheckOpt, exchange::ExchangeFormatResponse};
+use crate::{
+    additional::AttestationResult,
+    cli::{CheckOpt, CheckPolicyOpt},
+    exchange::ExchangeFormatResponse,
+};
 use anyhow::Result;
 use log::{debug, info, warn};
 use pv::{
-    attest::AttestationRequest,
+    attest::{challenge_user_data, AttestationRequest},
     misc::{create_file, open_file, read_file},
 };
 use serde::Serialize;
@@ -66,14 +72,15 @@ macro_rules! bail_check {
 }
 use bail_check;
 
-/// Check if the user-data matches with the user-data in the attestation response
+/// Check if the user-data or the challenge matches with the user-data in the attestation response
 fn user_data_check<'a>(
-    opt: &CheckOpt,
+    opt: &CheckPolicyOpt,
     att_res: &'a AttestationResult,
 ) -> Result<CheckState<HexSlice<'a>>> {
-    let user_data = match &opt.user_data {
-        Some(file) => read_file(file, "user-data")?,
-        None => return Ok(CheckState::None),
+    let user_data = match (&opt.user_data, &opt.challenge) {
+        (Some(file), _) => read_file(file, "user-data")?,
+        (None, Some(file)) => challenge_user_data(&read_file(file, "challenge")?)?.to_vec(),
+        (None, None) => return Ok(CheckState::None),
     };
 
     if Some(HexSlice::from(&user_data)) != att_res.user_data {
@@ -102,25 +109,52 @@ pub struct CheckResult<'a> {
     valid_firmware: Option<bool>,
 }
 
-/// Perform the policy checks
-pub fn check(opt: &CheckOpt) -> Result<ExitCode> {
-    let mut input = open_file(&opt.input)?;
-    let inp = ExchangeFormatResponse::read(&mut input)?;
-    let auth = AttestationRequest::auth_bin(inp.arcb())?;
-    let att_res = AttestationResult::from_exchange(&inp, auth.flags())?;
+impl CheckPolicyOpt {
+    /// Returns true if no policy is defined
+    pub fn is_empty(&self) -> bool {
+        self.host_key_documents.is_empty()
+            && self.key_hashes.is_none()
+            && self.user_data.is_none()
+            && self.challenge.is_none()
+            && self.secret_store_locked.is_none()
+            && !self.firmware
+    }
+}
+
+impl CheckResult<'_> {
+    /// Returns true if the attestation result fulfills all policies
+    pub const fn successful(&self) -> bool {
+        self.successful
+    }
+}
+
+/// The firmware check requires network access.
+#[cfg(not(feature = "network"))]
+fn firmware_check(opt: &CheckPolicyOpt, _att_res: &AttestationResult) -> Result<CheckState<()>> {
+    if opt.firmware {
+        anyhow::bail!("Cannot check the firmware: pvattest was built without network support");
+    }
+    Ok(None.into())
+}
+
+/// Check the attestation result against the policies
+pub fn check_policies<'a>(
+    opt: &'a CheckPolicyOpt,
+    att_res: &'a AttestationResult,
+) -> Result<CheckResult<'a>> {
     let mut issues = vec![];
 
-    let image_host_key = host_key_check(opt, host_key::HkCheck::Image, &att_res)?
+    let image_host_key = host_key_check(opt, host_key::HkCheck::Image, att_res)?
         .check(&mut issues)
         .unwrap();
-    let attest_host_key = host_key_check(opt, host_key::HkCheck::Attest, &att_res)?
+    let attest_host_key = host_key_check(opt, host_key::HkCheck::Attest, att_res)?
         .check(&mut issues)
         .unwrap();
 
-    let user_data = user_data_check(opt, &att_res)?.check(&mut issues);
-    let secret_store = secret_store_check(opt, &att_res)?.check(&mut issues);
+    let user_data = user_data_check(opt, att_res)?.check(&mut issues);
+    let secret_store = secret_store_check(opt, att_res)?.check(&mut issues);
 
-    let firmware_check = firmware_check(opt, &att_res)?;
+    let firmware_check = firmware_check(opt, att_res)?;
     let valid_firmware = match firmware_check {
         CheckState::None => None,
         CheckState::Data(_) => Some(true),
@@ -128,7 +162,7 @@ pub fn check(opt: &CheckOpt) -> Result<ExitCode> {
     };
     firmware_check.check(&mut issues);
 
-    let res = CheckResult {
+    Ok(CheckResult {
         successful: issues.is_empty(),
         issues,
         image_host_key,
@@ -136,7 +170,16 @@ pub fn check(opt: &CheckOpt) -> Result<ExitCode> {
         user_data,
         secret_store,
         valid_firmware,
-    };
+    })
+}
+
+/// Perform the policy checks
+pub fn check(opt: &CheckOpt) -> Result<ExitCode> {
+    let mut input = open_file(&opt.input)?;
+    let inp = ExchangeFormatResponse::read(&mut input)?;
+    let auth = AttestationRequest::auth_bin(inp.arcb())?;
+    let att_res = AttestationResult::from_exchange(&inp, auth.flags())?;
+    let res = check_policies(&opt.policies, &att_res)?;
 
     debug!("res {res:?}");
     let output = create_file(&opt.output)?;
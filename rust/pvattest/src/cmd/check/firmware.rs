@@ -11,7 +11,7 @@ use log::{debug, info, log_enabled};
 use serde::{Deserialize, Serialize};
 
 use super::{bail_check, CheckState};
-use crate::{additional::AttestationResult, cli::CheckOpt};
+use crate::{additional::AttestationResult, cli::CheckPolicyOpt};
 
 const CHECK_DEFAULT_ENDP: &str = "https://www.ibm.com/support/resourcelink/api";
 const VERIFY_API: &str = "firmware-attestation/verify/v1";
@@ -123,7 +123,7 @@ fn check<U: AsRef<[u8]>>(fw_hash: &U, endp: &str) -> Result<CheckState<()>> {
     Ok(CheckState::Data(()))
 }
 
-pub fn firmware_check(opt: &CheckOpt, att_res: &AttestationResult) -> Result<CheckState<()>> {
+pub fn firmware_check(opt: &CheckPolicyOpt, att_res: &AttestationResult) -> Result<CheckState<()>> {
     if !opt.firmware {
         return Ok(None.into());
     }
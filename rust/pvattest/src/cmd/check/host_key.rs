@@ -5,8 +5,9 @@
 use anyhow::Result;
 use log::{debug, info};
 use pv::{
-    misc::{read_certs, read_file},
-    request::{openssl::DigestBytes, EcPubKeyCoord},
+    misc::{open_file, read_certs, read_file},
+    request::EcPubKeyCoord,
+    uv::UvKeyHashesV1,
 };
 use serde::Serialize;
 use std::{fmt::Display, path::Path};
@@ -15,7 +16,7 @@ use utils::HexSlice;
 use super::CheckState;
 use crate::{
     additional::AttestationResult,
-    cli::{CheckOpt, HostKeyCheckPolicy},
+    cli::{CheckPolicyOpt, HostKeyCheckPolicy},
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -37,7 +38,7 @@ impl Display for HkCheck {
     }
 }
 
-fn load_host_keys<A: AsRef<Path>>(hkds: &[A]) -> Result<Vec<(&Path, DigestBytes)>> {
+fn load_host_keys<A: AsRef<Path>>(hkds: &[A]) -> Result<Vec<(&Path, Vec<u8>)>> {
     let mut hkd_hash = Vec::with_capacity(hkds.len());
     for hkd in hkds {
         let hkd = hkd.as_ref();
@@ -47,20 +48,36 @@ fn load_host_keys<A: AsRef<Path>>(hkds: &[A]) -> Result<Vec<(&Path, DigestBytes)
             source,
         })?;
         let ec_coord: EcPubKeyCoord = certs.first().unwrap().public_key()?.as_ref().try_into()?;
-        hkd_hash.push((hkd, ec_coord.sha256()?));
+        hkd_hash.push((hkd, ec_coord.sha256()?.to_vec()));
     }
     Ok(hkd_hash)
 }
 
+/// Load all distinct non-null host-key hashes from a file in the format of
+/// `/sys/firmware/uv/keys/all`
+fn load_key_hashes(path: &Path) -> Result<Vec<(&Path, Vec<u8>)>> {
+    let hashes = UvKeyHashesV1::read_from_io(open_file(path)?)?;
+    let mut key_hashes: Vec<_> = [&hashes.pchkh, &hashes.pbhkh]
+        .into_iter()
+        .chain(hashes.res.iter())
+        .map(|hash| hash.as_ref().to_vec())
+        .filter(|hash| hash.iter().any(|b| *b != 0))
+        .collect();
+    key_hashes.sort_unstable();
+    key_hashes.dedup();
+    Ok(key_hashes.into_iter().map(|hash| (path, hash)).collect())
+}
+
 fn contains_phkh<'a>(
-    hkd_hashes: &[(&'a Path, DigestBytes)],
+    hkd_hashes: &[(&'a Path, Vec<u8>)],
     phkh: &HexSlice<'_>,
     mode: HkCheck,
     check_enforced: bool,
+    source: &str,
 ) -> CheckState<HostKeyCheck<'a>> {
     let hk: Vec<_> = hkd_hashes
         .iter()
-        .filter_map(|(path, hash)| match hash.as_ref() == phkh.as_ref() {
+        .filter_map(|(path, hash)| match hash.as_slice() == phkh.as_ref() {
             true => Some(*path),
             false => None,
         })
@@ -68,13 +85,9 @@ fn contains_phkh<'a>(
 
     debug!("HK: {hk:?}");
     match hk.len() {
-        0 => CheckState::Err(format!(
-            "No given host-key document matches the given {mode}"
-        )),
+        0 => CheckState::Err(format!("No given {source} matches the given {mode}")),
         1 => CheckState::Data(HostKeyCheck::new(check_enforced, hk.first().copied())),
-        _ => CheckState::Err(format!(
-            "More than one host-key document matches the given {mode}"
-        )),
+        _ => CheckState::Err(format!("More than one {source} matches the given {mode}")),
     }
 }
 
@@ -99,11 +112,11 @@ impl<'a> HostKeyCheck<'a> {
 }
 
 pub fn host_key_check<'a, 'b>(
-    opt: &'a CheckOpt,
+    opt: &'a CheckPolicyOpt,
     kind: HkCheck,
     att_res: &'b AttestationResult<'b>,
 ) -> Result<CheckState<HostKeyCheck<'a>>> {
-    if opt.host_key_documents.is_empty() {
+    if opt.host_key_documents.is_empty() && opt.key_hashes.is_none() {
         return Ok(CheckState::Data(HostKeyCheck::default()));
     }
 
@@ -112,7 +125,13 @@ pub fn host_key_check<'a, 'b>(
         HkCheck::Attest => HostKeyCheckPolicy::AttKeyHash,
     });
 
-    let hkd_hashes = load_host_keys(&opt.host_key_documents)?;
+    let (hkd_hashes, source) = match &opt.key_hashes {
+        Some(path) => (load_key_hashes(path)?, "host-key hash"),
+        None => (
+            load_host_keys(&opt.host_key_documents)?,
+            "host-key document",
+        ),
+    };
 
     let res = match att_res
         .add_fields
@@ -121,7 +140,7 @@ pub fn host_key_check<'a, 'b>(
             HkCheck::Image => add_fields.image_public_host_key_hash(),
             HkCheck::Attest => add_fields.attestation_public_host_key_hash(),
         }) {
-        Some(phkh) => contains_phkh(&hkd_hashes, phkh, kind, check_enforced),
+        Some(phkh) => contains_phkh(&hkd_hashes, phkh, kind, check_enforced, source),
         None if check_enforced => CheckState::Err(format!(
             "The Attestation result does not contain an {}, but checking was enabled.",
             kind
@@ -135,8 +154,6 @@ pub fn host_key_check<'a, 'b>(
 
 #[cfg(test)]
 mod test {
-    use std::path::PathBuf;
-
     use super::*;
 
     #[test]
@@ -145,7 +162,13 @@ mod test {
             [concat!(env!("CARGO_MANIFEST_DIR"), "/tests/assets/host.pem.crt").to_string()];
         let hash = load_host_keys(&hostkey).unwrap();
 
-        let res = contains_phkh(&hash, &HexSlice::from(&[0; 32]), HkCheck::Image, true);
+        let res = contains_phkh(
+            &hash,
+            &HexSlice::from(&[0; 32]),
+            HkCheck::Image,
+            true,
+            "host-key document",
+        );
         assert!(matches!(res, CheckState::Err(_)));
     }
 
@@ -157,7 +180,13 @@ mod test {
         ];
         let hash = load_host_keys(&hostkey).unwrap();
 
-        let res = contains_phkh(&hash, &HexSlice::from(&hash[0].1), HkCheck::Image, true);
+        let res = contains_phkh(
+            &hash,
+            &HexSlice::from(&hash[0].1),
+            HkCheck::Image,
+            true,
+            "host-key document",
+        );
         assert!(matches!(res, CheckState::Err(_)));
     }
 
@@ -167,10 +196,51 @@ mod test {
             [concat!(env!("CARGO_MANIFEST_DIR"), "/tests/assets/host.pem.crt").to_string()];
         let hash = load_host_keys(&hostkey).unwrap();
 
-        let res = contains_phkh(&hash, &HexSlice::from(&hash[0].1), HkCheck::Image, true);
+        let res = contains_phkh(
+            &hash,
+            &HexSlice::from(&hash[0].1),
+            HkCheck::Image,
+            true,
+            "host-key document",
+        );
         assert!(matches!(
             res,
-            CheckState::Data(s) if s.hash.unwrap() == PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/assets/host.pem.crt"))
+            CheckState::Data(s) if s.hash.unwrap() == Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/assets/host.pem.crt"))
         ))
     }
+
+    #[test]
+    fn check_key_hashes() {
+        let key_hashes = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/assets/uv_keys_all"
+        ));
+        let hashes = load_key_hashes(key_hashes).unwrap();
+        assert_eq!(hashes.len(), 2);
+
+        let hostkey =
+            [concat!(env!("CARGO_MANIFEST_DIR"), "/tests/assets/host.pem.crt").to_string()];
+        let hkd_hash = load_host_keys(&hostkey).unwrap();
+
+        let res = contains_phkh(
+            &hashes,
+            &HexSlice::from(&hkd_hash[0].1),
+            HkCheck::Attest,
+            true,
+            "host-key hash",
+        );
+        assert!(matches!(
+            res,
+            CheckState::Data(s) if s.hash.unwrap() == key_hashes
+        ));
+
+        let res = contains_phkh(
+            &hashes,
+            &HexSlice::from(&[0; 32]),
+            HkCheck::Attest,
+            true,
+            "host-key hash",
+        );
+        assert!(matches!(res, CheckState::Err(_)));
+    }
 }
@@ -12,7 +12,7 @@ use pv::{misc::read_file, secret::AddSecretRequest};
 use serde::Serialize;
 
 use super::{bail_check, CheckState};
-use crate::{additional::AttestationResult, cli::CheckOpt};
+use crate::{additional::AttestationResult, cli::CheckPolicyOpt};
 
 #[derive(Debug, Serialize)]
 pub struct SecretStoreCheck<'a> {
@@ -34,7 +34,7 @@ fn secret_store_hash<A: AsRef<Path>>(asrcbs: &[A], locked: bool) -> Result<Diges
 }
 
 pub fn secret_store_check<'a>(
-    opt: &'a CheckOpt,
+    opt: &'a CheckPolicyOpt,
     att_res: &AttestationResult,
 ) -> Result<CheckState<SecretStoreCheck<'a>>> {
     // The locked flag is the feature gate of this check
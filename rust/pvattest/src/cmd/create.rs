@@ -4,16 +4,23 @@
 
 use crate::{
     cli::{AttAddFlags, CreateAttOpt},
-    exchange::{ExchangeFormatRequest, ExchangeFormatVersion},
+    exchange::{ExchangeFormatRequest, ExchangeMetadata},
 };
-use anyhow::{bail, Context, Result};
+use anyhow::{bail, Context, Error, Result};
+use clap::ValueEnum;
 use log::{debug, warn};
 use pv::{
     attest::{AttestationFlags, AttestationMeasAlg, AttestationRequest, AttestationVersion},
-    misc::{create_file, write_file},
-    request::{ReqEncrCtx, Request, SymKey, SymKeyType},
+    misc::{create_file, open_file, read_exact_file, read_file, write_file},
+    request::{
+        openssl::pkey::{PKey, Private},
+        BootHdrTags, ReqEncrCtx, Request, SymKey, SymKeyType,
+    },
+};
+use std::{
+    process::ExitCode,
+    time::{SystemTime, UNIX_EPOCH},
 };
-use std::process::ExitCode;
 
 fn flags(cli_flags: &[AttAddFlags]) -> AttestationFlags {
     let mut att_flags = AttestationFlags::default();
@@ -28,11 +35,46 @@ fn flags(cli_flags: &[AttAddFlags]) -> AttestationFlags {
     att_flags
 }
 
+fn read_private_key(buf: &[u8]) -> Result<PKey<Private>> {
+    PKey::private_key_from_der(buf)
+        .or_else(|_| PKey::private_key_from_pem(buf))
+        .map_err(Error::new)
+}
+
+fn metadata(opt: &CreateAttOpt) -> Result<ExchangeMetadata> {
+    let se_hdr_hash = match &opt.hdr {
+        Some(hdr) => {
+            let tags = BootHdrTags::from_se_image(&mut open_file(hdr)?)?;
+            Some(ExchangeMetadata::hash_se_hdr(&tags)?)
+        }
+        None => None,
+    };
+    Ok(ExchangeMetadata {
+        creation_time: Some(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs()),
+        tool_version: Some(format!(
+            "{} {}",
+            env!("CARGO_PKG_NAME"),
+            utils::release_string!()
+        )),
+        add_data: opt
+            .add_data
+            .iter()
+            .filter_map(|f| f.to_possible_value())
+            .map(|v| v.get_name().to_string())
+            .collect(),
+        se_hdr_hash,
+    })
+}
+
 pub fn create(opt: &CreateAttOpt) -> Result<ExitCode> {
     let att_version = AttestationVersion::One;
     let meas_alg = AttestationMeasAlg::HmacSha512;
 
     let mut arcb = AttestationRequest::new(att_version, meas_alg, flags(&opt.add_data))?;
+    if let Some(nonce) = &opt.nonce {
+        arcb.set_nonce(read_exact_file(nonce, "Attestation nonce")?);
+        debug!("Set the nonce from '{nonce}'");
+    }
     debug!("Generated Attestation request");
 
     // Add host-key documents
@@ -47,13 +89,23 @@ pub fn create(opt: &CreateAttOpt) -> Result<ExitCode> {
     let ser_arcb = arcb.encrypt(&encr_ctx)?;
     warn!("Successfully generated the request");
 
-    let mut output = create_file(&opt.output)?;
-    let exch_ctx = ExchangeFormatRequest::new(
+    let mut exch_ctx = ExchangeFormatRequest::new(
         ser_arcb,
         meas_alg.exp_size(),
         arcb.flags().expected_additional_size(),
     )?;
-    exch_ctx.write(&mut output, ExchangeFormatVersion::One)?;
+    if opt.metadata || opt.hdr.is_some() {
+        exch_ctx.set_metadata(&metadata(opt)?)?;
+        debug!("Added metadata");
+    }
+    if let Some(sign_key) = &opt.sign_key {
+        let skey = read_private_key(&read_file(sign_key, "request signing key")?)?;
+        exch_ctx.sign(&skey)?;
+        debug!("Signed the request with '{sign_key}'");
+    }
+
+    let mut output = create_file(&opt.output)?;
+    exch_ctx.write(&mut output, exch_ctx.min_version())?;
 
     let arpk = match encr_ctx.prot_key() {
         SymKey::Aes256(k) => k,
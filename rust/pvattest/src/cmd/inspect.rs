@@ -0,0 +1,227 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2024
+
+use std::{
+    fmt::Display,
+    io::{Seek, SeekFrom},
+    process::ExitCode,
+};
+
+use anyhow::Result;
+use clap::ValueEnum;
+use pv::{
+    attest::{AttestationFlags, AttestationMeasAlg, AttestationRequest},
+    misc::open_file,
+};
+use serde::Serialize;
+
+use crate::{
+    cli::{AttAddFlags, InspectOpt, InspectOutputType},
+    exchange::{ExchangeFormatRequest, ExchangeFormatResponse, ExchangeLayout, ExchangeMetadata},
+};
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum ExchangeKind {
+    Request,
+    Response,
+}
+
+#[derive(Serialize, Debug)]
+struct DataInfo {
+    present: bool,
+    size: u32,
+}
+
+impl DataInfo {
+    fn new(data: Option<&[u8]>) -> Self {
+        Self {
+            present: data.is_some(),
+            size: data.map_or(0, |d| d.len() as u32),
+        }
+    }
+
+    const fn expected(size: u32) -> Self {
+        Self {
+            present: false,
+            size,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct InspectResult {
+    kind: ExchangeKind,
+    layout: ExchangeLayout,
+    measurement_alg: &'static str,
+    flags: Vec<String>,
+    measurement: DataInfo,
+    additional_data: DataInfo,
+    user_data: DataInfo,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<ExchangeMetadata>,
+    signed: bool,
+}
+
+const fn meas_alg_name(mai: AttestationMeasAlg) -> &'static str {
+    match mai {
+        AttestationMeasAlg::HmacSha512 => "HMAC-SHA512",
+    }
+}
+
+/// Names of the requested additional data as used by 'pvattest create --add-data'
+fn flag_names(flags: &AttestationFlags) -> Vec<String> {
+    AttAddFlags::value_variants()
+        .iter()
+        .filter(|f| match f {
+            AttAddFlags::PhkhImg => flags.image_phkh(),
+            AttAddFlags::PhkhAtt => flags.attest_phkh(),
+            AttAddFlags::SecretStoreHash => flags.secret_store_hash(),
+            AttAddFlags::FirmwareState => flags.firmware_state(),
+        })
+        .filter_map(|f| f.to_possible_value())
+        .map(|v| v.get_name().to_string())
+        .collect()
+}
+
+impl Display for DataInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.present, self.size) {
+            (true, size) => write!(f, "present ({size} bytes)"),
+            (false, 0) => write!(f, "not present"),
+            (false, size) => write!(f, "not present (expected {size} bytes)"),
+        }
+    }
+}
+
+impl Display for InspectResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.kind {
+            ExchangeKind::Request => "Attestation request",
+            ExchangeKind::Response => "Attestation response",
+        };
+        writeln!(
+            f,
+            "{kind} (exchange format version {}, {} bytes)",
+            self.layout.version.number(),
+            self.layout.size
+        )?;
+        writeln!(f, "Entries:")?;
+        for entry in &self.layout.entries {
+            writeln!(
+                f,
+                "  {:<12} size: {:#07x} offset: {:#07x}",
+                entry.name, entry.size, entry.offset
+            )?;
+        }
+        writeln!(f, "Measurement algorithm: {}", self.measurement_alg)?;
+        match self.flags.is_empty() {
+            true => writeln!(f, "Requested additional data: none")?,
+            false => writeln!(f, "Requested additional data: {}", self.flags.join(", "))?,
+        }
+        writeln!(f, "Measurement: {}", self.measurement)?;
+        writeln!(f, "Additional data: {}", self.additional_data)?;
+        writeln!(f, "User data: {}", self.user_data)?;
+        if let Some(metadata) = &self.metadata {
+            writeln!(f, "Metadata:")?;
+            if let Some(time) = metadata.creation_time {
+                writeln!(f, "  Creation time: {time}")?;
+            }
+            if let Some(tool) = &metadata.tool_version {
+                writeln!(f, "  Created by: {tool}")?;
+            }
+            if !metadata.add_data.is_empty() {
+                writeln!(f, "  Additional data: {}", metadata.add_data.join(", "))?;
+            }
+            if let Some(hash) = &metadata.se_hdr_hash {
+                writeln!(f, "  SE header hash: {hash}")?;
+            }
+        }
+        write!(f, "Signed: {}", if self.signed { "yes" } else { "no" })
+    }
+}
+
+pub fn inspect(opt: &InspectOpt) -> Result<ExitCode> {
+    let mut input = open_file(&opt.input)?;
+    let layout = ExchangeLayout::read(&mut input)?;
+    input.seek(SeekFrom::Start(0))?;
+
+    let (kind, arcb, measurement, additional_data, user_data, metadata, signed) =
+        match layout.is_response() {
+            true => {
+                let resp = ExchangeFormatResponse::read(&mut input)?;
+                (
+                    ExchangeKind::Response,
+                    resp.arcb().to_vec(),
+                    DataInfo::new(Some(resp.measurement())),
+                    DataInfo::new(resp.additional()),
+                    DataInfo::new(resp.user()),
+                    resp.metadata()?,
+                    resp.is_signed(),
+                )
+            }
+            false => {
+                let req = ExchangeFormatRequest::read(&mut input)?;
+                (
+                    ExchangeKind::Request,
+                    req.arcb.clone(),
+                    DataInfo::expected(req.exp_measurement),
+                    DataInfo::expected(req.exp_additional),
+                    DataInfo::new(None),
+                    req.metadata()?,
+                    req.is_signed(),
+                )
+            }
+        };
+    let auth = AttestationRequest::auth_bin(&arcb)?;
+
+    let res = InspectResult {
+        kind,
+        layout,
+        measurement_alg: meas_alg_name(auth.mai()),
+        flags: flag_names(auth.flags()),
+        measurement,
+        additional_data,
+        user_data,
+        metadata,
+        signed,
+    };
+
+    match opt.format {
+        InspectOutputType::Human => println!("{res}"),
+        InspectOutputType::Yaml => serde_yaml::to_writer(std::io::stdout(), &res)?,
+        InspectOutputType::Json => {
+            serde_json::to_writer_pretty(std::io::stdout(), &res)?;
+            println!();
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn flags() {
+        let mut flags = AttestationFlags::default();
+        assert!(flag_names(&flags).is_empty());
+        flags.set_image_phkh();
+        flags.set_firmware_state();
+        assert_eq!(flag_names(&flags), ["phkh-img", "firmware-state"]);
+    }
+
+    #[test]
+    fn data_info() {
+        assert_eq!(
+            DataInfo::new(Some(&[0; 3])).to_string(),
+            "present (3 bytes)"
+        );
+        assert_eq!(DataInfo::new(None).to_string(), "not present");
+        assert_eq!(
+            DataInfo::expected(64).to_string(),
+            "not present (expected 64 bytes)"
+        );
+    }
+}
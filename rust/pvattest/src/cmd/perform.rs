@@ -4,12 +4,13 @@
 
 use crate::{
     cli::PerformAttOptComb,
-    exchange::{ExchangeFormatRequest, ExchangeFormatResponse, ExchangeFormatVersion},
+    exchange::{ExchangeFormatRequest, ExchangeFormatResponse},
 };
 use anyhow::Result;
 use pv::{
+    attest::challenge_user_data,
     misc::{create_file, open_file, read_file},
-    uv::{AttestationCmd, UvDevice},
+    uv::AttestationCmd,
 };
 use std::process::ExitCode;
 
@@ -20,13 +21,14 @@ where
     let opt = opt.into();
     let mut input = open_file(opt.input)?;
     let mut output = create_file(opt.output)?;
-    let uvdevice = UvDevice::open()?;
+    let uvdevice = opt.uv_device.open()?;
 
     let ex_in = ExchangeFormatRequest::read(&mut input)?;
-    let user_data = opt
-        .user_data
-        .map(|u| read_file(u, "user-data"))
-        .transpose()?;
+    let user_data = match (opt.user_data, opt.challenge) {
+        (Some(u), _) => Some(read_file(u, "user-data")?),
+        (None, Some(c)) => Some(challenge_user_data(&read_file(c, "challenge")?)?.to_vec()),
+        (None, None) => None,
+    };
 
     let mut cmd = AttestationCmd::new_request(
         ex_in.arcb.clone().into(),
@@ -41,14 +43,16 @@ where
     let additional = cmd.additional_owned();
     let cuid = cmd.cuid();
 
-    let ex_out = ExchangeFormatResponse::new(
+    let mut ex_out = ExchangeFormatResponse::new(
         ex_in.arcb,
         measurement.to_owned(),
         additional,
         user_data,
         cuid.to_owned(),
     )?;
-    ex_out.write(&mut output, ExchangeFormatVersion::One)?;
+    // Pass metadata and signature of the request creator to the verifier
+    ex_out.creator = ex_in.creator;
+    ex_out.write(&mut output, ex_out.min_version())?;
 
     Ok(ExitCode::SUCCESS)
 }
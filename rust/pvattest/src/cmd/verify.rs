@@ -2,12 +2,17 @@
 //
 // Copyright IBM Corp. 2024
 
-use anyhow::Result;
+use anyhow::{Error, Result};
 use log::{debug, warn};
 use pv::{
-    attest::{AttestationItems, AttestationMeasurement, AttestationRequest},
-    misc::{create_file, open_file, read_exact_file, write_file},
-    request::{openssl::pkey::PKey, BootHdrTags, Confidential, SymKey},
+    attest::{
+        AttestationAuthenticated, AttestationItems, AttestationMeasurement, AttestationRequest,
+    },
+    misc::{create_file, open_file, read_exact_file, read_file, write_file},
+    request::{
+        openssl::pkey::{PKey, PKeyRef, Public},
+        BootHdrTags, Confidential, SymKey,
+    },
 };
 use std::process::ExitCode;
 use utils::HexSlice;
@@ -15,24 +20,65 @@ use utils::HexSlice;
 use crate::{
     additional::AttestationResult,
     cli::{OutputType, VerifyOpt},
-    exchange::ExchangeFormatResponse,
+    exchange::{ExchangeFormatResponse, ExchangeMetadata},
     EXIT_CODE_ATTESTATION_FAIL,
 };
 
-pub fn verify(opt: &VerifyOpt) -> Result<ExitCode> {
-    let mut input = open_file(&opt.input)?;
-    let mut img = open_file(&opt.hdr)?;
-    let output = opt.output.as_ref().map(create_file).transpose()?;
-    let arpk = SymKey::Aes256(
-        read_exact_file(&opt.arpk, "Attestation request protection key").map(Confidential::new)?,
-    );
-    let tags = BootHdrTags::from_se_image(&mut img)?;
-    let exchange = ExchangeFormatResponse::read(&mut input)?;
+pub fn read_public_key(buf: &[u8]) -> Result<PKey<Public>> {
+    PKey::public_key_from_der(buf)
+        .or_else(|_| PKey::public_key_from_pem(buf))
+        .map_err(Error::new)
+}
+
+/// Result of the verification of an attestation response
+pub enum Verification {
+    /// The measurement is verified, contains the authenticated part of the request
+    Verified(AttestationAuthenticated),
+    /// The verification failed for the given reason
+    Failed(String),
+}
+
+/// Verify the attestation measurement of the exchange response
+///
+/// Optionally checks the nonce of the request and the signature of the request creator.
+/// Returns an error if the input is malformed and [`Verification::Failed`] if the attestation
+/// could not be verified.
+pub fn verify_response(
+    exchange: &ExchangeFormatResponse,
+    tags: &BootHdrTags,
+    arpk: &SymKey,
+    expect_nonce: Option<&[u8; 16]>,
+    signer_key: Option<&PKeyRef<Public>>,
+) -> Result<Verification> {
+    if let Some(pkey) = signer_key {
+        match exchange.verify_signature(pkey) {
+            Ok(true) => debug!("Request signature verified"),
+            Ok(false) => {
+                return Ok(Verification::Failed("Request signature verification failed. The signature does not match the signer key.".to_string()));
+            }
+            Err(e) => {
+                return Ok(Verification::Failed(format!(
+                    "Request signature verification failed: {e:#}"
+                )));
+            }
+        }
+    }
+    if let Some(hdr_hash) = exchange.metadata()?.and_then(|m| m.se_hdr_hash) {
+        if hdr_hash != ExchangeMetadata::hash_se_hdr(tags)? {
+            warn!("The request was created for a different SE header");
+        }
+    }
 
-    let (auth, conf) = AttestationRequest::decrypt_bin(exchange.arcb(), &arpk)?;
+    let (auth, conf) = AttestationRequest::decrypt_bin(exchange.arcb(), arpk)?;
+    if let Some(exp) = expect_nonce {
+        if conf.nonce().as_ref().map(|n| n.value()) != Some(exp) {
+            return Ok(Verification::Failed("Attestation nonce verification failed. The request does not contain the expected nonce.".to_string()));
+        }
+        debug!("Attestation nonce matches the expected nonce");
+    }
     let meas_key = PKey::hmac(conf.measurement_key())?;
     let items = AttestationItems::new(
-        &tags,
+        tags,
         exchange.config_uid(),
         exchange.user(),
         conf.nonce().as_ref().map(|v| v.value()),
@@ -46,9 +92,44 @@ pub fn verify(opt: &VerifyOpt) -> Result<ExitCode> {
         debug!("Measurement values:");
         debug!("Recieved: {}", HexSlice::from(uv_meas));
         debug!("Calculated: {}", HexSlice::from(measurement.as_ref()));
-        warn!("Attestation measurement verification failed. Calculated and received attestation measurement are not equal.");
-        return Ok(ExitCode::from(EXIT_CODE_ATTESTATION_FAIL));
+        return Ok(Verification::Failed("Attestation measurement verification failed. Calculated and received attestation measurement are not equal.".to_string()));
     }
+    Ok(Verification::Verified(auth))
+}
+
+pub fn verify(opt: &VerifyOpt) -> Result<ExitCode> {
+    let mut input = open_file(&opt.input)?;
+    let mut img = open_file(&opt.hdr)?;
+    let output = opt.output.as_ref().map(create_file).transpose()?;
+    let arpk = SymKey::Aes256(
+        read_exact_file(&opt.arpk, "Attestation request protection key").map(Confidential::new)?,
+    );
+    let tags = BootHdrTags::from_se_image(&mut img)?;
+    let exchange = ExchangeFormatResponse::read(&mut input)?;
+    let expect_nonce: Option<[u8; 16]> = opt
+        .expect_nonce
+        .as_ref()
+        .map(|f| read_exact_file(f, "Attestation nonce"))
+        .transpose()?;
+    let signer_key = opt
+        .signer_key
+        .as_ref()
+        .map(|f| read_public_key(&read_file(f, "signer key")?))
+        .transpose()?;
+
+    let auth = match verify_response(
+        &exchange,
+        &tags,
+        &arpk,
+        expect_nonce.as_ref(),
+        signer_key.as_deref(),
+    )? {
+        Verification::Verified(auth) => auth,
+        Verification::Failed(reason) => {
+            warn!("{reason}");
+            return Ok(ExitCode::from(EXIT_CODE_ATTESTATION_FAIL));
+        }
+    };
     warn!("Attestation measurement verified");
     // Error impossible CUID is present Attestation verified
     let pr_data = AttestationResult::from_exchange(&exchange, auth.flags())?;
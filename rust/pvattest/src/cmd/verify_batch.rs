@@ -0,0 +1,279 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2024
+
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use anyhow::{Context, Result};
+use log::{debug, info, warn};
+use pv::{
+    misc::{open_file, read_exact_file, read_file},
+    request::{
+        openssl::pkey::{PKeyRef, Public},
+        BootHdrTags, Confidential, SymKey,
+    },
+};
+use serde::{Deserialize, Serialize};
+use utils::{get_writer_from_cli_file_arg, STDOUT};
+
+use super::{
+    check::check_policies,
+    verify::{read_public_key, verify_response, Verification},
+};
+use crate::{
+    additional::AttestationResult,
+    cli::{CheckPolicyOpt, OutputType, VerifyBatchOpt},
+    exchange::ExchangeFormatResponse,
+    EXIT_CODE_ATTESTATION_FAIL,
+};
+
+/// One attestation response to verify
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct ManifestEntry {
+    name: Option<String>,
+    response: PathBuf,
+    hdr: PathBuf,
+    arpk: PathBuf,
+    expect_nonce: Option<PathBuf>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+enum ItemStatus {
+    /// Measurement verified and all policies fulfilled
+    Verified,
+    /// Measurement or policies could not be verified
+    Failed,
+    /// The input could not be processed
+    Error,
+}
+
+impl ItemStatus {
+    const fn exit_code(self) -> u8 {
+        match self {
+            Self::Verified => 0,
+            Self::Failed => EXIT_CODE_ATTESTATION_FAIL,
+            Self::Error => 1,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ItemReport {
+    name: String,
+    status: ItemStatus,
+    exit_code: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<serde_yaml::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    check: Option<serde_yaml::Value>,
+}
+
+impl ItemReport {
+    fn new(name: String, status: ItemStatus, reason: Option<String>) -> Self {
+        Self {
+            name,
+            status,
+            exit_code: status.exit_code(),
+            reason,
+            result: None,
+            check: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct BatchReport {
+    total: usize,
+    verified: usize,
+    failed: usize,
+    errors: usize,
+    items: Vec<ItemReport>,
+}
+
+impl BatchReport {
+    fn new(items: Vec<ItemReport>) -> Self {
+        let count = |s| items.iter().filter(|i| i.status == s).count();
+        Self {
+            total: items.len(),
+            verified: count(ItemStatus::Verified),
+            failed: count(ItemStatus::Failed),
+            errors: count(ItemStatus::Error),
+            items,
+        }
+    }
+}
+
+fn read_manifest(path: &Path) -> Result<Vec<ManifestEntry>> {
+    let manifest = read_file(path, "manifest")?;
+    serde_yaml::from_slice(&manifest)
+        .with_context(|| format!("Invalid manifest '{}'", path.display()))
+}
+
+/// Verify a single manifest entry
+///
+/// The SE header tags are parsed only once per header file.
+fn verify_entry(
+    entry: &ManifestEntry,
+    dir: &Path,
+    tags_cache: &mut HashMap<PathBuf, BootHdrTags>,
+    signer_key: Option<&PKeyRef<Public>>,
+    policies: &CheckPolicyOpt,
+) -> Result<ItemReport> {
+    let name = entry
+        .name
+        .clone()
+        .unwrap_or_else(|| entry.response.display().to_string());
+
+    let tags = match tags_cache.entry(dir.join(&entry.hdr)) {
+        Entry::Occupied(e) => e.into_mut(),
+        Entry::Vacant(e) => {
+            let tags = BootHdrTags::from_se_image(&mut open_file(e.key())?)?;
+            debug!("Parsed SE header '{}'", e.key().display());
+            e.insert(tags)
+        }
+    };
+    let arpk = SymKey::Aes256(
+        read_exact_file(dir.join(&entry.arpk), "Attestation request protection key")
+            .map(Confidential::new)?,
+    );
+    let exchange = ExchangeFormatResponse::read(&mut open_file(dir.join(&entry.response))?)?;
+    let expect_nonce: Option<[u8; 16]> = entry
+        .expect_nonce
+        .as_ref()
+        .map(|f| read_exact_file(dir.join(f), "Attestation nonce"))
+        .transpose()?;
+
+    let auth = match verify_response(&exchange, tags, &arpk, expect_nonce.as_ref(), signer_key)? {
+        Verification::Verified(auth) => auth,
+        Verification::Failed(reason) => {
+            return Ok(ItemReport::new(name, ItemStatus::Failed, Some(reason)))
+        }
+    };
+
+    let att_res = AttestationResult::from_exchange(&exchange, auth.flags())?;
+    let mut report = ItemReport::new(name, ItemStatus::Verified, None);
+    report.result = Some(serde_yaml::to_value(&att_res)?);
+    if !policies.is_empty() {
+        let check = check_policies(policies, &att_res)?;
+        if !check.successful() {
+            report.status = ItemStatus::Failed;
+            report.exit_code = ItemStatus::Failed.exit_code();
+            report.reason =
+                Some("The Attestation response does not fulfill all policies".to_string());
+        }
+        report.check = Some(serde_yaml::to_value(&check)?);
+    }
+    Ok(report)
+}
+
+/// Verify all attestation responses of the manifest
+pub fn verify_batch(opt: &VerifyBatchOpt) -> Result<ExitCode> {
+    let entries = read_manifest(&opt.manifest)?;
+    let dir = opt.manifest.parent().unwrap_or(Path::new(""));
+    let signer_key = opt
+        .signer_key
+        .as_ref()
+        .map(|f| read_public_key(&read_file(f, "signer key")?))
+        .transpose()?;
+
+    let mut tags_cache = HashMap::new();
+    let items: Vec<_> = entries
+        .iter()
+        .map(|entry| {
+            let report = verify_entry(
+                entry,
+                dir,
+                &mut tags_cache,
+                signer_key.as_deref(),
+                &opt.policies,
+            )
+            .unwrap_or_else(|e| {
+                let name = entry
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| entry.response.display().to_string());
+                ItemReport::new(name, ItemStatus::Error, Some(format!("{e:#}")))
+            });
+            match (report.status, &report.reason) {
+                (ItemStatus::Verified, _) => info!("✓ {}", report.name),
+                (_, Some(reason)) => warn!("✘ {}: {reason}", report.name),
+                (_, None) => warn!("✘ {}", report.name),
+            }
+            report
+        })
+        .collect();
+
+    let report = BatchReport::new(items);
+    let output = opt.output.as_deref().unwrap_or(Path::new(STDOUT));
+    let mut output = get_writer_from_cli_file_arg(output)?;
+    match opt.format {
+        OutputType::Yaml => serde_yaml::to_writer(&mut output, &report)?,
+    };
+
+    warn!(
+        "Verified {} of {} attestation responses",
+        report.verified, report.total
+    );
+    Ok(match (report.errors, report.failed) {
+        (0, 0) => ExitCode::SUCCESS,
+        (0, _) => ExitCode::from(EXIT_CODE_ATTESTATION_FAIL),
+        _ => ExitCode::FAILURE,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn manifest() {
+        let manifest = r#"
+- name: guest1
+  response: guest1/resp.bin
+  hdr: se.hdr
+  arpk: guest1/arpk
+  expect-nonce: guest1/nonce
+- response: guest2/resp.bin
+  hdr: se.hdr
+  arpk: guest2/arpk
+"#;
+        let entries: Vec<ManifestEntry> = serde_yaml::from_str(manifest).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name.as_deref(), Some("guest1"));
+        assert_eq!(
+            entries[0].expect_nonce.as_deref(),
+            Some(Path::new("guest1/nonce"))
+        );
+        assert_eq!(entries[1].name, None);
+
+        let json = r#"[{"response": "r", "hdr": "h", "arpk": "a"}]"#;
+        let entries: Vec<ManifestEntry> = serde_yaml::from_str(json).unwrap();
+        assert_eq!(entries[0].hdr, Path::new("h"));
+
+        let unknown = r#"[{"response": "r", "hdr": "h", "arpk": "a", "key": "k"}]"#;
+        serde_yaml::from_str::<Vec<ManifestEntry>>(unknown).unwrap_err();
+    }
+
+    #[test]
+    fn report() {
+        let report = BatchReport::new(vec![
+            ItemReport::new("a".to_string(), ItemStatus::Verified, None),
+            ItemReport::new("b".to_string(), ItemStatus::Failed, Some("x".to_string())),
+            ItemReport::new("c".to_string(), ItemStatus::Error, Some("y".to_string())),
+            ItemReport::new("d".to_string(), ItemStatus::Verified, None),
+        ]);
+        assert_eq!(report.total, 4);
+        assert_eq!(report.verified, 2);
+        assert_eq!(report.failed, 1);
+        assert_eq!(report.errors, 1);
+        assert_eq!(report.items[1].exit_code, EXIT_CODE_ATTESTATION_FAIL);
+    }
+}
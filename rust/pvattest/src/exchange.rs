@@ -2,7 +2,20 @@
 //
 // Copyright IBM Corp. 2024
 use anyhow::{anyhow, bail, Error, Result};
-use pv::{assert_size, request::MagicValue, uv::AttestationCmd, uv::ConfigUid};
+use openssl::{
+    hash::{hash, MessageDigest},
+    pkey::{HasPublic, Id, PKeyRef, Private},
+    rsa::Padding,
+    sign::{Signer, Verifier},
+};
+use pv::{
+    assert_size,
+    misc::encode_hex,
+    request::{BootHdrTags, MagicValue},
+    uv::AttestationCmd,
+    uv::ConfigUid,
+};
+use serde::{Deserialize, Serialize};
 use std::{
     io::{ErrorKind, Read, Seek, SeekFrom, Write},
     mem::size_of,
@@ -73,6 +86,15 @@ impl Entry {
             }
         }
     }
+
+    /// Reads data from stream if present and not larger than `max_size`
+    fn read_max<R>(&self, reader: &mut R, max_size: u32, field: &'static str) -> Result<ExpOrData>
+    where
+        R: Read + Seek,
+    {
+        verify_size(self.size.get(), 0, max_size, field)?;
+        self.read(reader)
+    }
 }
 
 #[repr(C)]
@@ -92,8 +114,13 @@ struct ExchangeFormatV1Hdr {
 assert_size!(ExchangeFormatV1Hdr, 0x40);
 
 impl ExchangeFormatV1Hdr {
-    fn new_request(arcb: &[u8], measurement: u32, additional: u32) -> Result<Self> {
-        let mut offset: u32 = size_of::<Self>() as u32;
+    fn new_request(
+        version: ExchangeFormatVersion,
+        arcb: &[u8],
+        measurement: u32,
+        additional: u32,
+    ) -> Result<Self> {
+        let mut offset: u32 = version.hdr_size();
         let arcb_entry = Entry::from_slice(Some(arcb), AttestationCmd::ARCB_MAX_SIZE, &mut offset);
         let measurement_entry = Entry::from_exp(Some(measurement));
         let exp_add = match additional {
@@ -107,7 +134,7 @@ impl ExchangeFormatV1Hdr {
 
         Ok(Self {
             magic: U64::from_bytes(ExchangeMagic::MAGIC),
-            version: ExchangeFormatVersion::One.into(),
+            version: version.into(),
             size: offset.into(),
             reserved: 0.into(),
             arcb: arcb_entry,
@@ -119,13 +146,14 @@ impl ExchangeFormatV1Hdr {
     }
 
     fn new_response(
+        version: ExchangeFormatVersion,
         arcb: &[u8],
         measurement: &[u8],
         additional: Option<&[u8]>,
         user: Option<&[u8]>,
         config_uid: &[u8],
     ) -> Result<Self> {
-        let mut offset: u32 = size_of::<Self>() as u32;
+        let mut offset: u32 = version.hdr_size();
         let arcb_entry = Entry::from_slice(Some(arcb), AttestationCmd::ARCB_MAX_SIZE, &mut offset);
         let measurement_entry = Entry::from_slice(
             Some(measurement),
@@ -139,7 +167,7 @@ impl ExchangeFormatV1Hdr {
 
         Ok(Self {
             magic: U64::from_bytes(ExchangeMagic::MAGIC),
-            version: ExchangeFormatVersion::One.into(),
+            version: version.into(),
             size: offset.into(),
             reserved: 0.into(),
             arcb: arcb_entry,
@@ -149,8 +177,103 @@ impl ExchangeFormatV1Hdr {
             config_uid: cuid_entry,
         })
     }
+
+    /// Reads the header and, for version 2, the header extension from the stream
+    ///
+    /// Leaves the stream at an unspecified position.
+    fn read<R>(reader: &mut R) -> Result<(Self, Option<ExchangeFormatV2Ext>)>
+    where
+        R: Read + Seek,
+    {
+        let mut buf = vec![0; size_of::<Self>()];
+        match reader.read_exact(&mut buf) {
+            Ok(it) => it,
+            // report hdr file to small for header
+            Err(err) if err.kind() == ErrorKind::UnexpectedEof => {
+                bail!("{INV_EXCHANGE_FMT_ERROR_TEXT} Invalid Header.");
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        if !ExchangeMagic::starts_with_magic(&buf) {
+            bail!("{INV_EXCHANGE_FMT_ERROR_TEXT} Does not start with the magic value.");
+        }
+
+        let hdr = Self::read_from_bytes(buf.as_slice())
+            .map_err(|_| anyhow!("{INV_EXCHANGE_FMT_ERROR_TEXT} Invalid Header."))?;
+
+        let ext = match TryInto::<ExchangeFormatVersion>::try_into(hdr.version)? {
+            ExchangeFormatVersion::One => None,
+            ExchangeFormatVersion::Two => {
+                let mut buf = vec![0; size_of::<ExchangeFormatV2Ext>()];
+                match reader.read_exact(&mut buf) {
+                    Ok(it) => it,
+                    Err(err) if err.kind() == ErrorKind::UnexpectedEof => {
+                        bail!("{INV_EXCHANGE_FMT_ERROR_TEXT} Invalid Header.");
+                    }
+                    Err(err) => return Err(err.into()),
+                };
+                Some(
+                    ExchangeFormatV2Ext::read_from_bytes(buf.as_slice())
+                        .map_err(|_| anyhow!("{INV_EXCHANGE_FMT_ERROR_TEXT} Invalid Header."))?,
+                )
+            }
+        };
+
+        if stream_len(reader)? < hdr.size.get() as u64 {
+            bail!("{INV_EXCHANGE_FMT_ERROR_TEXT} File size too small");
+        }
+        Ok((hdr, ext))
+    }
+}
+
+/// Header extension of the exchange format version 2
+///
+/// Directly follows the version 1 header. The offsets of all entries of a version 2 file are
+/// relative to the start of the file, as for version 1.
+#[repr(C)]
+#[derive(Debug, IntoBytes, FromBytes, Immutable, KnownLayout)]
+struct ExchangeFormatV2Ext {
+    metadata: Entry,
+    signature: Entry,
+}
+assert_size!(ExchangeFormatV2Ext, 0x10);
+
+impl ExchangeFormatV2Ext {
+    fn new(creator: &CreatorData, offset: &mut u32) -> Self {
+        Self {
+            metadata: Entry::from_slice(creator.metadata.as_deref(), METADATA_MAX_SIZE, offset),
+            signature: Entry::from_slice(creator.signature.as_deref(), SIGNATURE_MAX_SIZE, offset),
+        }
+    }
+
+    fn read<R>(&self, reader: &mut R) -> Result<CreatorData>
+    where
+        R: Read + Seek,
+    {
+        let metadata = self
+            .metadata
+            .read_max(reader, METADATA_MAX_SIZE, "Metadata")?
+            .data();
+        if let Some(metadata) = &metadata {
+            ExchangeMetadata::from_slice(metadata)?;
+        }
+        let signature = self
+            .signature
+            .read_max(reader, SIGNATURE_MAX_SIZE, "Signature")?
+            .data();
+        Ok(CreatorData {
+            metadata,
+            signature,
+        })
+    }
 }
 
+/// Maximum size of the serialized metadata of an exchange file
+const METADATA_MAX_SIZE: u32 = 0x1000;
+/// Maximum size of the signature of an exchange file
+const SIGNATURE_MAX_SIZE: u32 = 0x1000;
+
 /// The magic value used to identify an [`ExchangeFormatRequest`]
 ///
 /// The magic value is ASCII:
@@ -175,19 +298,40 @@ impl MagicValue<8> for ExchangeMagic {
 pub enum ExchangeFormatVersion {
     /// Version 1 (= 0x0100)
     One = 0x0100,
+    /// Version 2 (= 0x0200)
+    ///
+    /// Adds optional metadata and an optional signature of the creator of the request.
+    Two = 0x0200,
+}
+
+impl ExchangeFormatVersion {
+    /// Human readable version number
+    pub const fn number(self) -> u32 {
+        match self {
+            Self::One => 1,
+            Self::Two => 2,
+        }
+    }
+
+    /// Size of the header of this version
+    const fn hdr_size(self) -> u32 {
+        match self {
+            Self::One => size_of::<ExchangeFormatV1Hdr>() as u32,
+            Self::Two => {
+                (size_of::<ExchangeFormatV1Hdr>() + size_of::<ExchangeFormatV2Ext>()) as u32
+            }
+        }
+    }
 }
 
 impl<E: ByteOrder> TryFrom<U32<E>> for ExchangeFormatVersion {
     type Error = Error;
 
     fn try_from(value: U32<E>) -> Result<Self, Self::Error> {
-        if value.get() == Self::One as u32 {
-            Ok(Self::One)
-        } else {
-            bail!(
-                "{INV_EXCHANGE_FMT_ERROR_TEXT} Unsupported version: ({})",
-                value.get()
-            );
+        match value.get() {
+            v if v == Self::One as u32 => Ok(Self::One),
+            v if v == Self::Two as u32 => Ok(Self::Two),
+            v => bail!("{INV_EXCHANGE_FMT_ERROR_TEXT} Unsupported version: ({v})"),
         }
     }
 }
@@ -255,6 +399,205 @@ impl From<ExpOrData> for Option<Vec<u8>> {
     }
 }
 
+/// Metadata of an exchange file
+///
+/// Describes the circumstances under which an attestation request was created. Requires
+/// [`ExchangeFormatVersion::Two`]. Stored as JSON object, unknown fields are ignored when
+/// reading.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExchangeMetadata {
+    /// Creation time of the request in seconds since the Unix epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub creation_time: Option<u64>,
+    /// Name and version of the tool that created the request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_version: Option<String>,
+    /// Additional data requested by the request
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub add_data: Vec<String>,
+    /// Hex encoded SHA-256 hash of the tags of the SE header the request was created for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub se_hdr_hash: Option<String>,
+}
+
+impl ExchangeMetadata {
+    /// Calculates the value for [`ExchangeMetadata::se_hdr_hash`] for the given SE header tags
+    pub fn hash_se_hdr(tags: &BootHdrTags) -> Result<String> {
+        Ok(encode_hex(hash(MessageDigest::sha256(), tags.as_ref())?))
+    }
+
+    fn from_slice(data: &[u8]) -> Result<Self> {
+        serde_json::from_slice(data)
+            .map_err(|e| anyhow!("{INV_EXCHANGE_FMT_ERROR_TEXT} Invalid metadata: {e}"))
+    }
+}
+
+/// Data of the creator of an attestation request
+///
+/// Passed unchanged from the request to the response, so that the verifier can check it.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CreatorData {
+    /// serialized [`ExchangeMetadata`]
+    metadata: Option<Vec<u8>>,
+    signature: Option<Vec<u8>>,
+}
+
+impl CreatorData {
+    /// Returns the minimum exchange format version that can hold this data.
+    const fn min_version(&self) -> ExchangeFormatVersion {
+        match (&self.metadata, &self.signature) {
+            (None, None) => ExchangeFormatVersion::One,
+            _ => ExchangeFormatVersion::Two,
+        }
+    }
+
+    /// The data covered by the signature
+    ///
+    /// ```none
+    /// signed data = attestation request || serialized metadata (if any)
+    /// ```
+    fn signed_data(&self, arcb: &[u8]) -> Vec<u8> {
+        let metadata = self.metadata.as_deref().unwrap_or_default();
+        let mut data = Vec::with_capacity(arcb.len() + metadata.len());
+        data.extend_from_slice(arcb);
+        data.extend_from_slice(metadata);
+        data
+    }
+
+    fn set_metadata(&mut self, metadata: &ExchangeMetadata) -> Result<()> {
+        let metadata = serde_json::to_vec(metadata)?;
+        verify_slice(&metadata, METADATA_MAX_SIZE, "Metadata")?;
+        self.metadata = Some(metadata);
+        // An existing signature does not cover the new metadata
+        self.signature = None;
+        Ok(())
+    }
+
+    fn metadata(&self) -> Result<Option<ExchangeMetadata>> {
+        self.metadata
+            .as_deref()
+            .map(ExchangeMetadata::from_slice)
+            .transpose()
+    }
+
+    fn sign(&mut self, arcb: &[u8], skey: &PKeyRef<Private>) -> Result<()> {
+        if !matches!(skey.id(), Id::EC | Id::RSA) {
+            bail!("Signing is only supported for EC and RSA keys");
+        }
+        let mut signer = Signer::new(MessageDigest::sha512(), skey)?;
+        if skey.id() == Id::RSA {
+            signer.set_rsa_padding(Padding::PKCS1_PSS)?;
+        }
+        let signature = signer.sign_oneshot_to_vec(&self.signed_data(arcb))?;
+        verify_slice(&signature, SIGNATURE_MAX_SIZE, "Signature")?;
+        self.signature = Some(signature);
+        Ok(())
+    }
+
+    fn verify_signature<T: HasPublic>(&self, arcb: &[u8], pkey: &PKeyRef<T>) -> Result<bool> {
+        let signature = self
+            .signature
+            .as_deref()
+            .ok_or(anyhow!("The attestation request is not signed"))?;
+        if !matches!(pkey.id(), Id::EC | Id::RSA) {
+            bail!("Verifying signatures is only supported for EC and RSA keys");
+        }
+        let mut verifier = Verifier::new(MessageDigest::sha512(), pkey)?;
+        if pkey.id() == Id::RSA {
+            verifier.set_rsa_padding(Padding::PKCS1_PSS)?;
+        }
+        Ok(verifier.verify_oneshot(signature, &self.signed_data(arcb))?)
+    }
+
+    fn write<W>(&self, writer: &mut W) -> Result<()>
+    where
+        W: Write,
+    {
+        if let Some(data) = &self.metadata {
+            writer.write_all(data)?;
+        }
+        if let Some(data) = &self.signature {
+            writer.write_all(data)?;
+        }
+        Ok(())
+    }
+}
+
+/// Position of a labeled blob in an exchange file
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct EntryLayout {
+    pub name: &'static str,
+    pub size: u32,
+    pub offset: u32,
+}
+
+impl EntryLayout {
+    fn new(name: &'static str, entry: &Entry) -> Self {
+        Self {
+            name,
+            size: entry.size.get(),
+            offset: entry.offset.get(),
+        }
+    }
+}
+
+/// Layout of an exchange file as described by its header
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct ExchangeLayout {
+    #[serde(serialize_with = "ser_version")]
+    pub version: ExchangeFormatVersion,
+    pub size: u32,
+    pub entries: Vec<EntryLayout>,
+}
+
+fn ser_version<S: serde::Serializer>(
+    version: &ExchangeFormatVersion,
+    ser: S,
+) -> Result<S::Ok, S::Error> {
+    ser.serialize_u32(version.number())
+}
+
+impl ExchangeLayout {
+    /// Reads the header of the exchange file in the provided stream
+    ///
+    /// Does not read or validate the data the entries point to.
+    pub fn read<R>(reader: &mut R) -> Result<Self>
+    where
+        R: Read + Seek,
+    {
+        let (hdr, ext) = ExchangeFormatV1Hdr::read(reader)?;
+        let mut entries = vec![
+            EntryLayout::new("arcb", &hdr.arcb),
+            EntryLayout::new("measurement", &hdr.measurement),
+            EntryLayout::new("additional", &hdr.additional),
+            EntryLayout::new("user", &hdr.user),
+            EntryLayout::new("config_uid", &hdr.config_uid),
+        ];
+        if let Some(ext) = &ext {
+            entries.push(EntryLayout::new("metadata", &ext.metadata));
+            entries.push(EntryLayout::new("signature", &ext.signature));
+        }
+        Ok(Self {
+            version: hdr.version.try_into()?,
+            size: hdr.size.get(),
+            entries,
+        })
+    }
+
+    /// Returns the layout of the entry with the given name if the format has it.
+    pub fn entry(&self, name: &str) -> Option<&EntryLayout> {
+        self.entries.iter().find(|e| e.name == name)
+    }
+
+    /// Returns true if the file contains an attestation response
+    ///
+    /// A response contains the measurement, a request only its expected size.
+    pub fn is_response(&self) -> bool {
+        self.entry("measurement")
+            .is_some_and(|e| e.size > 0 && e.offset > 0)
+    }
+}
+
 /// The _exchange format_ is a simple file format to send labeled binary blobs between
 /// pvattest instances on different machines.
 #[derive(Debug, PartialEq, Eq)]
@@ -264,6 +607,7 @@ pub struct ExchangeFormatRequest {
     pub arcb: Vec<u8>,
     pub exp_measurement: u32,
     pub exp_additional: u32,
+    pub creator: CreatorData,
 }
 
 /// The _exchange format_ is a simple file format to send labeled binary blobs between
@@ -277,6 +621,7 @@ pub struct ExchangeFormatResponse {
     pub additional: Option<Vec<u8>>,
     pub user: Option<Vec<u8>>,
     pub config_uid: ConfigUid,
+    pub creator: CreatorData,
 }
 
 impl ExchangeFormatRequest {
@@ -301,35 +646,70 @@ impl ExchangeFormatRequest {
             arcb,
             exp_measurement,
             exp_additional,
+            creator: CreatorData::default(),
         })
     }
 
-    fn write_v1<W>(&self, writer: &mut W) -> Result<()>
+    /// Adds metadata to this request
+    ///
+    /// Removes any existing signature.
+    pub fn set_metadata(&mut self, metadata: &ExchangeMetadata) -> Result<()> {
+        self.creator.set_metadata(metadata)
+    }
+
+    /// Returns the metadata of this [`ExchangeFormatRequest`] if any.
+    pub fn metadata(&self) -> Result<Option<ExchangeMetadata>> {
+        self.creator.metadata()
+    }
+
+    /// Signs the attestation request and the metadata with `skey`
+    ///
+    /// EC and RSA (PSS padding) keys are supported. SHA-512 is used as digest.
+    pub fn sign(&mut self, skey: &PKeyRef<Private>) -> Result<()> {
+        self.creator.sign(&self.arcb, skey)
+    }
+
+    /// Returns true if the creator signed this request
+    pub const fn is_signed(&self) -> bool {
+        self.creator.signature.is_some()
+    }
+
+    /// Returns the minimum exchange format version that can hold this request
+    pub const fn min_version(&self) -> ExchangeFormatVersion {
+        self.creator.min_version()
+    }
+
+    /// Serializes the encapsulated data into the provides stream in the provided format
+    pub fn write<W>(&self, writer: &mut W, version: ExchangeFormatVersion) -> Result<()>
     where
         W: Write,
     {
-        let hdr = ExchangeFormatV1Hdr::new_request(
+        if self.min_version() == ExchangeFormatVersion::Two && version == ExchangeFormatVersion::One
+        {
+            bail!("Metadata and signatures require the exchange format version 2");
+        }
+        let mut hdr = ExchangeFormatV1Hdr::new_request(
+            version,
             self.arcb.as_slice(),
             self.exp_measurement,
             self.exp_additional,
         )?;
-        writer.write_all(hdr.as_bytes())?;
-        writer.write_all(&self.arcb)?;
-        Ok(())
-    }
+        let mut offset = hdr.size.get();
+        let ext = ExchangeFormatV2Ext::new(&self.creator, &mut offset);
+        hdr.size = offset.into();
 
-    /// Serializes the encapsulated data into the provides stream in the provided format
-    pub fn write<W>(&self, writer: &mut W, version: ExchangeFormatVersion) -> Result<()>
-    where
-        W: Write,
-    {
-        match version {
-            ExchangeFormatVersion::One => self.write_v1(writer),
+        writer.write_all(hdr.as_bytes())?;
+        if version == ExchangeFormatVersion::Two {
+            writer.write_all(ext.as_bytes())?;
         }
+        writer.write_all(&self.arcb)?;
+        self.creator.write(writer)
     }
 
     /// Reads and deserializes the exchange file in the provided stream
     ///
+    /// Supports all [`ExchangeFormatVersion`]s.
+    ///
     /// # Errors
     ///
     /// Returns an error if the stream does not contain data in exchange format, CUID or user data
@@ -338,37 +718,18 @@ impl ExchangeFormatRequest {
     where
         R: Read + Seek,
     {
-        let mut buf = vec![0; size_of::<ExchangeFormatV1Hdr>()];
-        match reader.read_exact(&mut buf) {
-            Ok(it) => it,
-            // report hdr file to small for header
-            Err(err) if err.kind() == ErrorKind::UnexpectedEof => {
-                bail!("{INV_EXCHANGE_FMT_ERROR_TEXT} Invalid Header.");
-            }
-            Err(err) => return Err(err.into()),
-        };
-
-        if !ExchangeMagic::starts_with_magic(&buf) {
-            bail!("{INV_EXCHANGE_FMT_ERROR_TEXT} Does not start with the magic value.",);
-        }
-
-        let hdr = ExchangeFormatV1Hdr::ref_from_bytes(buf.as_slice())
-            .map_err(|_| anyhow!("{INV_EXCHANGE_FMT_ERROR_TEXT} Invalid Header."))?;
-
-        match TryInto::<ExchangeFormatVersion>::try_into(hdr.version)? {
-            ExchangeFormatVersion::One => (),
-        }
-
-        if stream_len(reader)? < hdr.size.get() as u64 {
-            bail!("{INV_EXCHANGE_FMT_ERROR_TEXT} File size too small");
-        }
+        let (hdr, ext) = ExchangeFormatV1Hdr::read(reader)?;
         let arcb = hdr.arcb.read(reader)?.data().ok_or(anyhow!(
             "{INV_EXCHANGE_FMT_ERROR_TEXT} Contains no attestation request.",
         ))?;
 
         let measurement = hdr.measurement.read(reader)?.size();
         let additional = hdr.additional.read(reader)?.size();
-        Self::new(arcb, measurement, additional)
+        let mut req = Self::new(arcb, measurement, additional)?;
+        if let Some(ext) = ext {
+            req.creator = ext.read(reader)?;
+        }
+        Ok(req)
     }
 }
 
@@ -454,21 +815,45 @@ impl ExchangeFormatResponse {
             additional,
             user,
             config_uid,
+            creator: CreatorData::default(),
         })
     }
 
-    fn write_v1<W>(&self, writer: &mut W) -> Result<()>
+    /// Returns true if the creator signed the request of this response
+    pub const fn is_signed(&self) -> bool {
+        self.creator.signature.is_some()
+    }
+
+    /// Returns the minimum exchange format version that can hold this response
+    pub const fn min_version(&self) -> ExchangeFormatVersion {
+        self.creator.min_version()
+    }
+
+    /// Serializes the encapsulated data into the provides stream in the provided format
+    pub fn write<W>(&self, writer: &mut W, version: ExchangeFormatVersion) -> Result<()>
     where
         W: Write,
     {
-        let hdr = ExchangeFormatV1Hdr::new_response(
+        if self.min_version() == ExchangeFormatVersion::Two && version == ExchangeFormatVersion::One
+        {
+            bail!("Metadata and signatures require the exchange format version 2");
+        }
+        let mut hdr = ExchangeFormatV1Hdr::new_response(
+            version,
             self.arcb.as_slice(),
             &self.measurement,
             self.additional.as_deref(),
             self.user.as_deref(),
             &self.config_uid,
         )?;
+        let mut offset = hdr.size.get();
+        let ext = ExchangeFormatV2Ext::new(&self.creator, &mut offset);
+        hdr.size = offset.into();
+
         writer.write_all(hdr.as_bytes())?;
+        if version == ExchangeFormatVersion::Two {
+            writer.write_all(ext.as_bytes())?;
+        }
         writer.write_all(&self.arcb)?;
         writer.write_all(&self.measurement)?;
         if let Some(data) = &self.additional {
@@ -478,21 +863,13 @@ impl ExchangeFormatResponse {
             writer.write_all(data)?;
         }
         writer.write_all(&self.config_uid)?;
-        Ok(())
-    }
-
-    /// Serializes the encapsulated data into the provides stream in the provided format
-    pub fn write<W>(&self, writer: &mut W, version: ExchangeFormatVersion) -> Result<()>
-    where
-        W: Write,
-    {
-        match version {
-            ExchangeFormatVersion::One => self.write_v1(writer),
-        }
+        self.creator.write(writer)
     }
 
     /// Reads and deserializes the exchange file in the provided stream
     ///
+    /// Supports all [`ExchangeFormatVersion`]s.
+    ///
     /// # Errors
     ///
     /// Returns an error if the stream does not contain data in exchange format, CUID or user data
@@ -501,30 +878,7 @@ impl ExchangeFormatResponse {
     where
         R: Read + Seek,
     {
-        let mut buf = vec![0; size_of::<ExchangeFormatV1Hdr>()];
-        match reader.read_exact(&mut buf) {
-            Ok(it) => it,
-            // report hdr file to small for header
-            Err(err) if err.kind() == ErrorKind::UnexpectedEof => {
-                bail!("{INV_EXCHANGE_FMT_ERROR_TEXT} Invalid Header.");
-            }
-            Err(err) => return Err(err.into()),
-        };
-
-        if !ExchangeMagic::starts_with_magic(&buf) {
-            bail!("{INV_EXCHANGE_FMT_ERROR_TEXT} Does not start with the magic value.");
-        }
-
-        let hdr = ExchangeFormatV1Hdr::ref_from_bytes(buf.as_slice())
-            .map_err(|_| anyhow!("{INV_EXCHANGE_FMT_ERROR_TEXT} Invalid Header."))?;
-
-        match TryInto::<ExchangeFormatVersion>::try_into(hdr.version)? {
-            ExchangeFormatVersion::One => (),
-        }
-
-        if stream_len(reader)? < hdr.size.get() as u64 {
-            bail!("{INV_EXCHANGE_FMT_ERROR_TEXT} File size too small");
-        }
+        let (hdr, ext) = ExchangeFormatV1Hdr::read(reader)?;
         let arcb = hdr.arcb.read(reader)?.data().ok_or(anyhow!(
             "{INV_EXCHANGE_FMT_ERROR_TEXT} Contains no attestation request.",
         ))?;
@@ -545,7 +899,11 @@ anyhow!(
             bail!("{INV_EXCHANGE_FMT_ERROR_TEXT} Contains no attestation response (CUID missing).")
 ,
         };
-        Self::new(arcb, measurement, additional, user, config_uid)
+        let mut resp = Self::new(arcb, measurement, additional, user, config_uid)?;
+        if let Some(ext) = ext {
+            resp.creator = ext.read(reader)?;
+        }
+        Ok(resp)
     }
 
     /// Returns the measurement of this [`ExchangeFormatRequest`].
@@ -575,6 +933,21 @@ anyhow!(
     pub fn arcb(&self) -> &[u8] {
         self.arcb.as_ref()
     }
+
+    /// Returns the metadata of the request of this [`ExchangeFormatResponse`] if any.
+    pub fn metadata(&self) -> Result<Option<ExchangeMetadata>> {
+        self.creator.metadata()
+    }
+
+    /// Verifies the signature of the creator of the request with `pkey`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the response contains no signature or OpenSSL could not verify the
+    /// signature.
+    pub fn verify_signature<T: HasPublic>(&self, pkey: &PKeyRef<T>) -> Result<bool> {
+        self.creator.verify_signature(&self.arcb, pkey)
+    }
 }
 
 #[cfg(test)]
@@ -583,7 +956,10 @@ mod test {
     use std::io::Cursor;
 
     use super::*;
-    use pv::misc::read_file;
+    use pv::{
+        misc::read_file,
+        request::{gen_ec_key, openssl::Nid},
+    };
 
     #[test]
     fn exchange_from_slice() {
@@ -871,4 +1247,128 @@ mod test {
 
         let _ctx_read = ExchangeFormatResponse::read(&mut Cursor::new(&mut &buf)).unwrap_err();
     }
+
+    fn v2_request() -> ExchangeFormatRequest {
+        let mut req =
+            ExchangeFormatRequest::new(ARCB.to_vec(), MEASUREMENT.len() as u32, 0).unwrap();
+        req.set_metadata(&ExchangeMetadata {
+            creation_time: Some(1700000000),
+            tool_version: Some("pvattest test".to_string()),
+            add_data: vec!["phkh-img".to_string()],
+            se_hdr_hash: Some("00".repeat(32)),
+        })
+        .unwrap();
+        req
+    }
+
+    #[test]
+    fn v2_req() {
+        let req = v2_request();
+        assert_eq!(req.min_version(), ExchangeFormatVersion::Two);
+
+        let mut out = vec![];
+        req.write(&mut out, ExchangeFormatVersion::Two).unwrap();
+        assert_eq!(&out[0x8..0xc], &[0, 0, 2, 0]);
+        let read = ExchangeFormatRequest::read(&mut Cursor::new(&out)).unwrap();
+        assert_eq!(read, req);
+        assert_eq!(read.metadata().unwrap(), req.metadata().unwrap());
+
+        // metadata requires version 2
+        req.write(&mut vec![], ExchangeFormatVersion::One)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn v2_no_metadata() {
+        let req = ExchangeFormatRequest::new(ARCB.to_vec(), MEASUREMENT.len() as u32, 0).unwrap();
+        assert_eq!(req.min_version(), ExchangeFormatVersion::One);
+        let mut out = vec![];
+        req.write(&mut out, ExchangeFormatVersion::Two).unwrap();
+        let read = ExchangeFormatRequest::read(&mut Cursor::new(&out)).unwrap();
+        assert_eq!(read, req);
+        assert_eq!(read.metadata().unwrap(), None);
+    }
+
+    #[test]
+    fn v2_resp_sign() {
+        let skey = gen_ec_key(Nid::SECP521R1).unwrap();
+        let other = gen_ec_key(Nid::SECP521R1).unwrap();
+        let mut req = v2_request();
+        req.sign(&skey).unwrap();
+
+        let mut resp = ExchangeFormatResponse::new(
+            req.arcb.clone(),
+            MEASUREMENT.to_vec(),
+            None,
+            Some(USER.to_vec()),
+            CUID,
+        )
+        .unwrap();
+        resp.creator = req.creator.clone();
+
+        let mut out = vec![];
+        resp.write(&mut out, resp.min_version()).unwrap();
+        let read = ExchangeFormatResponse::read(&mut Cursor::new(&out)).unwrap();
+        assert_eq!(read, resp);
+        assert!(read.verify_signature(&skey).unwrap());
+        assert!(!read.verify_signature(&other).unwrap());
+
+        // tamper with the metadata
+        let pos = out.len() - read.creator.signature.as_ref().unwrap().len() - 2;
+        out[pos] ^= 0x1;
+        let tampered = ExchangeFormatResponse::read(&mut Cursor::new(&out));
+        assert!(tampered.map_or(true, |r| !r.verify_signature(&skey).unwrap()));
+    }
+
+    #[test]
+    fn v2_unsigned() {
+        let skey = gen_ec_key(Nid::SECP521R1).unwrap();
+        let resp =
+            ExchangeFormatResponse::new(ARCB.to_vec(), MEASUREMENT.to_vec(), None, None, CUID)
+                .unwrap();
+        resp.verify_signature(&skey).unwrap_err();
+    }
+
+    #[test]
+    fn v2_small() {
+        let mut out = vec![];
+        v2_request()
+            .write(&mut out, ExchangeFormatVersion::Two)
+            .unwrap();
+        // header extension missing
+        out.truncate(0x48);
+        ExchangeFormatRequest::read(&mut Cursor::new(&out)).unwrap_err();
+    }
+
+    #[test]
+    fn layout() {
+        let buf = include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/assets/",
+            "exp/exchange/full_resp.bin"
+        ));
+        let layout = ExchangeLayout::read(&mut Cursor::new(&buf)).unwrap();
+        assert_eq!(layout.version, ExchangeFormatVersion::One);
+        assert_eq!(layout.size as usize, buf.len());
+        assert_eq!(layout.entries.len(), 5);
+        assert_eq!(
+            layout.entry("arcb"),
+            Some(&EntryLayout {
+                name: "arcb",
+                size: ARCB.len() as u32,
+                offset: 0x40
+            })
+        );
+        assert!(layout.is_response());
+
+        let mut out = vec![];
+        v2_request()
+            .write(&mut out, ExchangeFormatVersion::Two)
+            .unwrap();
+        let layout = ExchangeLayout::read(&mut Cursor::new(&out)).unwrap();
+        assert_eq!(layout.version, ExchangeFormatVersion::Two);
+        assert_eq!(layout.entries.len(), 7);
+        assert_eq!(layout.entry("arcb").unwrap().offset, 0x50);
+        assert!(!layout.is_response());
+    }
 }
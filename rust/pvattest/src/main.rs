@@ -14,7 +14,7 @@ use log::trace;
 use std::process::ExitCode;
 use utils::{print_cli_error, print_error, print_version, PvLogger};
 
-use crate::cmd::{check, create, perform, verify, CMD_FN, UV_CMD_FN};
+use crate::cmd::{check, create, inspect, perform, verify, verify_batch, CMD_FN, UV_CMD_FN};
 
 static LOGGER: PvLogger = PvLogger;
 const FEATURES: &[&[&str]] = &[CMD_FN, UV_CMD_FN];
@@ -42,11 +42,13 @@ fn main() -> ExitCode {
         Command::Create(opt) => create(opt),
         Command::Perform(opt) => perform(opt),
         Command::Verify(opt) => verify(opt),
+        Command::VerifyBatch(opt) => verify_batch(opt),
         Command::Version => {
             print_version!("2024", log_level; FEATURES.concat());
             Ok(ExitCode::SUCCESS)
         }
         Command::Check(opt) => check(opt),
+        Command::Inspect(opt) => inspect(opt),
     };
     match res {
         Ok(c) => c,
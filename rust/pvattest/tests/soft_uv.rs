@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+#![allow(missing_docs)]
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+use pv::{
+    misc::encode_hex,
+    request::EcPubKeyCoord,
+    test_utils::{create_test_se_hdr, get_cert_asset_path, load_gen_cert},
+};
+
+fn tmp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pvattest_{name}_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn pvattest(dir: &Path, args: &[&str]) -> Output {
+    let out = Command::new(env!("CARGO_BIN_EXE_pvattest"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "pvattest {args:?} failed: {}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    out
+}
+
+fn phkh(hkd: &str) -> String {
+    let coords: EcPubKeyCoord = load_gen_cert(hkd).public_key().unwrap().try_into().unwrap();
+    encode_hex(coords.sha256().unwrap())
+}
+
+#[test]
+fn create_perform_verify() {
+    // the image is booted with host.key, the attestation request is for host2.crt
+    let dir = tmp_dir("attest");
+    let uv_dir = dir.join("uv");
+    fs::create_dir_all(&uv_dir).unwrap();
+    fs::write(
+        uv_dir.join("se.img"),
+        create_test_se_hdr(&["host.crt", "host2.crt"]),
+    )
+    .unwrap();
+    for key in ["host.key", "host2.key"] {
+        fs::copy(get_cert_asset_path(key), uv_dir.join(key)).unwrap();
+    }
+    let hkd = get_cert_asset_path("host2.crt");
+    let uv_backend = format!("--uv-backend=soft:{}", uv_dir.display());
+
+    pvattest(
+        &dir,
+        &[
+            "create",
+            "-k",
+            hkd.to_str().unwrap(),
+            "--no-verify",
+            "--arpk",
+            "arpk",
+            "-o",
+            "arcb",
+            "--add-data",
+            "phkh-img,phkh-att",
+        ],
+    );
+    fs::write(dir.join("user_data"), b"user data").unwrap();
+    pvattest(
+        &dir,
+        &[
+            "perform",
+            "arcb",
+            "measurement",
+            "-u",
+            "user_data",
+            &uv_backend,
+        ],
+    );
+    pvattest(
+        &dir,
+        &[
+            "verify",
+            "-i",
+            "measurement",
+            "--arpk",
+            "arpk",
+            "--hdr",
+            "uv/se.img",
+            "-u",
+            "user_data",
+            "-o",
+            "result.yaml",
+        ],
+    );
+
+    let result = fs::read_to_string(dir.join("result.yaml")).unwrap();
+    let img = format!("image_phkh: 0x{}", phkh("host.crt"));
+    let att = format!("attestation_phkh: 0x{}", phkh("host2.crt"));
+    assert!(result.contains(&img) && result.contains(&att), "{result}");
+    fs::remove_dir_all(&dir).unwrap();
+}
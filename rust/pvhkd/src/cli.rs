@@ -0,0 +1,151 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand, ValueEnum, ValueHint};
+use utils::{CertificateOptions, VerbosityOptions};
+
+/// Inspect and verify host-key documents for IBM Secure Execution.
+///
+/// Use to display which machine a host-key document is issued for, to verify host-key documents,
+/// and to download the certificate revocation lists (CRLs) for offline verification.
+#[derive(Parser, Debug)]
+pub struct CliOptions {
+    #[clap(flatten)]
+    pub verbosity: VerbosityOptions,
+
+    /// Print version information and exit.
+    // Implemented for the help message only. Actual parsing happens in the
+    // version command.
+    #[arg(long)]
+    pub version: bool,
+
+    #[command(subcommand)]
+    pub cmd: Command,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug, Default)]
+pub enum InspectOutputType {
+    /// Human-focused, non-parsable output format
+    #[default]
+    Human,
+    /// Use yaml format.
+    Yaml,
+}
+
+#[derive(Args, Debug)]
+pub struct InspectOpt {
+    /// Use FILE as a host-key document.
+    ///
+    /// Can be specified multiple times. A file may contain multiple host-key documents.
+    #[arg(value_name = "FILE", required = true, value_hint = ValueHint::FilePath)]
+    pub input: Vec<PathBuf>,
+
+    /// Define the output format.
+    #[arg(long, value_enum, default_value_t)]
+    pub format: InspectOutputType,
+}
+
+#[derive(Args, Debug)]
+pub struct VerifyOpt {
+    #[command(flatten)]
+    pub certificate_args: CertificateOptions,
+}
+
+#[derive(Args, Debug)]
+pub struct FetchCrlOpt {
+    /// Use FILE as a certificate to download the CRLs for.
+    ///
+    /// Specify the host-key documents, the IBM Z signing-key certificate, and the intermediate CA
+    /// certificate. Can be specified multiple times.
+    #[arg(value_name = "FILE", required = true, value_hint = ValueHint::FilePath)]
+    pub input: Vec<PathBuf>,
+
+    /// Store the CRLs in DIR.
+    ///
+    /// Use DIR with '--crl-cache' and '--offline' to verify host-key documents without network
+    /// access. DIR is created if it does not exist.
+    #[arg(short, long, value_name = "DIR", value_hint = ValueHint::DirPath)]
+    pub output: PathBuf,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Display information about host-key documents.
+    ///
+    /// Displays the subject, the machine type and serial number, the validity period, the key
+    /// type, and the public host-key hash of each host-key document. The public host-key hash is
+    /// the hash used in attestation responses and by 'pvimg test --key-hashes'. The machine type
+    /// and serial number are taken from the file name as issued by IBM: HKD-<type>-<serial>.crt.
+    /// They are marked as such in the output.
+    Inspect(InspectOpt),
+
+    /// Verify host-key documents.
+    ///
+    /// Establishes the chain of trust and verifies each host-key document against it, including
+    /// the certificate revocation lists (CRLs). Performs and reports each verification step, also
+    /// after a failing one. The revocation status is skipped if no valid CRL is available.
+    Verify(VerifyOpt),
+
+    /// Download the CRLs of certificates for later offline use.
+    ///
+    /// Downloads the certificate revocation lists (CRLs) from the distribution points of the
+    /// given certificates and stores them in a directory. CRLs that are already stored and not
+    /// outdated are not downloaded again.
+    FetchCrl(FetchCrlOpt),
+
+    /// Print version information and exit.
+    #[command(aliases(["--version"]), hide(true))]
+    Version,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[rustfmt::skip]
+    fn cli_args() {
+        //Verify only that some arguments are optional, we do not want to test clap, only the
+        //configuration
+        let valid_args = [
+            vec!["pvhkd", "version"],
+            vec!["pvhkd", "inspect", "hkd.crt"],
+            vec!["pvhkd", "inspect", "hkd.crt", "hkd2.crt", "--format", "yaml"],
+            vec!["pvhkd", "verify", "-k", "hkd.crt", "-C", "ibm.crt", "-C", "inter.crt"],
+            vec!["pvhkd", "verify", "-k", "hkd.crt", "-C", "ibm.crt", "--offline", "--crl-cache", "crls"],
+            vec!["pvhkd", "fetch-crl", "hkd.crt", "ibm.crt", "-o", "crls"],
+        ];
+        // Test for the minimal amount of flags to yield an invalid combination
+        let invalid_args = [
+            vec!["pvhkd"],
+            vec!["pvhkd", "inspect"],
+            vec!["pvhkd", "inspect", "hkd.crt", "--format", "bin"],
+            vec!["pvhkd", "verify", "-k", "hkd.crt"],
+            vec!["pvhkd", "verify", "-C", "ibm.crt"],
+            vec!["pvhkd", "fetch-crl", "hkd.crt"],
+            vec!["pvhkd", "fetch-crl", "-o", "crls"],
+        ];
+        for arg in valid_args {
+            let res = CliOptions::try_parse_from(&arg);
+            if let Err(e) = &res {
+                println!("arg: {arg:?}");
+                println!("{e}");
+            }
+            assert!(res.is_ok());
+        }
+
+        for arg in invalid_args {
+            let res = CliOptions::try_parse_from(&arg);
+            assert!(res.is_err());
+        }
+    }
+
+    #[test]
+    fn verify_cli() {
+        use clap::CommandFactory;
+        CliOptions::command().debug_assert()
+    }
+}
@@ -0,0 +1,33 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+mod inspect;
+pub use inspect::inspect;
+
+mod verify;
+pub use verify::verify;
+
+pub const CMD_FN: &[&str] = &["+inspect", "+verify"];
+
+#[cfg(feature = "network")]
+mod fetch_crl;
+
+// Downloading CRLs requires network support
+#[cfg(feature = "network")]
+mod net_cmd {
+    pub use super::fetch_crl::fetch_crl;
+    pub const NET_CMD_FN: &[&str] = &["+fetch-crl"];
+}
+
+#[cfg(not(feature = "network"))]
+mod net_cmd {
+    use crate::cli::FetchCrlOpt;
+    use anyhow::{bail, Result};
+
+    pub fn fetch_crl(_: &FetchCrlOpt) -> Result<()> {
+        bail!("Command not available: pvhkd was built without network support")
+    }
+    pub const NET_CMD_FN: &[&str] = &[];
+}
+pub use net_cmd::*;
@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use anyhow::{bail, Context, Result};
+use log::info;
+use pv::{
+    misc::{read_certs, read_file},
+    request::{x509_dist_points, CrlFetcher, HttpCrlFetcher},
+};
+
+use crate::cli::FetchCrlOpt;
+
+pub fn fetch_crl(opt: &FetchCrlOpt) -> Result<()> {
+    let fetcher = HttpCrlFetcher::with_cache(&opt.output)
+        .with_context(|| format!("Cannot use '{}' to store CRLs", opt.output.display()))?;
+
+    let mut missing = 0;
+    for path in &opt.input {
+        for cert in read_certs(read_file(path, "certificate")?)? {
+            if x509_dist_points(&cert).is_empty() {
+                info!("'{}' has no CRL distribution point", path.display());
+                continue;
+            }
+            match fetcher.fetch(&cert)? {
+                Some(crls) => println!("✓ {}: {} CRL(s)", path.display(), crls.len()),
+                None => {
+                    println!("✘ {}: No CRL available", path.display());
+                    missing += 1;
+                }
+            }
+        }
+    }
+
+    if missing > 0 {
+        bail!("Cannot download the CRLs of {missing} certificate(s)");
+    }
+    Ok(())
+}
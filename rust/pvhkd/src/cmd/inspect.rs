@@ -0,0 +1,215 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use std::{cmp::Ordering, path::Path};
+
+use anyhow::{bail, Result};
+use openssl::{
+    asn1::Asn1Time,
+    pkey::Id,
+    x509::{X509NameRef, X509Ref},
+};
+use pv::{
+    misc::{encode_hex, read_certs, read_file},
+    request::x509_dist_points,
+};
+use pvimg::uvdata::phkh_v1;
+use serde::Serialize;
+
+use crate::cli::{InspectOpt, InspectOutputType};
+
+/// Information about a host-key document
+#[derive(Debug, Serialize, PartialEq, Eq)]
+struct HkdInfo {
+    file: String,
+    subject: String,
+    issuer: String,
+    // The certificate does not contain the machine; it is only known from the file name.
+    #[serde(
+        rename = "machine_type_from_file_name",
+        skip_serializing_if = "Option::is_none"
+    )]
+    machine_type: Option<String>,
+    #[serde(
+        rename = "machine_serial_from_file_name",
+        skip_serializing_if = "Option::is_none"
+    )]
+    machine_serial: Option<String>,
+    serial_number: String,
+    not_before: String,
+    not_after: String,
+    valid: bool,
+    key_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    host_key_hash: Option<String>,
+    crl_distribution_points: Vec<String>,
+}
+
+/// Formats the name as one-line string, e.g. `C=US, O=IBM`.
+fn name_to_string(name: &X509NameRef) -> String {
+    name.entries()
+        .map(|e| {
+            format!(
+                "{}={}",
+                e.object().nid().short_name().unwrap_or("?"),
+                e.data()
+                    .as_utf8()
+                    .map(|s| s.to_string())
+                    .unwrap_or_default()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Extracts machine type and serial number from the file name.
+///
+/// IBM issues host-key documents as `HKD-<type>-<serial>.crt`.
+fn machine_from_path(path: &Path) -> Option<(String, String)> {
+    let stem = path.file_stem()?.to_str()?;
+    let (ty, serial) = stem.strip_prefix("HKD-")?.split_once('-')?;
+    if ty.len() != 4 || !ty.bytes().all(|b| b.is_ascii_digit()) || serial.is_empty() {
+        return None;
+    }
+    Some((ty.to_string(), serial.to_string()))
+}
+
+fn key_type(hkd: &X509Ref) -> Result<String> {
+    let key = hkd.public_key()?;
+    Ok(match key.id() {
+        Id::EC => {
+            let curve = key
+                .ec_key()?
+                .group()
+                .curve_name()
+                .and_then(|nid| nid.short_name().ok())
+                .unwrap_or("unknown curve");
+            format!("EC ({curve}, {} bits)", key.bits())
+        }
+        Id::RSA => format!("RSA ({} bits)", key.bits()),
+        _ => format!("unknown ({} bits)", key.bits()),
+    })
+}
+
+impl HkdInfo {
+    fn new(path: &Path, hkd: &X509Ref) -> Result<Self> {
+        let now = Asn1Time::days_from_now(0)?;
+        let valid = hkd.not_before().compare(&now)? != Ordering::Greater
+            && hkd.not_after().compare(&now)? == Ordering::Greater;
+        let (machine_type, machine_serial) = machine_from_path(path).unzip();
+        // Only EC keys have a public host-key hash
+        let host_key_hash = phkh_v1(hkd.public_key()?).ok().map(encode_hex);
+
+        Ok(Self {
+            file: path.display().to_string(),
+            subject: name_to_string(hkd.subject_name()),
+            issuer: name_to_string(hkd.issuer_name()),
+            machine_type,
+            machine_serial,
+            serial_number: hkd.serial_number().to_bn()?.to_hex_str()?.to_string(),
+            not_before: hkd.not_before().to_string(),
+            not_after: hkd.not_after().to_string(),
+            valid,
+            key_type: key_type(hkd)?,
+            host_key_hash,
+            crl_distribution_points: x509_dist_points(hkd),
+        })
+    }
+
+    fn print_human(&self) {
+        let na = "n/a".to_string();
+        let from_file_name = |v: &Option<String>| match v {
+            Some(v) => format!("{v} (from file name)"),
+            None => na.clone(),
+        };
+        println!("File:                 {}", self.file);
+        println!("Subject:              {}", self.subject);
+        println!("Issuer:               {}", self.issuer);
+        println!(
+            "Machine type:         {}",
+            from_file_name(&self.machine_type)
+        );
+        println!(
+            "Machine serial:       {}",
+            from_file_name(&self.machine_serial)
+        );
+        println!("Serial number:        {}", self.serial_number);
+        println!("Not before:           {}", self.not_before);
+        println!(
+            "Not after:            {}{}",
+            self.not_after,
+            if self.valid { "" } else { " (not valid now)" }
+        );
+        println!("Key type:             {}", self.key_type);
+        println!(
+            "Public host-key hash: {}",
+            self.host_key_hash.as_ref().unwrap_or(&na)
+        );
+        for dp in &self.crl_distribution_points {
+            println!("CRL distribution:     {dp}");
+        }
+    }
+}
+
+fn hkd_infos(path: &Path) -> Result<Vec<HkdInfo>> {
+    let hkds = read_certs(read_file(path, "host-key document")?)?;
+    if hkds.is_empty() {
+        bail!("'{}' contains no host-key document", path.display());
+    }
+    hkds.iter().map(|hkd| HkdInfo::new(path, hkd)).collect()
+}
+
+pub fn inspect(opt: &InspectOpt) -> Result<()> {
+    let mut infos = vec![];
+    for path in &opt.input {
+        infos.append(&mut hkd_infos(path)?);
+    }
+
+    match opt.format {
+        InspectOutputType::Human => {
+            for (idx, info) in infos.iter().enumerate() {
+                if idx > 0 {
+                    println!();
+                }
+                info.print_human();
+            }
+        }
+        InspectOutputType::Yaml => serde_yaml::to_writer(std::io::stdout(), &infos)?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pv::test_utils::get_cert_asset_path;
+
+    #[test]
+    fn machine() {
+        assert_eq!(
+            machine_from_path(Path::new("/a/HKD-3931-0123456.crt")),
+            Some(("3931".to_string(), "0123456".to_string()))
+        );
+        assert_eq!(machine_from_path(Path::new("HKD-39-0123456.crt")), None);
+        assert_eq!(machine_from_path(Path::new("HKD-3931-.crt")), None);
+        assert_eq!(machine_from_path(Path::new("host.crt")), None);
+    }
+
+    #[test]
+    fn info() {
+        let infos = hkd_infos(&get_cert_asset_path("host.crt")).unwrap();
+        assert_eq!(infos.len(), 1);
+        let info = &infos[0];
+        assert!(info.subject.ends_with("OU=IBM Z Host Key"));
+        assert!(info.issuer.ends_with("OU=IBM Z Host Key Signing Service"));
+        assert_eq!(info.machine_type, None);
+        assert!(info.valid);
+        assert!(info.key_type.starts_with("EC (secp521r1"));
+        assert_eq!(info.host_key_hash.as_ref().unwrap().len(), 64);
+
+        let expired = hkd_infos(&get_cert_asset_path("host_crt_expired.crt")).unwrap();
+        assert!(!expired[0].valid);
+        assert!(hkd_infos(&get_cert_asset_path("inter_ca.crl")).is_err());
+    }
+}
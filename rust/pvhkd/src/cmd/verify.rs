@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+use anyhow::{bail, Context, Result};
+use pv::{
+    misc::{read_certs, read_file},
+    request::{openssl::x509::X509Ref, CertVerifier, HkdStepOutcome},
+};
+
+use crate::cli::VerifyOpt;
+
+/// Verifies the host-key document and prints the outcome of each step.
+///
+/// Returns true if all steps passed.
+fn verify_hkd(verifier: &CertVerifier, hkd: &X509Ref) -> bool {
+    let mut verified = true;
+    for (step, outcome) in verifier.verify_steps(hkd) {
+        match outcome {
+            HkdStepOutcome::Passed => println!("  ✓ {step}"),
+            HkdStepOutcome::Failed(err) => {
+                println!("  ✘ {step}: {err}");
+                verified = false;
+            }
+            HkdStepOutcome::Skipped => {
+                println!("  - {step} (skipped)");
+                verified = false;
+            }
+        }
+    }
+    verified
+}
+
+pub fn verify(opt: &VerifyOpt) -> Result<()> {
+    let cert_args = &opt.certificate_args;
+    if cert_args.no_verify {
+        bail!("Cannot verify host-key documents if '--no-verify' is specified");
+    }
+
+    let verifier = cert_args
+        .cert_verifier()
+        .context("Cannot establish the chain of trust")?;
+    println!("✓ Chain of trust");
+
+    let mut failed = 0;
+    for path in &cert_args.host_key_documents {
+        let hkds = read_certs(read_file(path, "host-key document")?)?;
+        if hkds.is_empty() {
+            bail!("'{}' contains no host-key document", path.display());
+        }
+        for hkd in hkds {
+            println!("{}:", path.display());
+            if !verify_hkd(&verifier, &hkd) {
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        bail!("{failed} host-key document(s) could not be verified");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pv::test_utils::{get_cert_asset_path, load_gen_cert};
+    use utils::CertificateOptions;
+
+    fn verifier() -> CertVerifier {
+        CertificateOptions {
+            certs: vec![
+                get_cert_asset_path("ibm.crt"),
+                get_cert_asset_path("inter_ca.crt"),
+            ],
+            crls: vec![
+                get_cert_asset_path("ibm.crl"),
+                get_cert_asset_path("inter_ca.crl"),
+            ],
+            root_ca: Some(get_cert_asset_path("root_ca.chained.crt")),
+            offline: true,
+            ..Default::default()
+        }
+        .cert_verifier()
+        .unwrap()
+    }
+
+    #[test]
+    fn steps() {
+        let verifier = verifier();
+        let failed = |name: &str| -> Vec<usize> {
+            verifier
+                .verify_steps(&load_gen_cert(name))
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, outcome))| !matches!(outcome, HkdStepOutcome::Passed))
+                .map(|(idx, _)| idx)
+                .collect()
+        };
+
+        assert!(failed("host.crt").is_empty());
+        assert_eq!(failed("host_crt_expired.crt"), [1]);
+        assert_eq!(failed("host_invalid_signing_key.crt"), [2, 3]);
+        assert_eq!(failed("host_rev.crt"), [5]);
+
+        assert!(verify_hkd(&verifier, &load_gen_cert("host.crt")));
+        assert!(!verify_hkd(&verifier, &load_gen_cert("host_rev.crt")));
+    }
+}
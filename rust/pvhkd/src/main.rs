@@ -0,0 +1,50 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+#![allow(missing_docs)]
+mod cli;
+mod cmd;
+
+use clap::{CommandFactory, Parser};
+use cli::{CliOptions, Command};
+use log::trace;
+use std::process::ExitCode;
+use utils::{print_cli_error, print_error, print_version, PvLogger};
+
+static LOGGER: PvLogger = PvLogger;
+static EXIT_LOGGER: u8 = 3;
+const FEATURES: &[&[&str]] = &[cmd::CMD_FN, cmd::NET_CMD_FN];
+
+fn main() -> ExitCode {
+    let cli: CliOptions = match CliOptions::try_parse() {
+        Ok(cli) => cli,
+        Err(e) => return print_cli_error(e, CliOptions::command()),
+    };
+
+    // set up logger/std(out,err)
+    let log_level = cli.verbosity.to_level_filter();
+    if let Err(e) = LOGGER.start(log_level) {
+        // should(TM) never happen
+        eprintln!("Logger error: {e:?}");
+        return EXIT_LOGGER.into();
+    }
+
+    trace!("Options {cli:?}");
+
+    // perform the command selected by the user
+    let res = match &cli.cmd {
+        Command::Inspect(opt) => cmd::inspect(opt),
+        Command::Verify(opt) => cmd::verify(opt),
+        Command::FetchCrl(opt) => cmd::fetch_crl(opt),
+        Command::Version => {
+            print_version!("2025", log_level; FEATURES.concat());
+            Ok(())
+        }
+    };
+
+    match res {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(e) => print_error(&e, log_level),
+    }
+}
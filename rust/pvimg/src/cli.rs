@@ -254,6 +254,14 @@ pub struct TestArgs {
         group = "test-args",
         )]
     pub key_hashes: Option<PathBuf>,
+
+    /// Check that the Ultravisor of the local system supports the image.
+    ///
+    /// The check fails if the Ultravisor does not support the SE header
+    /// version or the plaintext control flags of the image, or if its
+    /// capabilities cannot be read.
+    #[arg(long, group = "test-args")]
+    pub uv_support: bool,
 }
 
 /// Create an IBM Secure Execution image.
@@ -663,6 +671,21 @@ mod test {
                     CliOption::new("verbose", ["-VVV"]),
                 ],
             )),
+            flat_map_collect(insert(
+                args.clone(),
+                vec![
+                    CliOption::new("uv-support", ["--uv-support"]),
+                    CliOption::new("image", ["/dev/null"]),
+                ],
+            )),
+            flat_map_collect(insert(
+                args.clone(),
+                vec![
+                    CliOption::new("host-key-hashes2", ["--key-hashes"]),
+                    CliOption::new("uv-support", ["--uv-support"]),
+                    CliOption::new("image", ["/dev/null"]),
+                ],
+            )),
         ];
 
         let invalid_test_args = [
@@ -2,17 +2,18 @@
 //
 // Copyright IBM Corp. 2024
 
-use std::path::{Path, PathBuf};
+use std::path::Path;
 
 use anyhow::Result;
 use log::{info, warn};
 use pv::{
     misc::{open_file, read_certs, read_file},
+    uv::UvQuery,
     FileAccessErrorType, PvCoreError,
 };
 use pvimg::{
     error::{Error, OwnExitCode, PvError},
-    uvdata::{KeyExchangeTrait, SeHdr, UvKeyHashesV1},
+    uvdata::{KeyExchangeTrait, SeHdr, SeHdrVersioned, UvKeyHashesV1},
 };
 use utils::HexSlice;
 
@@ -27,7 +28,7 @@ fn hdr_test_target_hashes(hdr: &SeHdr, key_hashes: &Path) -> Result<bool> {
             ref source,
         } if matches!(ty, FileAccessErrorType::Open)
             && source.kind() == std::io::ErrorKind::NotFound
-            && *path == PathBuf::from(UvKeyHashesV1::SYS_UV_KEYS_ALL) =>
+            && *path == Path::new(UvKeyHashesV1::SYS_UV_KEYS_ALL) =>
         {
             Error::UnavailableQueryUvKeyHashesSupport { source: err }
         }
@@ -62,6 +63,43 @@ fn hdr_test_target_hashes(hdr: &SeHdr, key_hashes: &Path) -> Result<bool> {
     Ok(contains)
 }
 
+/// Returns `Ok(true)` if the Ultravisor of the local system supports the
+/// SE header version and the plaintext control flags of the image. A
+/// capability the Ultravisor does not report is not checked.
+fn hdr_test_uv_support(hdr: &SeHdr) -> Result<bool> {
+    let query = match UvQuery::read() {
+        Ok(query) => query,
+        Err(err) => {
+            warn!(" ✘ Cannot read the Ultravisor capabilities: {err}");
+            return Ok(false);
+        }
+    };
+
+    let mut result = true;
+    let version = hdr.common.version as u32;
+    match query.supports_se_hdr_version(version) {
+        Some(true) => log_println!(" ✓ SE header version {version:#x} is supported"),
+        Some(false) => {
+            warn!(" ✘ SE header version {version:#x} is not supported by the Ultravisor");
+            result = false;
+        }
+        None => {}
+    }
+
+    let pcf = match &hdr.data {
+        SeHdrVersioned::SeHdrBinV1(bin) => bin.aad.pcf,
+    };
+    match query.supports_se_hdr_pcf(pcf) {
+        Some(true) => log_println!(" ✓ Plaintext control flags {pcf:#018x} are supported"),
+        Some(false) => {
+            warn!(" ✘ Plaintext control flags {pcf:#018x} are not supported by the Ultravisor");
+            result = false;
+        }
+        None => {}
+    }
+    Ok(result)
+}
+
 /// Returns `Ok(true)` if at least one of the given public key of the host key
 /// documents was used for the image creation or if no host key document was
 /// specified.
@@ -113,6 +151,9 @@ pub fn test(opt: &TestArgs) -> Result<OwnExitCode> {
     if let Some(path) = &opt.key_hashes {
         success = hdr_test_target_hashes(&hdr, path)? && success;
     }
+    if opt.uv_support {
+        success = hdr_test_uv_support(&hdr)? && success;
+    }
 
     Ok(if success {
         OwnExitCode::Success
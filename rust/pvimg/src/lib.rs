@@ -36,10 +36,11 @@ pub mod misc {
 
 pub mod uvdata {
     pub use crate::pv_utils::{
-        AeadPlainDataTrait, BuilderTrait, ComponentMetadataV1, ControlFlagTrait, ControlFlagsTrait,
-        FlagData, KeyExchangeTrait, PcfV1, PlaintextControlFlagsV1, ScfV1, SeHdr, SeHdrAadV1,
-        SeHdrBinV1, SeHdrBuilder, SeHdrData, SeHdrDataV1, SeHdrPlain, SeHdrVersion, SeHdrVersioned,
-        SecretControlFlagsV1, UvDataPlainTrait, UvDataTrait, UvKeyHashesV1,
+        phkh_v1, AeadPlainDataTrait, BuilderTrait, ComponentMetadataV1, ControlFlagTrait,
+        ControlFlagsTrait, FlagData, KeyExchangeTrait, PcfV1, PlaintextControlFlagsV1, ScfV1,
+        SeHdr, SeHdrAadV1, SeHdrBinV1, SeHdrBuilder, SeHdrData, SeHdrDataV1, SeHdrPlain,
+        SeHdrVersion, SeHdrVersioned, SecretControlFlagsV1, UvDataPlainTrait, UvDataTrait,
+        UvKeyHashesV1,
     };
 }
 
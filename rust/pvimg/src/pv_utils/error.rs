@@ -21,12 +21,6 @@ pub enum Error {
     )]
     ImgAlreadyFinalized,
 
-    #[error("Invalid target key hash")]
-    InvalidTargetKeyHash,
-
-    #[error("Invalid UV key hashes")]
-    InvalidUvKeyHashes,
-
     #[error("Invalid Secure Execution header")]
     InvalidSeHdr,
 
@@ -18,3 +18,4 @@ pub use flags::{
     SecretControlFlagsV1,
 };
 pub use hdr_v1::SeHdrAadV1;
+pub use keys::phkh_v1;
@@ -142,8 +142,10 @@ pub trait SeHdrPubBuilderTrait {
 
 #[enum_dispatch(SeHdrData)]
 pub trait SeHdrConfBuilderTrait {
+    #[allow(dead_code)]
     fn generate_cck(&self) -> Result<SymKey>;
     fn set_cck(&mut self, cck: Confidential<Vec<u8>>) -> Result<()>;
+    #[allow(dead_code)]
     fn set_psw(&mut self, psw: &PSW);
     fn set_scf(&mut self, scf: &SecretControlFlagsV1) -> Result<()>;
 }
@@ -0,0 +1,158 @@
+//! pvinfo check: compare SE images and requests with the Ultravisor capabilities
+
+use std::fs::{self, File};
+
+use serde::Serialize;
+
+use pv::attest::AttestationRequest;
+use pv::secret::AddSecretRequest;
+use pv_core::uv::query_desc::{self, BitDesc};
+use pv_core::uv::UvQuery;
+use pvimg::uvdata::{SeHdr, SeHdrVersioned};
+
+/*──────────────
+Output structures
+──────────────*/
+// One checked file with the properties that need Ultravisor support.
+// `supported` is None if the Ultravisor does not report the capability.
+
+#[derive(Serialize)]
+pub struct CheckedFile {
+    file: String,
+    kind: &'static str,
+    checks: Vec<Check>,
+}
+
+#[derive(Serialize)]
+pub struct Check {
+    property: &'static str,
+    value: String,
+    supported: Option<bool>,
+}
+
+impl CheckedFile {
+    fn new(file: &str, kind: &'static str) -> Self {
+        Self {
+            file: file.to_string(),
+            kind,
+            checks: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, property: &'static str, value: String, supported: Option<bool>) {
+        self.checks.push(Check {
+            property,
+            value,
+            supported,
+        });
+    }
+
+    // true if nothing is known to be unsupported
+    pub fn is_supported(&self) -> bool {
+        self.checks.iter().all(|c| c.supported != Some(false))
+    }
+}
+
+/*──────────────
+Checks
+──────────────*/
+
+pub fn check_image(query: &UvQuery, path: &str) -> Result<CheckedFile, String> {
+    let err = |e: &dyn std::fmt::Display| format!("Failed to read the SE header of {path}: {e}");
+    let mut input = File::open(path).map_err(|e| err(&e))?;
+    SeHdr::seek_sehdr(&mut input, None).map_err(|e| err(&e))?;
+    let hdr = SeHdr::try_from_io(input).map_err(|e| err(&e))?;
+
+    let mut res = CheckedFile::new(path, "se-image");
+    let version = hdr.common.version as u32;
+    res.push(
+        "SE header version",
+        format!("{version:#x}"),
+        query.supports_se_hdr_version(version),
+    );
+    let pcf = match &hdr.data {
+        SeHdrVersioned::SeHdrBinV1(bin) => bin.aad.pcf,
+    };
+    res.push(
+        "plaintext control flags",
+        format!("{pcf:#018x}"),
+        query.supports_se_hdr_pcf(pcf),
+    );
+    Ok(res)
+}
+
+pub fn check_add_secret_request(query: &UvQuery, path: &str) -> Result<CheckedFile, String> {
+    let err = |e: &dyn std::fmt::Display| format!("Invalid add-secret request {path}: {e}");
+    let asrcb = fs::read(path).map_err(|e| format!("Failed to read {path}: {e}"))?;
+    let version = AddSecretRequest::bin_version(&asrcb).map_err(|e| err(&e))?;
+    let flags = AddSecretRequest::bin_flags(&asrcb).map_err(|e| err(&e))?;
+    let secret_type = AddSecretRequest::bin_secret_type(&asrcb).map_err(|e| err(&e))?;
+
+    let mut res = CheckedFile::new(path, "add-secret-request");
+    res.push(
+        "add-secret request version",
+        format!("{version:#x}"),
+        query.supports_add_secret_req_version(version),
+    );
+    res.push(
+        "plaintext add-secret flags",
+        format!("{flags:#018x}"),
+        query.supports_add_secret_pcf(flags),
+    );
+    let secret_type = u16::from(secret_type);
+    let desc = match query_desc::describe(query_desc::SECRET_TYPES, secret_type.into()) {
+        Some(BitDesc::Desc(d)) => format!("{secret_type:#06x} ({d})"),
+        _ => format!("{secret_type:#06x}"),
+    };
+    res.push(
+        "secret type",
+        desc,
+        query.supports_secret_type(secret_type.into()),
+    );
+    Ok(res)
+}
+
+pub fn check_attestation_request(query: &UvQuery, path: &str) -> Result<CheckedFile, String> {
+    let err = |e: &dyn std::fmt::Display| format!("Invalid attestation request {path}: {e}");
+    let arcb = fs::read(path).map_err(|e| format!("Failed to read {path}: {e}"))?;
+    let version = AttestationRequest::bin_version(&arcb).map_err(|e| err(&e))?;
+
+    let mut res = CheckedFile::new(path, "attestation-request");
+    res.push(
+        "attestation request version",
+        format!("{version:#x}"),
+        query.supports_att_req_version(version),
+    );
+    // the flags can only be interpreted for known request versions
+    if let Ok(auth) = AttestationRequest::auth_bin(&arcb) {
+        let flags = auth.flags().bits();
+        res.push(
+            "plaintext attestation flags",
+            format!("{flags:#018x}"),
+            query.supports_att_pflags(flags),
+        );
+    }
+    Ok(res)
+}
+
+/*──────────────
+Printer for text mode
+──────────────*/
+pub fn print_text(results: &[CheckedFile]) {
+    for (n, res) in results.iter().enumerate() {
+        if n > 0 {
+            println!();
+        }
+        println!("{} '{}':", res.kind, res.file);
+        for c in &res.checks {
+            match c.supported {
+                Some(true) => println!(" ✓ {} {} is supported", c.property, c.value),
+                Some(false) => println!(" ✘ {} {} is not supported", c.property, c.value),
+                None => println!(
+                    " ? {} {}: not reported by the Ultravisor",
+                    c.property, c.value
+                ),
+            }
+        }
+    }
+}
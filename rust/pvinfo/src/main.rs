@@ -1,90 +1,71 @@
 //! pvinfo tool implementation
 
+mod check;
+
 //command-line argument parsing library
-use clap::{CommandFactory, Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 
-// YAML serialization
+// YAML/JSON serialization
 use serde::Serialize;
 
-//allows file reading
-use std::fs;
-
-//path handling
-use std::path::Path;
-
 //exit program with exit(0) or error codes
 use std::process;
 
-/*──────────────
-Base Directories
-───────────────*/
-//Constants point to where data files are stored in the project
-const UV_QUERY_DIR: &str = "/home/annmariyajojo/Documents/s390_tools_learning/s390-tools/uv/query";
-
-const UV_FOLDER: &str = "/home/annmariyajojo/Documents/s390_tools_learning/s390-tools/uv";
-const PVINFO_SRC: &str =
-    "/home/annmariyajojo/Documents/s390_tools_learning/s390-tools/rust/pvinfo/src";
-
-// Files in uv/query
-// Each constant is the filename for a specific ultravisor query or description
-const FACILITIES_FILE: &str = "facilities";
-const FACILITIES_DESC_FILE: &str = "facilities_value.txt";
-
-const FEATURE_BITS_FILE: &str = "feature_indications";
-const FEATURE_TEXT_FILE: &str = "feature_indications_value.txt";
-
-const SUPP_ADD_SECRET_PCF_FILE: &str = "supp_add_secret_pcf";
-const SUPP_ADD_SECRET_REQ_FILE: &str = "supp_add_secret_req_ver";
-const SUPP_ATTEST_REQ_VER_FILE: &str = "supp_att_req_hdr_ver";
-
-const SUPP_SE_HDR_PCF_FILE: &str = "supp_se_hdr_pcf";
-const SUPP_SE_HDR_PCF_DESC_FILE: &str = "supp_se_hdr_pcf_value.txt";
-
-const SUPP_SE_HDR_VER_FILE: &str = "supp_se_hdr_ver";
-
-const SUPP_ATT_PFLAGS_FILE: &str = "supp_att_pflags";
-const SUPP_ATT_PFLAGS_DESC_FILE: &str = "supp_att_pflags_value.txt";
-
-const SUPP_SECRET_TYPES_FILE: &str = "supp_secret_types";
-const SUPP_SECRET_TYPES_DESC_FILE: &str = "supp_secret_types_value.txt";
-
-const MAX_ADDRESS_FILE: &str = "max_address";
-const MAX_ASSOC_SECRETS_FILE: &str = "max_assoc_secrets";
-const MAX_CPUS_FILE: &str = "max_cpus";
-const MAX_GUESTS_FILE: &str = "max_guests";
-const MAX_RETR_SECRETS_FILE: &str = "max_retr_secrets";
-const MAX_SECRETS_FILE: &str = "max_secrets";
+// typed Ultravisor query data shared with the other pv tools
+use pv_core::misc::set_sysfs_root;
+use pv_core::uv::query_desc::{self, BitDesc};
+use pv_core::uv::{SeMode, UvBitmask, UvDevice, UvDeviceInfo, UvLimits, UvQuery, UvSnapshot};
 
 /*──────────────
-YAML structures
+Output structures
 ──────────────*/
-// PvInfo holds all collected data
+// PvInfo holds the selected sections. Sections which are not selected or
+// not reported by the system are not serialized.
 
 #[derive(Serialize, Default)]
 struct PvInfo {
-    se_status: Vec<String>,
-    facilities: Vec<String>,
-    feature_indications: Vec<String>,
-    supported_plaintext_add_secret_flags: Vec<String>,
-    supported_add_secret_request_versions: Vec<String>,
-    supported_attestation_request_versions: Vec<String>,
-    supported_plaintext_control_flags: Vec<String>,
-    supported_se_header_versions: Vec<String>,
-    supported_plaintext_attestation_flags: Vec<String>,
-    supported_secret_types: Vec<String>,
-    limits: Limits,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    se_status: Option<SeMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    facilities: Option<Vec<BitEntry>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    feature_indications: Option<Vec<BitEntry>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    supported_plaintext_add_secret_flags: Option<Vec<BitEntry>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    supported_add_secret_request_versions: Option<Vec<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    supported_attestation_request_versions: Option<Vec<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    supported_plaintext_control_flags: Option<Vec<BitEntry>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    supported_se_header_versions: Option<Vec<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    supported_plaintext_attestation_flags: Option<Vec<BitEntry>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    supported_secret_types: Option<Vec<BitEntry>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limits: Option<UvLimits>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uvdevice: Option<UvDeviceEntry>,
 }
 
-//Struct groups system limits into a YAML map.
+// Supported IOCTLs of the uvdevice and the corresponding Ultravisor calls,
+// LSB0 bitmasks as reported by the uvdevice.
+#[derive(Serialize)]
+struct UvDeviceEntry {
+    supp_uvio_cmds: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    supp_uv_cmds: Option<u64>,
+}
 
-#[derive(Serialize, Default)]
-struct Limits {
-    maximal_address: u64,
-    maximal_number_of_associated_secrets: u64,
-    maximal_number_of_cpus: u64,
-    maximal_number_of_se_guests: u64,
-    maximal_number_of_retrievable_secrets: u64,
-    maximal_number_of_secrets: u64,
+// One set bit of an Ultravisor query bitmask; reserved and confidential
+// bits have no description.
+#[derive(Serialize)]
+struct BitEntry {
+    bit: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'static str>,
 }
 
 /*─────
@@ -117,14 +98,42 @@ struct Cli {
     supported_secret_types: bool,
     #[arg(long)]
     limits: bool,
+    #[arg(long)]
+    uvdevice: bool,
 
-    #[arg(long, default_value = "text")]
-    format: String, // "text" or "yaml"
+    /// Output format.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text, global = true)]
+    format: OutputFormat,
+
+    /// Read the Ultravisor attributes below DIR instead of /sys (for testing).
+    #[arg(long, value_name = "DIR", global = true)]
+    sysfs_root: Option<String>,
+
+    /// Show the Ultravisor capabilities captured by 'pvinfo snapshot' in FILE.
+    #[arg(
+        long,
+        value_name = "FILE",
+        global = true,
+        conflicts_with = "sysfs_root"
+    )]
+    from_snapshot: Option<String>,
 
     #[command(subcommand)]
     command: Option<Commands>,
 }
 
+// Output formats
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    /// Human readable text
+    Text,
+    /// YAML
+    Yaml,
+    /// JSON
+    Json,
+}
+
 // Subcommands
 
 #[derive(Subcommand, Debug)]
@@ -139,8 +148,68 @@ enum Commands {
         #[arg(long)]
         header: bool,
     },
+
+    /// Capture the Ultravisor capabilities of this system into a file.
+    ///
+    /// The file can be shown on another system with '--from-snapshot'.
+    Snapshot {
+        /// Write the snapshot to FILE.
+        #[arg(short, long, value_name = "FILE")]
+        output: String,
+    },
+
+    /// Check if the Ultravisor of this system supports SE images and requests.
+    ///
+    /// Exits with 2 if any of the files uses a feature that is not supported.
+    #[command(group(clap::ArgGroup::new("files").required(true).multiple(true)))]
+    Check {
+        /// SE image to check.
+        #[arg(long, value_name = "FILE", group = "files")]
+        image: Vec<String>,
+
+        /// Add-secret request to check.
+        #[arg(long, value_name = "FILE", group = "files")]
+        add_secret_request: Vec<String>,
+
+        /// Attestation request to check.
+        #[arg(long, value_name = "FILE", group = "files")]
+        attestation_request: Vec<String>,
+    },
+}
+
+// Sections of the output, in output order
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Section {
+    SeStatus,
+    Facilities,
+    FeatureIndications,
+    AddSecretFlags,
+    AddSecretReqVersions,
+    AttestationReqVersions,
+    PlaintextControlFlags,
+    SeHeaderVersions,
+    AttestationFlags,
+    SecretTypes,
+    Limits,
+    UvDevice,
 }
 
+const ALL_SECTIONS: [Section; 12] = [
+    Section::SeStatus,
+    Section::Facilities,
+    Section::FeatureIndications,
+    Section::AddSecretFlags,
+    Section::AddSecretReqVersions,
+    Section::AttestationReqVersions,
+    Section::PlaintextControlFlags,
+    Section::SeHeaderVersions,
+    Section::AttestationFlags,
+    Section::SecretTypes,
+    Section::Limits,
+    Section::UvDevice,
+];
+
 /*────────────
 Main
 ────────────*/
@@ -155,447 +224,416 @@ fn main() {
     //Parse CLI args into args
     let args = Cli::parse();
 
-    // Verify UV folder exists or not
-    verify_uv_folder();
-    let mut any = false;
-
-    // Handling Subcommands
-    match &args.command {
-        Some(Commands::SupportedFlags {
-            secret,
-            attestation,
-            header,
-        }) => {
-            if *secret {
-                print_list("Supported Secret Types:", collect_secret_types());
-                print_list(
-                    "Supported Add Secret Request Versions:",
-                    collect_add_secret_req_versions(),
-                );
-                print_list(
-                    "Supported Plaintext Add Secret Flags:",
-                    collect_add_secret_flags(),
-                );
-            }
-            if *attestation {
-                print_list(
-                    "Supported Plaintext Attestation Flags:",
-                    collect_attestation_flags(),
-                );
-                print_list(
-                    "Supported Attestation Request Versions:",
-                    collect_attestation_req_versions(),
-                );
-            }
-            if *header {
-                print_list(
-                    "Supported SE Header Versions:",
-                    collect_se_header_versions(),
-                );
-                print_list(
-                    "Supported Plaintext Control Flags:",
-                    collect_plaintext_control_flags(),
-                );
-            }
-            if !*secret && !*attestation && !*header {
-                // all three
-                print_list("Supported Secret Types:", collect_secret_types());
-                print_list(
-                    "Supported Add Secret Request Versions:",
-                    collect_add_secret_req_versions(),
-                );
-                print_list(
-                    "Supported Plaintext Add Secret Flags:",
-                    collect_add_secret_flags(),
-                );
-                print_list(
-                    "Supported Plaintext Attestation Flags:",
-                    collect_attestation_flags(),
-                );
-                print_list(
-                    "Supported Attestation Request Versions:",
-                    collect_attestation_req_versions(),
-                );
-                print_list(
-                    "Supported SE Header Versions:",
-                    collect_se_header_versions(),
-                );
-                print_list(
-                    "Supported Plaintext Control Flags:",
-                    collect_plaintext_control_flags(),
-                );
-            }
-            return;
+    // Relocate /sys, e.g. to a copy of the attributes of another system
+    if let Some(root) = &args.sysfs_root {
+        if let Err(err) = set_sysfs_root(root) {
+            eprintln!("{err}");
+            process::exit(1);
         }
+    }
 
-        // If --format=yaml then collect everything and print in YAML
-        None => {
-            if args.format == "yaml" {
-                let info = collect_all();
-                println!("{}", serde_yaml::to_string(&info).unwrap());
-                return;
-            }
-
-            if args.se_status {
-                print_list("se_status:", vec![collect_se_status()]);
-                any = true;
-            }
-            if args.facilities {
-                print_list(
-                    "Facilities: Installed Ultravisor Calls",
-                    collect_facilities(),
-                );
-                any = true;
-            }
-            if args.feature_indications {
-                print_list(
-                    "Feature Indications: Ultravisor Features",
-                    collect_feature_indications(),
-                );
-                any = true;
-            }
-            if args.supported_plaintext_add_secret_flags {
-                print_list(
-                    "Supported Plaintext Add Secret Flags:",
-                    collect_add_secret_flags(),
-                );
-                any = true;
-            }
-            if args.supported_add_secret_request_versions {
-                print_list(
-                    "Supported Add Secret Request Versions:",
-                    collect_add_secret_req_versions(),
-                );
-                any = true;
-            }
-            if args.supported_attestation_request_versions {
-                print_list(
-                    "Supported Attestation Request Versions:",
-                    collect_attestation_req_versions(),
-                );
-                any = true;
-            }
-            if args.supported_plaintext_control_flags {
-                print_list(
-                    "Supported Plaintext Control Flags:",
-                    collect_plaintext_control_flags(),
-                );
-                any = true;
-            }
-            if args.supported_se_header_versions {
-                print_list(
-                    "Supported SE Header Versions:",
-                    collect_se_header_versions(),
-                );
-                any = true;
-            }
-            if args.supported_plaintext_attestation_flags {
-                print_list(
-                    "Supported Plaintext Attestation Flags:",
-                    collect_attestation_flags(),
-                );
-                any = true;
-            }
-            if args.supported_secret_types {
-                print_list("Supported Secret Types:", collect_secret_types());
-                any = true;
-            }
-            if args.limits {
-                print_limits(collect_limits());
-                any = true;
-            }
+    if let Some(Commands::Snapshot { output }) = &args.command {
+        write_snapshot(&args, output);
+    }
 
-            // If no flag given print everything in text mode
-            if !any {
-                // default show all in text mode
-                print_list("se_status:", vec![collect_se_status()]);
-                print_list(
-                    "\nFacilities: Installed Ultravisor Calls",
-                    collect_facilities(),
-                );
-                print_list(
-                    "\nFeature Indications: Ultravisor Features",
-                    collect_feature_indications(),
-                );
-                print_list(
-                    "\nSupported Plaintext Add Secret Flags:",
-                    collect_add_secret_flags(),
-                );
-                print_list(
-                    "\nSupported Add Secret Request Versions:",
-                    collect_add_secret_req_versions(),
-                );
-                print_list(
-                    "\nSupported Attestation Request Versions:",
-                    collect_attestation_req_versions(),
-                );
-                print_list(
-                    "\nSupported Plaintext Control Flags:",
-                    collect_plaintext_control_flags(),
-                );
-                print_list(
-                    "\nSupported SE Header Versions:",
-                    collect_se_header_versions(),
-                );
-                print_list(
-                    "\nSupported Plaintext Attestation Flags:",
-                    collect_attestation_flags(),
-                );
-                print_list("\nSupported Secret Types:", collect_secret_types());
-                println!();
-                print_limits(collect_limits());
-            }
+    let (query, uvdevice) = match &args.from_snapshot {
+        Some(file) => {
+            let snapshot = read_snapshot(file);
+            let query = snapshot.query().unwrap_or_else(|err| {
+                eprintln!("{file}: {err}");
+                process::exit(1);
+            });
+            (query, snapshot.uvdevice_info())
         }
+        None => (read_query(&args), read_uvdevice_info()),
+    };
+
+    if let Some(Commands::Check {
+        image,
+        add_secret_request,
+        attestation_request,
+    }) = &args.command
+    {
+        run_check(
+            &query,
+            args.format,
+            image,
+            add_secret_request,
+            attestation_request,
+        );
     }
-}
 
-/*──────────────
-Collectors
-──────────────*/
-
-//Reads prot_virt_guest and prot_virt_host
-
-fn collect_se_status() -> String {
-    let guest_flag = read_flag_file(&Path::new(UV_FOLDER).join("prot_virt_guest"));
-    let host_flag = read_flag_file(&Path::new(UV_FOLDER).join("prot_virt_host"));
-    match (guest_flag, host_flag) {
-        (true, false) => "Secure Execution Guest Mode".to_string(),
-        (false, true) => "Secure Execution Host Mode".to_string(),
-        (false, false) => "Secure Execution is disabled".to_string(),
-        (true, true) => "Configuration error: both Guest and Host enabled".to_string(),
+    let sections = selected_sections(&args);
+    let info = collect(&query, uvdevice.as_ref(), &sections);
+    match args.format {
+        OutputFormat::Text => print_text(&info, &sections),
+        OutputFormat::Yaml => print!("{}", serde_yaml::to_string(&info).unwrap()),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&info).unwrap()),
     }
 }
 
-fn collect_facilities() -> Vec<String> {
-    read_hex_mask(&Path::new(UV_QUERY_DIR).join(FACILITIES_FILE))
-        .map(|m| {
-            collect_bitmask_with_desc(m, &Path::new(PVINFO_SRC).join(FACILITIES_DESC_FILE), &[10])
-        })
-        .unwrap_or_else(|| vec!["no active entries".into()])
-}
-
-fn collect_feature_indications() -> Vec<String> {
-    read_hex_mask(&Path::new(UV_QUERY_DIR).join(FEATURE_BITS_FILE))
-        .map(|m| {
-            collect_bitmask_with_desc(
-                m,
-                &Path::new(PVINFO_SRC).join(FEATURE_TEXT_FILE),
-                &[0, 2, 3],
-            )
-        })
-        .unwrap_or_else(|| vec!["no active entries".into()])
-}
-
-fn collect_add_secret_flags() -> Vec<String> {
-    if let Some(mask) = read_hex_mask(&Path::new(UV_QUERY_DIR).join(SUPP_ADD_SECRET_PCF_FILE)) {
-        let mut out = Vec::new();
-        if (mask & (1u64 << 0)) != 0 {
-            out.push("Disable dumping".to_string());
+// Read the UV folder, exit if it does not exist
+
+fn read_query(args: &Cli) -> UvQuery {
+    match UvQuery::read() {
+        Ok(q) => q,
+        Err(pv_core::Error::FileAccess { path, .. }) if !path.exists() => {
+            println!("UV directory not found at {}", path.display());
+            println!("Does not operate as a SE host or SE guest.");
+            // the capabilities are unknown, so no file can be checked
+            let checking = matches!(args.command, Some(Commands::Check { .. }));
+            process::exit(checking as i32);
         }
-        if out.is_empty() {
-            out.push("no active flags".to_string());
+        Err(err) => {
+            eprintln!("{err}");
+            process::exit(1);
         }
-        out
-    } else {
-        vec!["no active flags".into()]
     }
 }
 
-fn collect_add_secret_req_versions() -> Vec<String> {
-    read_hex_mask(&Path::new(UV_QUERY_DIR).join(SUPP_ADD_SECRET_REQ_FILE))
-        .map(collect_version_mask)
-        .unwrap_or_else(|| vec!["no supported versions".into()])
-}
-
-fn collect_attestation_req_versions() -> Vec<String> {
-    read_hex_mask(&Path::new(UV_QUERY_DIR).join(SUPP_ATTEST_REQ_VER_FILE))
-        .map(collect_version_mask)
-        .unwrap_or_else(|| vec!["no supported versions".into()])
-}
+// The uvdevice is only available in SE guests
 
-fn collect_plaintext_control_flags() -> Vec<String> {
-    read_hex_mask(&Path::new(UV_QUERY_DIR).join(SUPP_SE_HDR_PCF_FILE))
-        .map(|m| {
-            collect_bitmask_with_desc(
-                m,
-                &Path::new(PVINFO_SRC).join(SUPP_SE_HDR_PCF_DESC_FILE),
-                &[],
-            )
-        })
-        .unwrap_or_else(|| vec!["no active entries".into()])
+fn read_uvdevice_info() -> Option<UvDeviceInfo> {
+    UvDevice::open().and_then(|uv| UvDeviceInfo::get(&uv)).ok()
 }
 
-fn collect_se_header_versions() -> Vec<String> {
-    read_hex_mask(&Path::new(UV_QUERY_DIR).join(SUPP_SE_HDR_VER_FILE))
-        .map(collect_version_mask)
-        .unwrap_or_else(|| vec!["no supported versions".into()])
-}
+/*──────────────
+Snapshots
+──────────────*/
 
-fn collect_attestation_flags() -> Vec<String> {
-    read_hex_mask(&Path::new(UV_QUERY_DIR).join(SUPP_ATT_PFLAGS_FILE))
-        .map(|m| {
-            collect_bitmask_with_desc(
-                m,
-                &Path::new(PVINFO_SRC).join(SUPP_ATT_PFLAGS_DESC_FILE),
-                &[],
-            )
-        })
-        .unwrap_or_else(|| vec!["no active entries".into()])
+fn read_snapshot(file: &str) -> UvSnapshot {
+    let snapshot: UvSnapshot = std::fs::read_to_string(file)
+        .map_err(|err| err.to_string())
+        .and_then(|content| serde_yaml::from_str(&content).map_err(|err| err.to_string()))
+        .unwrap_or_else(|err| {
+            eprintln!("Failed to read the snapshot {file}: {err}");
+            process::exit(1);
+        });
+    if let Err(err) = snapshot.check_version() {
+        eprintln!("{file}: {err}");
+        process::exit(1);
+    }
+    snapshot
 }
 
-fn collect_secret_types() -> Vec<String> {
-    read_hex_mask(&Path::new(UV_QUERY_DIR).join(SUPP_SECRET_TYPES_FILE))
-        .map(|m| {
-            collect_bitmask_with_desc(
-                m,
-                &Path::new(PVINFO_SRC).join(SUPP_SECRET_TYPES_DESC_FILE),
-                &[],
-            )
-        })
-        .unwrap_or_else(|| vec!["no active entries".into()])
+// Capture the live system, or re-write a snapshot given by --from-snapshot
+
+fn write_snapshot(args: &Cli, output: &str) -> ! {
+    let snapshot = match &args.from_snapshot {
+        Some(file) => read_snapshot(file),
+        None => UvSnapshot::capture().unwrap_or_else(|err| {
+            eprintln!("{err}");
+            process::exit(1);
+        }),
+    };
+    let content = serde_yaml::to_string(&snapshot).unwrap();
+    if let Err(err) = std::fs::write(output, content) {
+        eprintln!("Failed to write the snapshot {output}: {err}");
+        process::exit(1);
+    }
+    process::exit(0)
 }
 
-fn collect_limits() -> Limits {
-    Limits {
-        maximal_address: read_integer(&Path::new(UV_QUERY_DIR).join(MAX_ADDRESS_FILE)).unwrap_or(0),
-        maximal_number_of_associated_secrets: read_integer(
-            &Path::new(UV_QUERY_DIR).join(MAX_ASSOC_SECRETS_FILE),
+// Check the given files against the Ultravisor capabilities and exit
+
+fn run_check(
+    query: &UvQuery,
+    format: OutputFormat,
+    images: &[String],
+    add_secret_requests: &[String],
+    attestation_requests: &[String],
+) -> ! {
+    let results: Result<Vec<_>, String> = images
+        .iter()
+        .map(|f| check::check_image(query, f))
+        .chain(
+            add_secret_requests
+                .iter()
+                .map(|f| check::check_add_secret_request(query, f)),
         )
-        .unwrap_or(0),
-        maximal_number_of_cpus: read_integer(&Path::new(UV_QUERY_DIR).join(MAX_CPUS_FILE))
-            .unwrap_or(0),
-        maximal_number_of_se_guests: read_integer(&Path::new(UV_QUERY_DIR).join(MAX_GUESTS_FILE))
-            .unwrap_or(0),
-        maximal_number_of_retrievable_secrets: read_integer(
-            &Path::new(UV_QUERY_DIR).join(MAX_RETR_SECRETS_FILE),
+        .chain(
+            attestation_requests
+                .iter()
+                .map(|f| check::check_attestation_request(query, f)),
         )
-        .unwrap_or(0),
-        maximal_number_of_secrets: read_integer(&Path::new(UV_QUERY_DIR).join(MAX_SECRETS_FILE))
-            .unwrap_or(0),
+        .collect();
+    let results = results.unwrap_or_else(|err| {
+        eprintln!("{err}");
+        process::exit(1);
+    });
+
+    match format {
+        OutputFormat::Text => check::print_text(&results),
+        OutputFormat::Yaml => print!("{}", serde_yaml::to_string(&results).unwrap()),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&results).unwrap()),
     }
+    process::exit(if results.iter().all(|r| r.is_supported()) {
+        0
+    } else {
+        2
+    })
 }
 
-fn collect_all() -> PvInfo {
-    PvInfo {
-        se_status: vec![collect_se_status()],
-        facilities: collect_facilities(),
-        feature_indications: collect_feature_indications(),
-        supported_plaintext_add_secret_flags: collect_add_secret_flags(),
-        supported_add_secret_request_versions: collect_add_secret_req_versions(),
-        supported_attestation_request_versions: collect_attestation_req_versions(),
-        supported_plaintext_control_flags: collect_plaintext_control_flags(),
-        supported_se_header_versions: collect_se_header_versions(),
-        supported_plaintext_attestation_flags: collect_attestation_flags(),
-        supported_secret_types: collect_secret_types(),
-        limits: collect_limits(),
+// Sections selected by the subcommand or the flags, all if none is selected
+
+fn selected_sections(args: &Cli) -> Vec<Section> {
+    let selected: Vec<Section> = match &args.command {
+        Some(Commands::SupportedFlags {
+            secret,
+            attestation,
+            header,
+        }) => {
+            let all = !*secret && !*attestation && !*header;
+            let mut s = Vec::new();
+            if *secret || all {
+                s.extend([
+                    Section::SecretTypes,
+                    Section::AddSecretReqVersions,
+                    Section::AddSecretFlags,
+                ]);
+            }
+            if *attestation || all {
+                s.extend([Section::AttestationFlags, Section::AttestationReqVersions]);
+            }
+            if *header || all {
+                s.extend([Section::SeHeaderVersions, Section::PlaintextControlFlags]);
+            }
+            s
+        }
+        Some(Commands::Check { .. } | Commands::Snapshot { .. }) => unreachable!("handled before"),
+        None => [
+            (args.se_status, Section::SeStatus),
+            (args.facilities, Section::Facilities),
+            (args.feature_indications, Section::FeatureIndications),
+            (
+                args.supported_plaintext_add_secret_flags,
+                Section::AddSecretFlags,
+            ),
+            (
+                args.supported_add_secret_request_versions,
+                Section::AddSecretReqVersions,
+            ),
+            (
+                args.supported_attestation_request_versions,
+                Section::AttestationReqVersions,
+            ),
+            (
+                args.supported_plaintext_control_flags,
+                Section::PlaintextControlFlags,
+            ),
+            (args.supported_se_header_versions, Section::SeHeaderVersions),
+            (
+                args.supported_plaintext_attestation_flags,
+                Section::AttestationFlags,
+            ),
+            (args.supported_secret_types, Section::SecretTypes),
+            (args.limits, Section::Limits),
+            (args.uvdevice, Section::UvDevice),
+        ]
+        .into_iter()
+        .filter(|(set, _)| *set)
+        .map(|(_, s)| s)
+        .collect(),
+    };
+    if selected.is_empty() {
+        ALL_SECTIONS.to_vec()
+    } else {
+        selected
     }
 }
 
 /*──────────────
-Shared helpers
+Collectors
 ──────────────*/
-fn verify_uv_folder() {
-    if !Path::new(UV_FOLDER).exists() {
-        println!("UV directory not found at {UV_FOLDER}");
-        println!("Does not operate as a SE host or SE guest.");
-        process::exit(0);
-    }
-}
 
-fn read_flag_file(path: &Path) -> bool {
-    if let Ok(content) = fs::read_to_string(path) {
-        return content.trim() == "1";
-    }
-    false
+fn bits(mask: &Option<UvBitmask>, table: &[(usize, BitDesc)]) -> Option<Vec<BitEntry>> {
+    mask.as_ref().map(|m| {
+        m.iter_set()
+            .map(|bit| BitEntry {
+                bit,
+                description: match query_desc::describe(table, bit) {
+                    Some(BitDesc::Desc(d)) => Some(d),
+                    Some(BitDesc::Confidential) | None => None,
+                },
+            })
+            .collect()
+    })
 }
 
-fn read_hex_mask(path: &Path) -> Option<u64> {
-    let content = fs::read_to_string(path).ok()?;
-    let first_line = content.lines().find(|l| !l.trim().is_empty())?.trim();
-    let hex_str = first_line.trim_start_matches("0x");
-    u64::from_str_radix(hex_str, 16).ok()
-}
+// Versions are only reported if the system provides the attribute
 
-fn read_integer(path: &Path) -> Option<u64> {
-    let content = fs::read_to_string(path).ok()?;
-    let first_line = content.lines().find(|l| !l.trim().is_empty())?.trim();
-    first_line.parse::<u64>().ok()
+fn versions(mask: &Option<UvBitmask>, versions: Vec<u32>) -> Option<Vec<u32>> {
+    mask.as_ref().map(|_| versions)
 }
 
-fn collect_bitmask_with_desc(mask: u64, desc_file: &Path, reserved_bits: &[usize]) -> Vec<String> {
-    let mut out = Vec::new();
-    let content = fs::read_to_string(desc_file).unwrap_or_default();
-    let lines: Vec<&str> = content.lines().collect();
-
-    for (line_index, line) in lines.iter().enumerate() {
-        let bit_position = 63 - line_index;
-
-        if (mask & (1u64 << bit_position)) != 0 {
-            if reserved_bits.contains(&line_index) {
-                out.push(format!(
-                    "Confidential - report as reserved Bit-{}",
-                    line_index
-                ));
-            } else if line.trim() == "Reserved" {
-                out.push(format!("Reserved Bit-{}", line_index));
-            } else {
-                out.push(line.to_string());
+fn collect(query: &UvQuery, uvdevice: Option<&UvDeviceInfo>, sections: &[Section]) -> PvInfo {
+    let mut info = PvInfo::default();
+    for section in sections {
+        match section {
+            Section::SeStatus => info.se_status = query.se_mode,
+            Section::Facilities => {
+                info.facilities = bits(&query.facilities, query_desc::FACILITIES)
+            }
+            Section::FeatureIndications => {
+                info.feature_indications =
+                    bits(&query.feature_indications, query_desc::FEATURE_INDICATIONS)
+            }
+            Section::AddSecretFlags => {
+                info.supported_plaintext_add_secret_flags =
+                    bits(&query.supp_add_secret_pcf, query_desc::ADD_SECRET_PCF)
+            }
+            Section::AddSecretReqVersions => {
+                info.supported_add_secret_request_versions = versions(
+                    &query.supp_add_secret_req_ver,
+                    query.add_secret_req_versions(),
+                )
+            }
+            Section::AttestationReqVersions => {
+                info.supported_attestation_request_versions =
+                    versions(&query.supp_att_req_hdr_ver, query.att_req_versions())
+            }
+            Section::PlaintextControlFlags => {
+                info.supported_plaintext_control_flags =
+                    bits(&query.supp_se_hdr_pcf, query_desc::SE_HDR_PCF)
+            }
+            Section::SeHeaderVersions => {
+                info.supported_se_header_versions =
+                    versions(&query.supp_se_hdr_ver, query.se_hdr_versions())
+            }
+            Section::AttestationFlags => {
+                info.supported_plaintext_attestation_flags =
+                    bits(&query.supp_att_pflags, query_desc::ATT_PFLAGS)
+            }
+            Section::SecretTypes => {
+                info.supported_secret_types =
+                    bits(&query.supp_secret_types, query_desc::SECRET_TYPES)
+            }
+            Section::Limits => info.limits = Some(query.limits.clone()),
+            Section::UvDevice => {
+                info.uvdevice = uvdevice.map(|d| UvDeviceEntry {
+                    supp_uvio_cmds: d.supp_uvio_cmds(),
+                    supp_uv_cmds: d.supp_uv_cmds(),
+                })
             }
         }
     }
+    info
+}
 
-    if out.is_empty() {
-        out.push("no active entries".to_string());
+/*──────────────
+Printers for text mode
+──────────────*/
+fn print_text(info: &PvInfo, sections: &[Section]) {
+    for (n, section) in sections.iter().enumerate() {
+        // separate the sections by an empty line if everything is shown
+        if n > 0 && sections.len() == ALL_SECTIONS.len() {
+            println!();
+        }
+        match section {
+            Section::SeStatus => {
+                println!("se_status:");
+                match info.se_status {
+                    Some(mode) => println!("{mode}"),
+                    None => println!("not available"),
+                }
+            }
+            Section::Facilities => {
+                print_bits("Facilities: Installed Ultravisor Calls", &info.facilities)
+            }
+            Section::FeatureIndications => print_bits(
+                "Feature Indications: Ultravisor Features",
+                &info.feature_indications,
+            ),
+            Section::AddSecretFlags => print_bits(
+                "Supported Plaintext Add Secret Flags:",
+                &info.supported_plaintext_add_secret_flags,
+            ),
+            Section::AddSecretReqVersions => print_versions(
+                "Supported Add Secret Request Versions:",
+                &info.supported_add_secret_request_versions,
+            ),
+            Section::AttestationReqVersions => print_versions(
+                "Supported Attestation Request Versions:",
+                &info.supported_attestation_request_versions,
+            ),
+            Section::PlaintextControlFlags => print_bits(
+                "Supported Plaintext Control Flags:",
+                &info.supported_plaintext_control_flags,
+            ),
+            Section::SeHeaderVersions => print_versions(
+                "Supported SE Header Versions:",
+                &info.supported_se_header_versions,
+            ),
+            Section::AttestationFlags => print_bits(
+                "Supported Plaintext Attestation Flags:",
+                &info.supported_plaintext_attestation_flags,
+            ),
+            Section::SecretTypes => {
+                print_bits("Supported Secret Types:", &info.supported_secret_types)
+            }
+            Section::Limits => {
+                if let Some(limits) = &info.limits {
+                    print_limits(limits)
+                }
+            }
+            Section::UvDevice => match &info.uvdevice {
+                Some(d) => print!(
+                    "{}",
+                    UvDeviceInfo::from_raw(d.supp_uvio_cmds, d.supp_uv_cmds)
+                ),
+                None => println!("uvdevice:\nnot available"),
+            },
+        }
     }
-    out
 }
 
-fn collect_version_mask(mask: u64) -> Vec<String> {
-    let mut out = Vec::new();
-    for bit in 0..64 {
-        let actual_bit = 63 - bit;
-        if (mask & (1u64 << actual_bit)) != 0 {
-            let version = (bit + 1) * 0x100;
-            out.push(format!("version {:x} hex is supported", version));
+fn print_bits(title: &str, entries: &Option<Vec<BitEntry>>) {
+    println!("{}", title);
+    match entries {
+        None => println!("not available"),
+        Some(entries) if entries.is_empty() => println!("no active entries"),
+        Some(entries) => {
+            for e in entries {
+                match e.description {
+                    Some(d) => println!("{}", d),
+                    None => println!("Reserved Bit-{}", e.bit),
+                }
+            }
         }
     }
-    if out.is_empty() {
-        out.push("no supported versions".to_string());
-    }
-    out
 }
 
-/*──────────────
-Printers for text mode
-──────────────*/
-fn print_list(title: &str, items: Vec<String>) {
+fn print_versions(title: &str, versions: &Option<Vec<u32>>) {
     println!("{}", title);
-    for i in items {
-        println!("{}", i);
+    match versions {
+        Some(v) if !v.is_empty() => {
+            for version in v {
+                println!("version {:x} hex is supported", version);
+            }
+        }
+        _ => println!("no supported versions"),
     }
 }
 
-fn print_limits(lim: Limits) {
+fn print_limits(lim: &UvLimits) {
+    let value = |v: Option<u64>| v.map_or("not available".to_string(), |v| v.to_string());
     println!("Limits:");
-    println!("Maximal Address for a SE-Guest {}", lim.maximal_address);
+    println!("Maximal Address for a SE-Guest {}", value(lim.max_address));
     println!(
         "Maximal number of associated secrets {}",
-        lim.maximal_number_of_associated_secrets
+        value(lim.max_assoc_secrets)
     );
     println!(
         "Maximal number of CPUs in one SE-Guest {}",
-        lim.maximal_number_of_cpus
-    );
-    println!(
-        "Maximal number of SE-Guests {}",
-        lim.maximal_number_of_se_guests
+        value(lim.max_cpus)
     );
+    println!("Maximal number of SE-Guests {}", value(lim.max_guests));
     println!(
         "Maximal number of retrievable secrets {}",
-        lim.maximal_number_of_retrievable_secrets
+        value(lim.max_retr_secrets)
     );
     println!(
         "Maximal number of secrets in the system {}",
-        lim.maximal_number_of_secrets
+        value(lim.max_secrets)
     );
 }
@@ -6,7 +6,7 @@ use std::fmt::Display;
 
 use clap::error::ErrorKind::ValueValidation;
 use clap::{ArgGroup, Args, CommandFactory, Parser, Subcommand, ValueEnum, ValueHint};
-use utils::{CertificateOptions, DeprecatedVerbosityOptions, STDOUT};
+use utils::{CertificateOptions, DeprecatedVerbosityOptions, UvDeviceOptions, STDOUT};
 
 /// Manage secrets for IBM Secure Execution guests.
 ///
@@ -130,16 +130,18 @@ pub struct CreateSecretOpt {
     #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath,)]
     pub user_data: Option<String>,
 
-    /// Use the content of FILE as user signing key.
+    /// Use KEY as user signing key.
     ///
-    /// Adds a signature calculated from the key in FILE to the add-secret request. The
-    /// file must be in DER or PEM format containing a private key. Supported are RSA 2048 &
-    /// 3072-bit and EC(secp521r1) keys. The firmware ignores the content, but the request tag
-    /// protects the signature. The user-signing key signs the request. The location of the
-    /// signature is filled with zeros during the signature calculation. The request tag also
-    /// secures the signature. See man pvsecret verify for more details. Optional. No signature
-    /// by default.
-    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath,)]
+    /// Adds a signature calculated from KEY to the add-secret request. KEY is either a file in
+    /// DER or PEM format containing a private key, or a URI that OpenSSL can load a private key
+    /// from, for example, a 'pkcs11:' URI of a key in a hardware security module. For URIs, the
+    /// required OpenSSL provider must be configured; you are prompted for a PIN if required.
+    /// Supported are RSA 2048 & 3072-bit and EC(secp521r1) keys. The firmware ignores the
+    /// content, but the request tag protects the signature. The user-signing key signs the
+    /// request. The location of the signature is filled with zeros during the signature
+    /// calculation. The request tag also secures the signature. See man pvsecret verify for more
+    /// details. Optional. No signature by default.
+    #[arg(long, value_name = "KEY", value_hint = ValueHint::FilePath,)]
     pub user_sign_key: Option<String>,
 
     /// Do not hash the name, use it directly as secret ID.
@@ -265,12 +267,10 @@ impl Display for RetrieveableSecretInpKind {
     }
 }
 
-// all members s390x only
 #[derive(Args, Debug)]
 pub struct AddSecretOpt {
     /// Specify the request to be sent.
     #[arg(value_name = "FILE", value_hint = ValueHint::FilePath,)]
-    #[cfg(target_arch = "s390x")]
     pub input: String,
 
     /// Force the addition of add-secret requests.
@@ -279,10 +279,12 @@ pub struct AddSecretOpt {
     /// store.
     #[arg(short, long)]
     pub force: bool,
+
+    #[command(flatten)]
+    pub uv_device: UvDeviceOptions,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug, Default)]
-#[cfg(target_arch = "s390x")]
 pub enum ListSecretOutputType {
     /// Human-focused, non-parsable output format
     #[default]
@@ -293,18 +295,24 @@ pub enum ListSecretOutputType {
     Bin,
 }
 
-// all members s390x only
 #[derive(Args, Debug)]
 pub struct ListSecretOpt {
     /// Store the result in FILE
     #[arg(value_name = "FILE", default_value = STDOUT, value_hint = ValueHint::FilePath,)]
-    #[cfg(target_arch = "s390x")]
     pub output: String,
 
     /// Define the output format of the list.
     #[arg(long, value_enum, default_value_t)]
-    #[cfg(target_arch = "s390x")]
     pub format: ListSecretOutputType,
+
+    #[command(flatten)]
+    pub uv_device: UvDeviceOptions,
+}
+
+#[derive(Args, Debug)]
+pub struct LockSecretOpt {
+    #[command(flatten)]
+    pub uv_device: UvDeviceOptions,
 }
 
 #[derive(Args, Debug)]
@@ -331,7 +339,6 @@ pub struct VerifyOpt {
     pub output: String,
 }
 
-// all members s390x only
 #[derive(Args, Debug)]
 pub struct RetrSecretOptions {
     /// Specify the secret ID to be retrieved.
@@ -341,24 +348,23 @@ pub struct RetrSecretOptions {
     /// handle encodes in hexadecimal. Leading zeros are required. If there are multiple secrets in
     /// the store with the same Id there are no guarantees on which specific secret is retrieved.
     /// Use --inform=idx to make sure a specific secret is retrieved.
-    #[cfg(target_arch = "s390x")]
     #[arg(value_name = "ID", value_hint = ValueHint::FilePath)]
     pub input: String,
 
     /// Specify the output path to place the secret value
-    #[cfg(target_arch = "s390x")]
     #[arg(short, long, value_name = "FILE", default_value = STDOUT, value_hint = ValueHint::FilePath)]
     pub output: String,
 
     /// Define input type for the Secret ID
-    #[cfg(target_arch = "s390x")]
     #[arg(long, value_enum, default_value_t)]
     pub inform: RetrInpFmt,
 
     /// Define the output format for the retrieved secret
-    #[cfg(target_arch = "s390x")]
     #[arg(long, value_enum, default_value_t)]
     pub outform: RetrOutFmt,
+
+    #[command(flatten)]
+    pub uv_device: UvDeviceOptions,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug, Default)]
@@ -400,15 +406,15 @@ pub enum Command {
 
     /// Submit an add-secret request to the Ultravisor (s390x only).
     ///
-    /// Perform an add-secret request using a previously generated add-secret request. Only
-    /// available on s390x.
+    /// Perform an add-secret request using a previously generated add-secret request. The request
+    /// is rejected if the Ultravisor does not support its version. Only available on s390x.
     Add(AddSecretOpt),
 
     /// Lock the secret-store (s390x only).
     ///
     /// Lock the secret store (s390x only). After this command executed successfully, all
     /// subsequent add-secret requests will fail. Only available on s390x.
-    Lock,
+    Lock(LockSecretOpt),
 
     /// List all ultravisor secrets (s390x only).
     ///
@@ -478,10 +484,7 @@ mod test {
             vec!["pvsecret", "lock"],
             vec!["pvsecret", "version"],
             vec!["pvsecret", "list"],
-            #[cfg(target_arch = "s390x")]
             vec!["pvsecret", "add", "abc"],
-            #[cfg(not(target_arch = "s390x"))]
-            vec!["pvsecret", "add"],
             vec!["pvsecret", "create", "-k", "abc", "--hdr", "abc", "-o", "abc", "--no-verify", "meta"],
             vec!["pvsecret", "create", "-k", "abc", "--hdr", "abc", "-o", "abc", "--no-verify", "association", "name" ],
             vec!["pvsecret", "create", "-k", "abc", "--hdr", "abc", "-o", "abc", "--no-verify", "update-cck", "--secret", "abc"],
@@ -492,11 +495,8 @@ mod test {
                 "--root-ca", "tttt", "--cck", "cck", "--cuid-hex", "0x11223344556677889900aabbccddeeff", "--pcf", "0x123", "association", "name", "--stdout",
                 "--output-secret", "secret"],
             vec!["pvsecret", "create", "-k", "abc", "--hdr", "abc", "-o", "abc", "--no-verify", "association", "name", "--output-secret", "secret"],
-            #[cfg(target_arch = "s390x")]
             vec!["pvsecret", "list", "--format", "human"],
-            #[cfg(target_arch = "s390x")]
             vec!["pvsecret", "list", "--format", "yaml"],
-            #[cfg(target_arch = "s390x")]
             vec!["pvsecret", "list", "--format", "bin"],
         ];
         // Test for the minimal amount of flags to yield an invalid combination
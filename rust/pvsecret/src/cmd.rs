@@ -10,13 +10,9 @@ pub use verify::verify;
 
 pub const CMD_FN: &[&str] = &["+create", "+verify"];
 
-#[cfg(target_arch = "s390x")]
 mod add;
-#[cfg(target_arch = "s390x")]
 mod list;
-#[cfg(target_arch = "s390x")]
 mod lock;
-#[cfg(target_arch = "s390x")]
 mod retr;
 
 // Commands (directly) related to UVCs are only available on s389x
@@ -30,21 +26,25 @@ mod uv_cmd {
     pub const UV_CMD_FN: &[&str] = &["+add", "+lock", "+list"];
 }
 
+// Other architectures can only use an Ultravisor backend, like the software UV for testing
 #[cfg(not(target_arch = "s390x"))]
 mod uv_cmd {
-    use crate::cli::{AddSecretOpt, ListSecretOpt, RetrSecretOptions};
+    use crate::cli::{AddSecretOpt, ListSecretOpt, LockSecretOpt, RetrSecretOptions};
     use anyhow::{bail, Result};
     macro_rules! not_supp {
-        ($name: ident $( ,$opt: ty )?) => {
-            pub fn $name($(_: &$opt)?) -> Result<()> {
-                bail!("Command only available on s390x")
+        ($name: ident, $opt: ty) => {
+            pub fn $name(opt: &$opt) -> Result<()> {
+                if opt.uv_device.uv_backend.is_none() {
+                    bail!("Command only available on s390x")
+                }
+                super::$name::$name(opt)
             }
         };
     }
     not_supp!(add, AddSecretOpt);
     not_supp!(list, ListSecretOpt);
     not_supp!(retr, RetrSecretOptions);
-    not_supp!(lock);
+    not_supp!(lock, LockSecretOpt);
     pub const UV_CMD_FN: &[&str] = &[];
 }
 pub use uv_cmd::*;
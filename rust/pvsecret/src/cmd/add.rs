@@ -4,20 +4,29 @@
 
 use crate::{cli::AddSecretOpt, cmd::list::list_uvc};
 use anyhow::{bail, Context, Result};
-use log::warn;
+use log::{debug, warn};
 use pv::{
     secret::AddSecretRequest,
-    uv::{AddCmd, UvCmd, UvDevice},
+    uv::{AddCmd, UvCmd, UvQuery},
 };
 use utils::get_reader_from_cli_file_arg;
 
 /// Do an Add Secret UVC
 pub fn add(opt: &AddSecretOpt) -> Result<()> {
-    let uv = UvDevice::open()?;
+    let uv = opt.uv_device.open()?;
     let mut rd_in = get_reader_from_cli_file_arg(&opt.input)?;
     let mut cmd =
         AddCmd::new(&mut rd_in).context(format!("Processing input file {}", opt.input))?;
 
+    let version = AddSecretRequest::bin_version(cmd.data().unwrap())?;
+    match UvQuery::read() {
+        Ok(query) if query.supports_add_secret_req_version(version) == Some(false) => {
+            bail!("The Ultravisor does not support add-secret requests of version {version:#x}")
+        }
+        Ok(_) => {}
+        Err(err) => debug!("Cannot check the supported add-secret request versions: {err}"),
+    }
+
     if let Some(id) = AddSecretRequest::bin_id(cmd.data().unwrap())? {
         if list_uvc(&uv)?.iter().any(|e| e.id() == id.as_ref()) {
             warn!("There is already a secret in the secret store with that id.");
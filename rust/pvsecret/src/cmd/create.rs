@@ -12,6 +12,7 @@ use pv::{
         try_parse_u64, write,
     },
     request::{
+        load_private_key,
         openssl::pkey::{PKey, Private},
         BootHdrTags, ReqEncrCtx, Request, SymKeyType,
     },
@@ -90,6 +91,27 @@ fn read_private_key(buf: &[u8]) -> Result<PKey<Private>> {
         .map_err(Error::new)
 }
 
+/// Whether `key` starts with a URI scheme, e.g. `pkcs11:`.
+fn has_uri_scheme(key: &str) -> bool {
+    key.split_once(':').is_some_and(|(scheme, _)| {
+        scheme.len() > 1
+            && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+    })
+}
+
+/// Read the user-signing key from a PEM or DER file, or load it from an OpenSSL store URI.
+fn read_user_sign_key(key: &str) -> Result<PKey<Private>> {
+    if !Path::new(key).exists() && has_uri_scheme(key) {
+        return load_private_key(key).context("Cannot load the user-signing key");
+    }
+    let buf = read_file(key, "User-signing key")?;
+    read_private_key(&buf)
+        .with_context(|| format!("Cannot read '{key}' as private key from PEM or DER"))
+}
+
 /// Set-up the `add-secret request` from command-line arguments
 fn build_asrcb(opt: &CreateSecretOpt) -> Result<AddSecretRequest> {
     debug!("Build add-secret request");
@@ -159,12 +181,8 @@ fn build_asrcb(opt: &CreateSecretOpt) -> Result<AddSecretRequest> {
 
     let user_key = opt
         .user_sign_key
-        .as_ref()
-        .map(|p| read_file(p, "User-signing key"))
-        .transpose()?
-        .map(|buf| {
-            read_private_key(&buf).context("Cannot read {secret} as private key from PEM or DER")
-        })
+        .as_deref()
+        .map(read_user_sign_key)
         .transpose()?;
 
     if user_data.is_some() || user_key.is_some() {
@@ -310,4 +328,26 @@ mod test {
         let key = super::read_private_key(key);
         assert!(key.is_err());
     }
+
+    #[test]
+    fn has_uri_scheme() {
+        assert!(super::has_uri_scheme("pkcs11:token=hsm;object=sign"));
+        assert!(super::has_uri_scheme("file:/etc/key.pem"));
+        assert!(!super::has_uri_scheme("key.pem"));
+        assert!(!super::has_uri_scheme("C:\\key.pem"));
+        assert!(!super::has_uri_scheme("./pkcs11:key.pem"));
+    }
+
+    #[test]
+    fn read_user_sign_key() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../pv/tests/assets/keys/rsa2048key.pem"
+        );
+        for key in [path.to_string(), format!("file:{path}")] {
+            let key = super::read_user_sign_key(&key).unwrap();
+            assert_eq!(key.rsa().unwrap().size(), 256);
+        }
+        assert!(super::read_user_sign_key("file:/does/not/exist.pem").is_err());
+    }
 }
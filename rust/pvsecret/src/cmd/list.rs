@@ -34,7 +34,7 @@ pub fn list_uvc(uv: &UvDevice) -> Result<SecretList> {
 
 /// Do a List Secrets UVC and output the list in the requested format
 pub fn list(opt: &ListSecretOpt) -> Result<()> {
-    let uv = UvDevice::open()?;
+    let uv = opt.uv_device.open()?;
     let secret_list = list_uvc(&uv)?;
     let mut wr_out = get_writer_from_cli_file_arg(&opt.output)?;
 
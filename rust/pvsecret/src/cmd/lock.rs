@@ -4,11 +4,13 @@
 
 use anyhow::Result;
 use log::warn;
-use pv::uv::{LockCmd, UvDevice};
+use pv::uv::LockCmd;
+
+use crate::cli::LockSecretOpt;
 
 /// Do a Lock Secret Store UVC
-pub fn lock() -> Result<()> {
-    UvDevice::open()?.send_cmd(&mut LockCmd)?;
+pub fn lock(opt: &LockSecretOpt) -> Result<()> {
+    opt.uv_device.open()?.send_cmd(&mut LockCmd)?;
     warn!("Successfully locked secret store");
     Ok(())
 }
@@ -73,9 +73,8 @@ fn find_secret_by_id(secrets: &SecretList, id: &SecretId) -> Option<SecretEntry>
     secret.cloned()
 }
 
-fn retrieve(value: Value) -> Result<RetrievedSecret> {
-    let uv = UvDevice::open()?;
-    let secrets = list_uvc(&uv)?;
+fn retrieve(uv: &UvDevice, value: Value) -> Result<RetrievedSecret> {
+    let secrets = list_uvc(uv)?;
 
     let entry = match &value {
         Value::Id(id) => {
@@ -105,7 +104,8 @@ fn retrieve(value: Value) -> Result<RetrievedSecret> {
 
 pub fn retr(opt: &RetrSecretOptions) -> Result<()> {
     let mut output = get_writer_from_cli_file_arg(&opt.output)?;
-    let retr_secret = retrieve(opt.try_into()?)
+    let uv = opt.uv_device.open()?;
+    let retr_secret = retrieve(&uv, opt.try_into()?)
         .context("Could not retrieve the secret from the UV secret store.")?;
 
     let out_data = match opt.outform {
@@ -41,9 +41,12 @@ fn main() -> ExitCode {
     let res = match &cli.cmd {
         Command::Add(opt) => cmd::add(opt),
         Command::List(opt) => cmd::list(opt),
-        Command::Lock => cmd::lock(),
+        Command::Lock(opt) => cmd::lock(opt),
         Command::Create(opt) => cmd::create(opt),
-        Command::Version => Ok(print_version!("2024", log_level; FEATURES.concat())),
+        Command::Version => {
+            print_version!("2024", log_level; FEATURES.concat());
+            Ok(())
+        }
         Command::Verify(opt) => cmd::verify(opt),
         Command::Retrieve(opt) => cmd::retr(opt),
     };
@@ -0,0 +1,134 @@
+// SPDX-License-Identifier: MIT
+//
+// Copyright IBM Corp. 2025
+
+#![allow(missing_docs)]
+use std::{
+    fs,
+    io::Cursor,
+    os::unix::net::UnixListener,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+    thread,
+};
+
+use pv::{
+    request::openssl::pkey::PKey,
+    secret::GuestSecret,
+    test_utils::{create_test_se_hdr, get_cert_asset, get_cert_asset_path},
+    testing::{SoftUv, SoftUvBackend},
+    uv::{RemoteBackend, UvDevice},
+};
+
+fn tmp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pvsecret_{name}_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Serves the UV calls of all connections to the Unix socket `path` with the software UV.
+fn serve(path: &Path, backend: SoftUvBackend) {
+    let listener = UnixListener::bind(path).unwrap();
+    thread::spawn(move || {
+        let uv = UvDevice::with_backend(backend);
+        for stream in listener.incoming() {
+            RemoteBackend::serve(stream.unwrap(), &uv).unwrap();
+        }
+    });
+}
+
+fn pvsecret(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_pvsecret"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn assert_success(out: &Output) {
+    assert!(
+        out.status.success(),
+        "pvsecret failed: {}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+}
+
+#[test]
+fn secret_store_lifecycle() {
+    let dir = tmp_dir("lifecycle");
+    let hdr = create_test_se_hdr(&["host.crt"]);
+    fs::write(dir.join("se.hdr"), &hdr).unwrap();
+    fs::write(dir.join("secret"), b"very secret").unwrap();
+    let host_key = PKey::private_key_from_pem(&get_cert_asset("host.key")).unwrap();
+    let backend =
+        SoftUvBackend::new(SoftUv::from_se_image(vec![host_key], &mut Cursor::new(&hdr)).unwrap());
+    let socket = dir.join("uv.sock");
+    serve(&socket, backend.clone());
+    let uv_backend = format!("--uv-backend=remote:{}", socket.display());
+
+    let hkd = get_cert_asset_path("host.crt");
+    let hkd = hkd.to_str().unwrap();
+    let create = |name: &str, req: &str| {
+        pvsecret(
+            &dir,
+            &[
+                "create",
+                "-k",
+                hkd,
+                "--no-verify",
+                "--hdr",
+                "se.hdr",
+                "-o",
+                req,
+                "retrievable",
+                name,
+                "--secret",
+                "secret",
+                "--type",
+                "plain",
+            ],
+        )
+    };
+    assert_success(&create("plain", "plain.bin"));
+    assert_success(&create("other", "other.bin"));
+
+    assert_success(&pvsecret(&dir, &["add", "plain.bin", &uv_backend]));
+    assert_eq!(backend.soft_uv().list().len(), 1);
+    // duplicated IDs are refused by pvsecret before calling the UV
+    assert!(!pvsecret(&dir, &["add", "plain.bin", &uv_backend])
+        .status
+        .success());
+
+    let list = pvsecret(&dir, &["list", "--format", "yaml", &uv_backend]);
+    assert_success(&list);
+    let id = GuestSecret::name_to_id("plain").unwrap();
+    assert!(String::from_utf8_lossy(&list.stdout).contains(&format!("id: {id:#x}")));
+
+    let retr = pvsecret(
+        &dir,
+        &[
+            "retrieve",
+            "plain.yaml",
+            "--outform",
+            "bin",
+            "-o",
+            "retrieved",
+            &uv_backend,
+        ],
+    );
+    assert_success(&retr);
+    assert_eq!(fs::read(dir.join("retrieved")).unwrap(), b"very secret");
+
+    assert_success(&pvsecret(&dir, &["lock", &uv_backend]));
+    assert!(backend.soft_uv().locked());
+    assert!(!pvsecret(&dir, &["add", "other.bin", &uv_backend])
+        .status
+        .success());
+    assert_eq!(backend.soft_uv().list().len(), 1);
+
+    // without an Ultravisor backend
+    #[cfg(not(target_arch = "s390x"))]
+    assert!(!pvsecret(&dir, &["add", "other.bin"]).status.success());
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -9,14 +9,70 @@ use pv::{
     misc::{create_file, open_file, read_certs},
     request::{
         openssl::pkey::{PKey, Public},
-        HkdVerifier,
+        CertVerifier, CrlFetcher, DirCrlFetcher, HkdVerifier, NoCrlFetcher,
     },
+    testing::{SoftUv, SoftUvBackend},
+    uv::{UvBackendSpec, UvDevice},
     Error, Result,
 };
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 
+/// Parses a UTC point in time into seconds since the Epoch.
+///
+/// Accepts `YYYY-MM-DD`, `YYYY-MM-DDTHH:MM:SS` (optionally followed by `Z`), and `@SECONDS`.
+fn parse_verify_time(s: &str) -> std::result::Result<i64, String> {
+    let invalid = || format!("'{s}' is not a valid time");
+    if let Some(secs) = s.strip_prefix('@') {
+        return secs.parse().map_err(|_| invalid());
+    }
+
+    let utc = s.strip_suffix('Z').unwrap_or(s);
+    let (date, time) = utc.split_once('T').unwrap_or((utc, "00:00:00"));
+    let fields = |v: &str, sep: char| -> std::result::Result<Vec<i64>, String> {
+        let v = v
+            .split(sep)
+            .map(|f| match f.bytes().all(|b| b.is_ascii_digit()) {
+                true => f.parse().map_err(|_| invalid()),
+                false => Err(invalid()),
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        match v.len() {
+            3 => Ok(v),
+            _ => Err(invalid()),
+        }
+    };
+    let (date, time) = (fields(date, '-')?, fields(time, ':')?);
+    let (year, month, day) = (date[0], date[1], date[2]);
+    // X.509 times have four-digit years; also keeps the calculation below from overflowing
+    if year > 9999 {
+        return Err(invalid());
+    }
+
+    let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let days_in_month = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return Err(invalid()),
+    };
+    if !(1..=days_in_month).contains(&day) || time[0] > 23 || time[1] > 59 || time[2] > 59 {
+        return Err(invalid());
+    }
+
+    // days since 1970-01-01 in the proleptic Gregorian calendar
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+
+    Ok(days * 86400 + time[0] * 3600 + time[1] * 60 + time[2])
+}
+
 /// CLI Argument collection for handling host-keys, IBM signing keys, and certificates.
 #[derive(Args, Debug, Clone, PartialEq, Eq, Default)]
 #[command(
@@ -78,6 +134,35 @@ pub struct CertificateOptions {
     #[arg(long, requires("certs"))]
     pub offline: bool,
 
+    /// Use DIR as a cache for downloaded CRLs.
+    ///
+    /// Downloaded CRLs are stored in DIR and reused until they expire. With '--offline', DIR
+    /// is used as a bundle of pre-fetched CRLs instead; no attempt to download CRLs is made.
+    #[arg(
+        long,
+        requires("certs"),
+        value_name = "DIR",
+        value_hint = ValueHint::DirPath,
+    )]
+    pub crl_cache: Option<PathBuf>,
+
+    /// Verify the host-key documents at TIME instead of now.
+    ///
+    /// The chain of trust, the CRLs, and the host-key documents must be valid at TIME. Specify
+    /// TIME in UTC as 'YYYY-MM-DD', as 'YYYY-MM-DDTHH:MM:SS', or as seconds since the Epoch
+    /// prefixed with '@'. Use this option to check whether host-key documents were valid and
+    /// not revoked at a past point in time. Requires '--offline', as CRLs downloaded now are not
+    /// valid at a past TIME. Provide the CRLs that were valid at TIME with '--crl' or
+    /// '--crl-cache'.
+    #[arg(
+        long,
+        requires("certs"),
+        requires("offline"),
+        value_name = "TIME",
+        value_parser = parse_verify_time,
+    )]
+    pub verify_time: Option<i64>,
+
     /// Use FILE as the root-CA certificate for the verification.
     ///
     /// If omitted, the system wide-root CAs installed on the system are used.
@@ -95,7 +180,7 @@ impl CertificateOptions {
     ///
     /// This function will return an error if [`crate::request::HkdVerifier`] cannot be created.
     fn verifier(&self, protectee: &'static str) -> Result<Box<dyn HkdVerifier>> {
-        use pv::request::{CertVerifier, NoVerifyHkd};
+        use pv::request::NoVerifyHkd;
         match self.no_verify {
             true => {
                 log::warn!(
@@ -103,15 +188,37 @@ impl CertificateOptions {
                 );
                 Ok(Box::new(NoVerifyHkd))
             }
-            false => Ok(Box::new(CertVerifier::new(
-                &self.certs,
-                &self.crls,
-                self.root_ca.as_ref(),
-                self.offline,
-            )?)),
+            false => Ok(Box::new(self.cert_verifier()?)),
         }
     }
 
+    /// Returns the [`CertVerifier`] for the chain of trust given by the CLI options.
+    ///
+    /// Ignores '--no-verify'.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the chain of trust cannot be established.
+    pub fn cert_verifier(&self) -> Result<CertVerifier> {
+        let fetcher: Box<dyn CrlFetcher> = match (&self.crl_cache, self.offline) {
+            (Some(dir), true) => Box::new(DirCrlFetcher::new(dir)?),
+            (None, true) => Box::new(NoCrlFetcher),
+            #[cfg(feature = "network")]
+            (Some(dir), false) => Box::new(pv::request::HttpCrlFetcher::with_cache(dir)?),
+            #[cfg(feature = "network")]
+            (None, false) => Box::new(pv::request::HttpCrlFetcher::new()),
+            #[cfg(not(feature = "network"))]
+            (_, false) => return Err(Error::NoNetwork("download CRLs")),
+        };
+        CertVerifier::with_fetcher(
+            &self.certs,
+            &self.crls,
+            self.root_ca.as_ref(),
+            fetcher,
+            self.verify_time,
+        )
+    }
+
     /// Read the host-keys specified and verifies them if required
     ///
     /// - `protectee`: what you want to create. e.g. add-secret request or SE-image
@@ -202,6 +309,33 @@ where
     ExitCode::FAILURE
 }
 
+/// CLI Argument collection for selecting the backend of the uvdevice.
+#[derive(Args, Debug, Clone, PartialEq, Eq, Default)]
+pub struct UvDeviceOptions {
+    /// Use SPEC as backend for Ultravisor calls instead of /dev/uv.
+    ///
+    /// SPEC is one of 'device:PATH', 'record:DIR', 'replay:DIR', 'remote:SOCKET', or 'soft:DIR'.
+    /// Recordings contain all exchanged data in plain, including retrieved secrets.
+    /// 'soft:DIR' emulates the Ultravisor for the SE-image 'DIR/se.img' with the host-keys
+    /// 'DIR/*.key'; its secret store only lives as long as the command.
+    /// Hidden in user documentation.
+    #[arg(long, value_name = "SPEC", hide = true)]
+    pub uv_backend: Option<UvBackendSpec>,
+}
+
+impl UvDeviceOptions {
+    /// Open the uvdevice with the selected backend.
+    pub fn open(&self) -> Result<UvDevice> {
+        Ok(match &self.uv_backend {
+            Some(UvBackendSpec::Soft(dir)) => {
+                UvDevice::with_backend(SoftUvBackend::new(SoftUv::from_dir(dir)?))
+            }
+            Some(spec) => spec.open()?,
+            None => UvDevice::open()?,
+        })
+    }
+}
+
 #[derive(Args, Debug, Clone, Default)]
 pub struct VerbosityOptions {
     #[arg(
@@ -288,7 +422,12 @@ mod test {
     fn cli_args() {
         //Verify only that some arguments are optional, we do not want to test clap, only the
         //configuration
-        let valid_args = [vec!["pgr", "-k", "hkd.crt", "--no-verify"], vec!["pgr", "-k", "hkd.crt", "--crt", "abc.crt"]];
+        let valid_args = [
+            vec!["pgr", "-k", "hkd.crt", "--no-verify"],
+            vec!["pgr", "-k", "hkd.crt", "--crt", "abc.crt"],
+            vec!["pgr", "-k", "hkd.crt", "--crt", "abc.crt", "--offline", "--crl-cache", "crls"],
+            vec!["pgr", "-k", "hkd.crt", "--crt", "abc.crt", "--offline", "--verify-time", "2024-06-01"],
+        ];
         // Test for the minimal amount of flags to yield an invalid combination
         let invalid_args = [
             vec!["pgr", "-k", "hkd.crt"],
@@ -297,6 +436,10 @@ mod test {
             vec!["pgr", "--no-verify", "--crt", "abc.crt", "--crl", "abc.crl"],
             vec!["pgr", "--no-verify", "--crt", "abc.crt", "--root-ca", "root.crt"],
             vec!["pgr", "--offline"],
+            vec!["pgr", "--crl-cache", "crls"],
+            vec!["pgr", "-k", "hkd.crt", "--crt", "abc.crt", "--offline", "--verify-time", "yesterday"],
+            vec!["pgr", "-k", "hkd.crt", "--crt", "abc.crt", "--verify-time", "2024-06-01"],
+            vec!["pgr", "--verify-time", "@0"],
             vec!["pgr", "--crl", "abc.crl"],
             vec!["pgr", "--root-ca", "root.crt"],
         ];
@@ -316,4 +459,32 @@ mod test {
             assert!(res.is_err());
         }
     }
+
+    #[test]
+    fn verify_time() {
+        assert_eq!(parse_verify_time("1970-01-01"), Ok(0));
+        assert_eq!(parse_verify_time("@1700000000"), Ok(1_700_000_000));
+        assert_eq!(parse_verify_time("2000-03-01T12:00:00Z"), Ok(951_912_000));
+        assert_eq!(parse_verify_time("2024-02-29T23:59:59"), Ok(1_709_251_199));
+        assert_eq!(parse_verify_time("1969-12-31T23:59:59Z"), Ok(-1));
+        assert_eq!(
+            parse_verify_time("9999-12-31T23:59:59Z"),
+            Ok(253_402_300_799)
+        );
+
+        for inv in [
+            "2023-02-29",
+            "2024-13-01",
+            "2024-01-01T24:00:00",
+            "2024-01-01T12:00",
+            "2024-1-+1",
+            "10000-01-01",
+            "900000000000000000-01-01",
+            "99999999999999999999-01-01",
+            "@now",
+            "",
+        ] {
+            assert!(parse_verify_time(inv).is_err(), "{inv}");
+        }
+    }
 }